@@ -17,8 +17,15 @@ CREATE TABLE IF NOT EXISTS photos (
 	creation_datetime		DATETIME,
 	format					TEXT,
 	orientation				TEXT,
+	-- Dimensions once the orientation is applied
+	display_height			INTEGER,
+	display_width			INTEGER,
 	rating					INTEGER DEFAULT 0,
 	starred					INTEGER DEFAULT 0,
+	-- Name of the color, like "red"
+	color_label				TEXT,
+	-- 1 when the photo is picked, -1 when it is rejected
+	flag					INTEGER DEFAULT 0,
 	-- Image metadata
 	make					TEXT,
 	model					TEXT,
@@ -32,10 +39,22 @@ CREATE TABLE IF NOT EXISTS photos (
 	metering_mode			INTEGER,
 	title					TEXT,
 	comment					TEXT,
+	perceptual_hash			INTEGER,
+	-- Where the photo was taken, in degrees, negative in the south and in the west
+	latitude				REAL,
+	longitude				REAL,
+	-- Non-destructive edits applied to the original, like "rotate 1;exposure 0.5"
+	edits					TEXT,
+	-- When the photo was moved to the trash, NULL for the photos of the library
+	deleted_datetime		DATETIME,
 	-- Image position: TODO: Research this
 	-- Key configuration
 	PRIMARY KEY(id AUTOINCREMENT)
 );
+-- Index used to find duplicates at import.
+CREATE INDEX IF NOT EXISTS photos_hash ON photos(hash);
+-- Index used to list the trash.
+CREATE INDEX IF NOT EXISTS photos_deleted ON photos(deleted_datetime);
 
 -- Table where each row represents an album.
 CREATE TABLE IF NOT EXISTS albums (
@@ -45,39 +64,111 @@ CREATE TABLE IF NOT EXISTS albums (
 	creation_datetime		DATETIME,
 	modification_datetime	DATETIME,
 	collection				INTEGER,
+	-- Text query of a smart album, like "tag:beach rating>=4". NULL when the photos are chosen one by one.
+	query					TEXT,
+	-- Photo shown for the album, NULL shows its first photo.
+	cover					INTEGER,
+	-- How the photos of the album are sorted, like "capture_date". NULL keeps the order chosen by the user.
+	sort					TEXT,
 	FOREIGN KEY(collection) REFERENCES collections(id),
+	FOREIGN KEY(cover) REFERENCES photos(id) ON DELETE SET NULL,
 	PRIMARY KEY(id AUTOINCREMENT)
 );
 
--- Link table between photos and albums.
+-- Link table between photos and albums, the links go away with the photo or the album.
+-- A photo is only once in an album, at the position chosen by the user.
 CREATE TABLE IF NOT EXISTS photos_albums_map (
 	containing_album		INTEGER NOT NULL,
 	contained_photo			INTEGER NOT NULL,
-	FOREIGN KEY(contained_photo) REFERENCES photos(id),
-	FOREIGN KEY(containing_album) REFERENCES albums(id)
+	position				INTEGER NOT NULL DEFAULT 0,
+	FOREIGN KEY(contained_photo) REFERENCES photos(id) ON DELETE CASCADE,
+	FOREIGN KEY(containing_album) REFERENCES albums(id) ON DELETE CASCADE,
+	UNIQUE(containing_album, contained_photo)
 );
 
 -- Table where each row represents a collection. (The Albums table references the Collection, not the other way around.)
+-- Collections without parent are at the root of the collections directory.
 CREATE TABLE IF NOT EXISTS collections (
 	id						INTEGER NOT NULL UNIQUE,
-	name					TEXT NOT NULL UNIQUE,
+	name					TEXT NOT NULL,
 	comment					TEXT,
 	creation_datetime		DATETIME,
 	modification_datetime	DATETIME,
+	parent					INTEGER,
+	FOREIGN KEY(parent) REFERENCES collections(id),
 	PRIMARY KEY(id AUTOINCREMENT)
 );
+-- Two collections with the same parent cannot have the same name.
+CREATE UNIQUE INDEX IF NOT EXISTS collections_name_parent ON collections(name, IFNULL(parent, 0));
 
--- Table where each row represents a tag.
+-- Table where each row represents a tag. Tags without parent are at the root of the hierarchy.
 CREATE TABLE IF NOT EXISTS tags (
 	id						INTEGER NOT NULL UNIQUE,
-	name					TEXT NOT NULL UNIQUE,
+	name					TEXT NOT NULL,
+	parent					INTEGER,
+	FOREIGN KEY(parent) REFERENCES tags(id),
 	PRIMARY KEY(id AUTOINCREMENT)
 );
--- Link table between photos and tags.
+-- Two tags with the same parent cannot have the same name.
+CREATE UNIQUE INDEX IF NOT EXISTS tags_name_parent ON tags(name, IFNULL(parent, 0));
+-- Full path of every tag, like "Places/France/Paris".
+CREATE VIEW IF NOT EXISTS tags_paths AS
+	WITH RECURSIVE paths(id, path) AS (
+		SELECT id, name FROM tags WHERE parent IS NULL
+		UNION ALL
+		SELECT tags.id, paths.path || '/' || tags.name FROM tags INNER JOIN paths ON tags.parent = paths.id
+	)
+	SELECT id, path FROM paths;
+-- Link table between photos and tags, the links go away with the photo or the tag.
 CREATE TABLE IF NOT EXISTS photos_tags_map (
 	containing_tag			INTEGER NOT NULL,
 	contained_photo			INTEGER NOT NULL,
-	FOREIGN KEY(contained_photo) REFERENCES photos(id),
-	FOREIGN KEY(containing_tag) REFERENCES tags(id)
+	FOREIGN KEY(contained_photo) REFERENCES photos(id) ON DELETE CASCADE,
+	FOREIGN KEY(containing_tag) REFERENCES tags(id) ON DELETE CASCADE
 );
 
+
+-- Full-text index of the names, titles and comments of the photos, albums, collections and tags.
+-- The rowid tells which element a row is: the id of the element times 4, plus 0 for a photo,
+-- 1 for an album, 2 for a collection and 3 for a tag. The triggers keep it in sync with the tables.
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(name, title, comment, tokenize = 'unicode61 remove_diacritics 2');
+
+CREATE TRIGGER IF NOT EXISTS photos_search_insert AFTER INSERT ON photos BEGIN
+	INSERT INTO search_index (rowid, name, title, comment) VALUES (new.id * 4, new.filename, new.title, new.comment);
+END;
+CREATE TRIGGER IF NOT EXISTS photos_search_update AFTER UPDATE OF filename, title, comment ON photos BEGIN
+	UPDATE search_index SET name = new.filename, title = new.title, comment = new.comment WHERE rowid = old.id * 4;
+END;
+CREATE TRIGGER IF NOT EXISTS photos_search_delete AFTER DELETE ON photos BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4;
+END;
+
+CREATE TRIGGER IF NOT EXISTS albums_search_insert AFTER INSERT ON albums BEGIN
+	INSERT INTO search_index (rowid, name, comment) VALUES (new.id * 4 + 1, new.name, new.comment);
+END;
+CREATE TRIGGER IF NOT EXISTS albums_search_update AFTER UPDATE OF name, comment ON albums BEGIN
+	UPDATE search_index SET name = new.name, comment = new.comment WHERE rowid = old.id * 4 + 1;
+END;
+CREATE TRIGGER IF NOT EXISTS albums_search_delete AFTER DELETE ON albums BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS collections_search_insert AFTER INSERT ON collections BEGIN
+	INSERT INTO search_index (rowid, name, comment) VALUES (new.id * 4 + 2, new.name, new.comment);
+END;
+CREATE TRIGGER IF NOT EXISTS collections_search_update AFTER UPDATE OF name, comment ON collections BEGIN
+	UPDATE search_index SET name = new.name, comment = new.comment WHERE rowid = old.id * 4 + 2;
+END;
+CREATE TRIGGER IF NOT EXISTS collections_search_delete AFTER DELETE ON collections BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 2;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tags_search_insert AFTER INSERT ON tags BEGIN
+	INSERT INTO search_index (rowid, name) VALUES (new.id * 4 + 3, new.name);
+END;
+CREATE TRIGGER IF NOT EXISTS tags_search_update AFTER UPDATE OF name ON tags BEGIN
+	UPDATE search_index SET name = new.name WHERE rowid = old.id * 4 + 3;
+END;
+CREATE TRIGGER IF NOT EXISTS tags_search_delete AFTER DELETE ON tags BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 3;
+END;
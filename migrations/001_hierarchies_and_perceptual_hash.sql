@@ -0,0 +1,30 @@
+-- Version 1: nested collections, hierarchical tags and perceptual hashes.
+
+ALTER TABLE photos ADD COLUMN perceptual_hash INTEGER;
+-- Index used to find duplicates at import.
+CREATE INDEX IF NOT EXISTS photos_hash ON photos(hash);
+
+-- Collections without parent are at the root of the collections directory.
+ALTER TABLE collections ADD COLUMN parent INTEGER REFERENCES collections(id);
+
+-- Tag names are only unique among the children of a tag: the table is rebuilt without the UNIQUE constraint.
+CREATE TABLE tags_new (
+	id						INTEGER NOT NULL UNIQUE,
+	name					TEXT NOT NULL,
+	parent					INTEGER,
+	FOREIGN KEY(parent) REFERENCES tags(id),
+	PRIMARY KEY(id AUTOINCREMENT)
+);
+INSERT INTO tags_new (id, name) SELECT id, name FROM tags;
+DROP TABLE tags;
+ALTER TABLE tags_new RENAME TO tags;
+-- Two tags with the same parent cannot have the same name.
+CREATE UNIQUE INDEX IF NOT EXISTS tags_name_parent ON tags(name, IFNULL(parent, 0));
+-- Full path of every tag, like "Places/France/Paris".
+CREATE VIEW IF NOT EXISTS tags_paths AS
+	WITH RECURSIVE paths(id, path) AS (
+		SELECT id, name FROM tags WHERE parent IS NULL
+		UNION ALL
+		SELECT tags.id, paths.path || '/' || tags.name FROM tags INNER JOIN paths ON tags.parent = paths.id
+	)
+	SELECT id, path FROM paths;
@@ -0,0 +1,8 @@
+-- Version 2: dimensions of the photos once their EXIF orientation is applied.
+
+ALTER TABLE photos ADD COLUMN display_height INTEGER;
+ALTER TABLE photos ADD COLUMN display_width INTEGER;
+-- Orientations 5 to 8 turn the picture by a quarter, its width becomes its height.
+UPDATE photos SET
+	display_height = CASE WHEN orientation IN ('5', '6', '7', '8') THEN width ELSE height END,
+	display_width = CASE WHEN orientation IN ('5', '6', '7', '8') THEN height ELSE width END;
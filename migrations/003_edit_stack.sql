@@ -0,0 +1,4 @@
+-- Version 3: non-destructive edits of the photos.
+
+-- The edits applied to the original, in order, like "rotate 1;exposure 0.5". NULL when the photo is not edited.
+ALTER TABLE photos ADD COLUMN edits TEXT;
@@ -0,0 +1,6 @@
+-- Version 4: color labels and pick/reject flags of the photos.
+
+-- Name of the color, like "red". NULL when the photo has no label.
+ALTER TABLE photos ADD COLUMN color_label TEXT;
+-- 1 when the photo is picked, -1 when it is rejected.
+ALTER TABLE photos ADD COLUMN flag INTEGER DEFAULT 0;
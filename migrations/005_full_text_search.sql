@@ -0,0 +1,52 @@
+-- Version 5: full-text search.
+
+-- Full-text index of the names, titles and comments of the photos, albums, collections and tags.
+-- The rowid tells which element a row is: the id of the element times 4, plus 0 for a photo,
+-- 1 for an album, 2 for a collection and 3 for a tag. The triggers keep it in sync with the tables.
+CREATE VIRTUAL TABLE search_index USING fts5(name, title, comment, tokenize = 'unicode61 remove_diacritics 2');
+
+CREATE TRIGGER photos_search_insert AFTER INSERT ON photos BEGIN
+	INSERT INTO search_index (rowid, name, title, comment) VALUES (new.id * 4, new.filename, new.title, new.comment);
+END;
+CREATE TRIGGER photos_search_update AFTER UPDATE OF filename, title, comment ON photos BEGIN
+	UPDATE search_index SET name = new.filename, title = new.title, comment = new.comment WHERE rowid = old.id * 4;
+END;
+CREATE TRIGGER photos_search_delete AFTER DELETE ON photos BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4;
+END;
+
+CREATE TRIGGER albums_search_insert AFTER INSERT ON albums BEGIN
+	INSERT INTO search_index (rowid, name, comment) VALUES (new.id * 4 + 1, new.name, new.comment);
+END;
+CREATE TRIGGER albums_search_update AFTER UPDATE OF name, comment ON albums BEGIN
+	UPDATE search_index SET name = new.name, comment = new.comment WHERE rowid = old.id * 4 + 1;
+END;
+CREATE TRIGGER albums_search_delete AFTER DELETE ON albums BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 1;
+END;
+
+CREATE TRIGGER collections_search_insert AFTER INSERT ON collections BEGIN
+	INSERT INTO search_index (rowid, name, comment) VALUES (new.id * 4 + 2, new.name, new.comment);
+END;
+CREATE TRIGGER collections_search_update AFTER UPDATE OF name, comment ON collections BEGIN
+	UPDATE search_index SET name = new.name, comment = new.comment WHERE rowid = old.id * 4 + 2;
+END;
+CREATE TRIGGER collections_search_delete AFTER DELETE ON collections BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 2;
+END;
+
+CREATE TRIGGER tags_search_insert AFTER INSERT ON tags BEGIN
+	INSERT INTO search_index (rowid, name) VALUES (new.id * 4 + 3, new.name);
+END;
+CREATE TRIGGER tags_search_update AFTER UPDATE OF name ON tags BEGIN
+	UPDATE search_index SET name = new.name WHERE rowid = old.id * 4 + 3;
+END;
+CREATE TRIGGER tags_search_delete AFTER DELETE ON tags BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 3;
+END;
+
+-- Index of the elements created before.
+INSERT INTO search_index (rowid, name, title, comment) SELECT id * 4, filename, title, comment FROM photos;
+INSERT INTO search_index (rowid, name, comment) SELECT id * 4 + 1, name, comment FROM albums;
+INSERT INTO search_index (rowid, name, comment) SELECT id * 4 + 2, name, comment FROM collections;
+INSERT INTO search_index (rowid, name) SELECT id * 4 + 3, name FROM tags;
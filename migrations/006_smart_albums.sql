@@ -0,0 +1,7 @@
+-- Version 6: smart albums and GPS coordinates of the photos.
+
+-- Text query of a smart album, like "tag:beach rating>=4". NULL for an album whose photos are chosen one by one.
+ALTER TABLE albums ADD COLUMN query TEXT;
+-- Where the photo was taken, in degrees, negative in the south and in the west.
+ALTER TABLE photos ADD COLUMN latitude REAL;
+ALTER TABLE photos ADD COLUMN longitude REAL;
@@ -0,0 +1,5 @@
+-- Version 7: trash of the deleted photos.
+
+-- When the photo was moved to the trash, NULL for the photos of the library.
+ALTER TABLE photos ADD COLUMN deleted_datetime DATETIME;
+CREATE INDEX IF NOT EXISTS photos_deleted ON photos(deleted_datetime);
@@ -0,0 +1,27 @@
+-- Version 8: the links of a photo to its albums and its tags are deleted with the photo, the album or the tag.
+
+-- Foreign key actions cannot be changed on an existing table: the link tables are rebuilt,
+-- without the rows that already point to nothing. The rowids are kept, they give the order of the albums.
+CREATE TABLE photos_albums_map_new (
+	containing_album		INTEGER NOT NULL,
+	contained_photo			INTEGER NOT NULL,
+	FOREIGN KEY(contained_photo) REFERENCES photos(id) ON DELETE CASCADE,
+	FOREIGN KEY(containing_album) REFERENCES albums(id) ON DELETE CASCADE
+);
+INSERT INTO photos_albums_map_new (rowid, containing_album, contained_photo)
+	SELECT rowid, containing_album, contained_photo FROM photos_albums_map
+	WHERE containing_album IN (SELECT id FROM albums) AND contained_photo IN (SELECT id FROM photos);
+DROP TABLE photos_albums_map;
+ALTER TABLE photos_albums_map_new RENAME TO photos_albums_map;
+
+CREATE TABLE photos_tags_map_new (
+	containing_tag			INTEGER NOT NULL,
+	contained_photo			INTEGER NOT NULL,
+	FOREIGN KEY(contained_photo) REFERENCES photos(id) ON DELETE CASCADE,
+	FOREIGN KEY(containing_tag) REFERENCES tags(id) ON DELETE CASCADE
+);
+INSERT INTO photos_tags_map_new (rowid, containing_tag, contained_photo)
+	SELECT rowid, containing_tag, contained_photo FROM photos_tags_map
+	WHERE containing_tag IN (SELECT id FROM tags) AND contained_photo IN (SELECT id FROM photos);
+DROP TABLE photos_tags_map;
+ALTER TABLE photos_tags_map_new RENAME TO photos_tags_map;
@@ -0,0 +1,24 @@
+-- Version 9: order of the photos in the albums, cover photo and sort mode of the albums.
+
+-- A photo is only once in an album: the link table is rebuilt without the duplicated rows,
+-- the photos keep the order they were assigned in.
+CREATE TABLE photos_albums_map_new (
+	containing_album		INTEGER NOT NULL,
+	contained_photo			INTEGER NOT NULL,
+	position				INTEGER NOT NULL DEFAULT 0,
+	FOREIGN KEY(contained_photo) REFERENCES photos(id) ON DELETE CASCADE,
+	FOREIGN KEY(containing_album) REFERENCES albums(id) ON DELETE CASCADE,
+	UNIQUE(containing_album, contained_photo)
+);
+INSERT INTO photos_albums_map_new (containing_album, contained_photo, position)
+	SELECT containing_album, contained_photo, ROW_NUMBER() OVER (PARTITION BY containing_album ORDER BY rowid) - 1
+	FROM photos_albums_map
+	WHERE rowid IN (SELECT MIN(rowid) FROM photos_albums_map GROUP BY containing_album, contained_photo)
+	ORDER BY rowid;
+DROP TABLE photos_albums_map;
+ALTER TABLE photos_albums_map_new RENAME TO photos_albums_map;
+
+-- Photo shown for the album, NULL shows its first photo.
+ALTER TABLE albums ADD COLUMN cover INTEGER REFERENCES photos(id) ON DELETE SET NULL;
+-- How the photos of the album are sorted, like "capture_date". NULL keeps the order chosen by the user.
+ALTER TABLE albums ADD COLUMN sort TEXT;
@@ -0,0 +1,30 @@
+-- Version 10: collection names are only unique among the children of a collection, like tags.
+
+-- The UNIQUE constraint cannot be dropped from an existing table: the table is rebuilt without it.
+CREATE TABLE collections_new (
+	id						INTEGER NOT NULL UNIQUE,
+	name					TEXT NOT NULL,
+	comment					TEXT,
+	creation_datetime		DATETIME,
+	modification_datetime	DATETIME,
+	parent					INTEGER,
+	FOREIGN KEY(parent) REFERENCES collections(id),
+	PRIMARY KEY(id AUTOINCREMENT)
+);
+INSERT INTO collections_new (id, name, comment, creation_datetime, modification_datetime, parent)
+	SELECT id, name, comment, creation_datetime, modification_datetime, parent FROM collections;
+DROP TABLE collections;
+ALTER TABLE collections_new RENAME TO collections;
+-- Two collections with the same parent cannot have the same name.
+CREATE UNIQUE INDEX IF NOT EXISTS collections_name_parent ON collections(name, IFNULL(parent, 0));
+
+-- The search triggers are dropped with the table.
+CREATE TRIGGER collections_search_insert AFTER INSERT ON collections BEGIN
+	INSERT INTO search_index (rowid, name, comment) VALUES (new.id * 4 + 2, new.name, new.comment);
+END;
+CREATE TRIGGER collections_search_update AFTER UPDATE OF name, comment ON collections BEGIN
+	UPDATE search_index SET name = new.name, comment = new.comment WHERE rowid = old.id * 4 + 2;
+END;
+CREATE TRIGGER collections_search_delete AFTER DELETE ON collections BEGIN
+	DELETE FROM search_index WHERE rowid = old.id * 4 + 2;
+END;
@@ -1,9 +1,83 @@
 use crate::Library;
 use crate::OsplError;
 use crate::Error;
-use crate::Database;
 use crate::Album;
 use crate::Photo;
+use crate::transaction::LibraryTransaction;
+use crate::api::query::{PhotoQuery, PhotoSort, SortOrder};
+use crate::api::library::DeletePolicy;
+
+/// How the photos of an album are sorted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AlbumSort
+{
+	/// In the order chosen with [Library::move_photo_before()] and [Library::move_photo_after()],
+	/// the photos of a smart album are in the order of its query
+	#[default]
+	Manual,
+	/// By the date the photos were taken, photos without a date are last
+	CaptureDate,
+	ImportDate,
+	Filename,
+}
+
+impl AlbumSort
+{
+	pub const ALL: [AlbumSort; 4] = [AlbumSort::Manual, AlbumSort::CaptureDate, AlbumSort::ImportDate, AlbumSort::Filename];
+
+	/// Name of the sort mode, the way it is stored in the database
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			AlbumSort::Manual => "manual",
+			AlbumSort::CaptureDate => "capture_date",
+			AlbumSort::ImportDate => "import_date",
+			AlbumSort::Filename => "filename",
+		}
+	}
+
+	/// Value of the `sort` column of the albums table, None for the manual order
+	pub(crate) fn to_setting(self) -> Option<&'static str>
+	{
+		match self
+		{
+			AlbumSort::Manual => None,
+			sort => Some(sort.name()),
+		}
+	}
+
+	pub(crate) fn from_setting(value: Option<&str>) -> AlbumSort
+	{
+		AlbumSort::ALL.into_iter().find(|sort| Some(sort.name()) == value).unwrap_or_default()
+	}
+
+	/// ORDER BY clause listing the photos joined with photos_albums_map,
+	/// photos taken or imported at the same time stay in the manual order
+	pub(crate) fn to_order(self) -> &'static str
+	{
+		match self
+		{
+			AlbumSort::Manual => "photos_albums_map.position, photos_albums_map.rowid",
+			AlbumSort::CaptureDate => "photos.creation_datetime IS NULL, photos.creation_datetime, \
+				photos_albums_map.position, photos_albums_map.rowid",
+			AlbumSort::ImportDate => "photos.import_datetime, photos_albums_map.position, photos_albums_map.rowid",
+			AlbumSort::Filename => "photos.filename COLLATE NOCASE, photos_albums_map.position, photos_albums_map.rowid",
+		}
+	}
+
+	/// Sort replacing the one of the query of a smart album, None keeps it
+	fn to_photo_sort(self) -> Option<PhotoSort>
+	{
+		match self
+		{
+			AlbumSort::Manual => None,
+			AlbumSort::CaptureDate => Some(PhotoSort::CreationDate),
+			AlbumSort::ImportDate => Some(PhotoSort::ImportDate),
+			AlbumSort::Filename => Some(PhotoSort::Filename),
+		}
+	}
+}
 
 impl Library
 {
@@ -18,7 +92,31 @@ impl Library
 	/// ```
 	pub fn create_album(&self, name: &str, comment: &str, collection: u32) -> Result<Album, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		self.create_album_with_query(name, comment, collection, None)
+	}
+
+	/// Creates a smart album, its photos are the ones matching the query when they are listed
+	///
+	/// The limit and the offset of the query are not kept.
+	/// Fails with InvalidInput if the query holds a value the text syntax cannot store, see [PhotoQuery::to_text()].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::query::PhotoQuery;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let query = PhotoQuery::parse("tag:beach rating>=4 sort:date").unwrap();
+	/// let album = library.create_smart_album("Best of the beach", "", 35, &query).unwrap();
+	/// assert!(album.is_smart());
+	/// ```
+	pub fn create_smart_album(&self, name: &str, comment: &str, collection: u32, query: &PhotoQuery) -> Result<Album, OsplError>
+	{
+		self.create_album_with_query(name, comment, collection, Some(query.to_stored_text()?))
+	}
+
+	fn create_album_with_query(&self, name: &str, comment: &str, collection: u32, query: Option<String>) -> Result<Album, OsplError>
+	{
+		let db = &self.db;
 
 		if name.is_empty()
 		{
@@ -26,9 +124,15 @@ impl Library
 		}
 		let collection = self.get_collection_from_id(collection)?;
 		let mut album = Album::new_with_name(name, comment, collection);
+		album.query = query;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
 		let id = db.insert(&album)?;
 		album.set_id(id);
+		transaction.step("create_album.filesystem")?;
 		self.fs.insert(&album)?;
+		transaction.created(album.get_full_path(&self.fs));
+		transaction.step("create_album.commit")?;
+		transaction.commit()?;
 		Ok(album)
 	}
 
@@ -46,7 +150,7 @@ impl Library
 	/// ```
 	pub fn get_album_from_id(&self, id: u32) -> Result<Album, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let mut album = Album::default();
 		db.load_from_id(&mut album, id)?;
 		Ok(album)
@@ -66,11 +170,15 @@ impl Library
 	/// ```
 	pub fn rename_album_with_id(&self, id: u32, new_name: &str) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let album = self.get_album_from_id(id)?;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
 		self.fs.rename(&album, new_name)?;
+		transaction.moved(album.get_full_path(&self.fs), album.get_full_path(&self.fs).with_file_name(new_name));
+		transaction.step("rename_album.database")?;
 		db.rename(&album, new_name)?;
-		Ok(())
+		transaction.step("rename_album.commit")?;
+		transaction.commit()
 	}
 
 	/// Move an album from the current collection to another one with it's id
@@ -89,12 +197,16 @@ impl Library
 	/// ```
 	pub fn move_album_by_id(&self, album_id: u32, collection_id: u32) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let album = self.get_album_from_id(album_id)?;
 		let collection = self.get_collection_from_id(collection_id)?;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
 		album.move_to(&self.fs, &collection)?;
-		album.assign_to(&db, &collection)?;
-		Ok(())
+		transaction.moved(album.get_full_path(&self.fs), collection.path(&self.fs).join(album.name()));
+		transaction.step("move_album.database")?;
+		album.assign_to(db, &collection)?;
+		transaction.step("move_album.commit")?;
+		transaction.commit()
 	}
 
 	/// Delete an album with its id
@@ -109,13 +221,73 @@ impl Library
 	/// ```
 	pub fn delete_album_by_id(&self, id: u32) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		self.delete_album_with_policy(id, DeletePolicy::Cascade)
+	}
+
+	/// Deletes an album with the given id, `policy` tells what happens to its photos,
+	/// which always stay in the library:
+	/// - [DeletePolicy::Cascade] only takes them out of the album, like [Library::delete_album_by_id()]
+	/// - [DeletePolicy::Reassign] assigns them to another album first, which cannot be a smart album
+	/// - [DeletePolicy::Refuse] fails with [Error::NotEmpty] if the album has photos
+	///
+	/// A smart album has no photos of its own, it is deleted with any policy.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::library::DeletePolicy;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.delete_album_with_policy(35, DeletePolicy::Reassign(36)).unwrap();
+	/// ```
+	pub fn delete_album_with_policy(&self, id: u32, policy: DeletePolicy) -> Result<(), OsplError>
+	{
+		let db = &self.db;
 		let album = self.get_album_from_id(id)?;
-		self.fs.remove(&album)?;
-		db.delete(&album)
+		let photos = Album::list_photo_ids(db, id)?;
+		let target = match policy
+		{
+			DeletePolicy::Cascade => None,
+			DeletePolicy::Refuse if photos.is_empty() => None,
+			DeletePolicy::Refuse => return Err(OsplError::InternalError(Error::NotEmpty)),
+			DeletePolicy::Reassign(target) =>
+			{
+				let target = self.get_album_from_id(target)?;
+				if target.id() == album.id() || target.is_smart()
+				{
+					return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+				}
+				Some(target)
+			}
+		};
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		if let Some(target) = &target
+		{
+			for photo in photos
+			{
+				let photo = self.get_photo_from_id(photo)?;
+				if !target.contains(db, photo.id())?
+				{
+					target.put(db, &photo)?;
+				}
+				let link = target.get_full_path(&self.fs).join(photo.get_filename());
+				if !photo.is_in_trash() && !link.exists()
+				{
+					target.add(&self.fs, &photo)?;
+					transaction.created(link);
+				}
+			}
+			transaction.step("delete_album.reassign")?;
+		}
+		transaction.remove(album.get_full_path(&self.fs))?;
+		transaction.step("delete_album.database")?;
+		db.delete(&album)?;
+		transaction.step("delete_album.commit")?;
+		transaction.commit()
 	}
 
-	/// Get all photos from an album in a Vec<Photo>
+	/// Get all photos from an album in a Vec<Photo>, sorted the way the album is
+	///
+	/// The photos of a smart album are the ones matching its query right now.
 	///
 	/// # Example
 	/// ```no_run
@@ -129,11 +301,24 @@ impl Library
 	/// ```
 	pub fn list_photos_in_album(&self, album_id: u32) -> Result<Vec<Photo>, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		<Album as crate::element::traits::InsideElementListing<Photo>>::list_inside(&db, album_id)
+		let db = &self.db;
+		let album = self.get_album_from_id(album_id)?;
+		if let Some(query) = &album.query
+		{
+			let query = match album.sort.to_photo_sort()
+			{
+				Some(sort) => PhotoQuery::parse(query)?.sort_by(sort, SortOrder::Ascending),
+				None => PhotoQuery::parse(query)?,
+			};
+			return self.search_photos(&query);
+		}
+		Album::list_sorted(db, album_id, album.sort)
 	}
 
-	/// Assign a photo to an album
+	/// Assign a photo to an album, at the end of it
+	///
+	/// Nothing is done if the photo is already in the album.
+	/// Fails with InvalidInput for a smart album, its photos are chosen by its query.
 	///
 	/// # Example
 	/// ```no_run
@@ -144,11 +329,253 @@ impl Library
 	/// ```
 	pub fn assign_photo_to_album(&self, photo: u32, album: u32) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let album = self.get_album_from_id(album)?;
+		if album.is_smart()
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
 		let photo = self.get_photo_from_id(photo)?;
-		album.put(&db, &photo)?;
-		album.add(&self.fs, &photo)?;
-		Ok(())
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		album.put(db, &photo)?;
+		transaction.step("assign_photo_to_album.filesystem")?;
+		let link = album.get_full_path(&self.fs).join(photo.get_filename());
+		if !link.exists()
+		{
+			album.add(&self.fs, &photo)?;
+			transaction.created(link);
+		}
+		transaction.step("assign_photo_to_album.commit")?;
+		transaction.commit()
+	}
+
+	/// Changes the query of a smart album
+	///
+	/// Fails with InvalidInput if the album is not a smart album,
+	/// or if the query cannot be stored like with [Library::create_smart_album()].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::query::PhotoQuery;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_smart_album_query(12, &PhotoQuery::new().starred(true)).unwrap();
+	/// ```
+	pub fn set_smart_album_query(&self, id: u32, query: &PhotoQuery) -> Result<(), OsplError>
+	{
+		let mut album = self.get_album_from_id(id)?;
+		if !album.is_smart()
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		album.query = Some(query.to_stored_text()?);
+		album.update_query(&self.db)
+	}
+
+	/// Turns a smart album into an album whose photos are chosen one by one,
+	/// it keeps the photos matching its query right now
+	///
+	/// Nothing is done if the album is not a smart album.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.convert_smart_album_to_static(12).unwrap();
+	/// assert!(!library.get_album_from_id(12).unwrap().is_smart());
+	/// ```
+	pub fn convert_smart_album_to_static(&self, id: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let mut album = self.get_album_from_id(id)?;
+		if !album.is_smart()
+		{
+			return Ok(());
+		}
+		let photos = self.list_photos_in_album(id)?;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		album.query = None;
+		album.update_query(db)?;
+		for photo in &photos
+		{
+			album.put(db, photo)?;
+		}
+		transaction.step("convert_smart_album.filesystem")?;
+		for photo in &photos
+		{
+			let link = album.get_full_path(&self.fs).join(photo.get_filename());
+			if !link.exists()
+			{
+				album.add(&self.fs, photo)?;
+				transaction.created(link);
+			}
+		}
+		transaction.step("convert_smart_album.commit")?;
+		transaction.commit()
+	}
+
+	/// Takes a photo out of an album, the photo stays in the library
+	///
+	/// Fails with NotFound if the photo is not in the album, and with InvalidInput for a smart album.
+	/// The album loses its cover if it was this photo.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.remove_photo_from_album(27, 35).unwrap();
+	/// ```
+	pub fn remove_photo_from_album(&self, photo: u32, album: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let mut album = self.get_album_from_id(album)?;
+		if album.is_smart()
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		let photo = self.get_photo_from_id(photo)?;
+		if !album.contains(db, photo.id())?
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
+		}
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		Album::delete_link(db, album.id(), photo.id())?;
+		if album.cover == Some(photo.id())
+		{
+			album.cover = None;
+			album.update_cover(db)?;
+		}
+		transaction.step("remove_photo_from_album.filesystem")?;
+		let link = album.get_full_path(&self.fs).join(photo.get_filename());
+		if link.exists()
+		{
+			transaction.remove(link)?;
+		}
+		transaction.step("remove_photo_from_album.commit")?;
+		transaction.commit()
+	}
+
+	/// Moves a photo of an album just before another photo of the album, in the manual order
+	///
+	/// Fails with NotFound if one of the photos is not in the album, and with InvalidInput
+	/// for a smart album. The order is only used when the album is sorted with [AlbumSort::Manual].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// // photo 27 is now shown right before photo 12 in album 35
+	/// library.move_photo_before(35, 27, 12).unwrap();
+	/// ```
+	pub fn move_photo_before(&self, album: u32, photo: u32, before: u32) -> Result<(), OsplError>
+	{
+		self.move_photo_in_album(album, photo, before, 0)
+	}
+
+	/// Moves a photo of an album just after another photo of the album, in the manual order
+	///
+	/// Fails like [Library::move_photo_before()].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.move_photo_after(35, 27, 12).unwrap();
+	/// ```
+	pub fn move_photo_after(&self, album: u32, photo: u32, after: u32) -> Result<(), OsplError>
+	{
+		self.move_photo_in_album(album, photo, after, 1)
+	}
+
+	/// Chooses the photo shown for an album, None shows its first photo again
+	///
+	/// The photo has to be in the album, a smart album can use any photo of the library.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_album_cover(35, Some(27)).unwrap();
+	/// assert_eq!(library.get_album_from_id(35).unwrap().cover(), Some(27));
+	/// ```
+	pub fn set_album_cover(&self, album: u32, photo: Option<u32>) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let mut album = self.get_album_from_id(album)?;
+		if let Some(photo) = photo
+		{
+			self.get_photo_from_id(photo)?;
+			if !album.is_smart() && !album.contains(db, photo)?
+			{
+				return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+			}
+		}
+		album.cover = photo;
+		album.update_cover(db)
+	}
+
+	/// Get the photo shown for an album: its cover, or its first photo when it has no cover
+	/// or its cover is in the trash. None when the album is empty.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// if let Some(cover) = library.get_album_cover(35).unwrap()
+	/// {
+	///     println!("album 35 is shown with photo {}", cover.id());
+	/// }
+	/// ```
+	pub fn get_album_cover(&self, album: u32) -> Result<Option<Photo>, OsplError>
+	{
+		if let Some(cover) = self.get_album_from_id(album)?.cover
+		{
+			let cover = self.get_photo_from_id(cover)?;
+			if !cover.is_in_trash()
+			{
+				return Ok(Some(cover));
+			}
+		}
+		Ok(self.list_photos_in_album(album)?.into_iter().next())
+	}
+
+	/// Changes how the photos of an album are sorted, it is saved in the library
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::album::AlbumSort;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_album_sort(35, AlbumSort::CaptureDate).unwrap();
+	/// ```
+	pub fn set_album_sort(&self, album: u32, sort: AlbumSort) -> Result<(), OsplError>
+	{
+		let mut album = self.get_album_from_id(album)?;
+		album.sort = sort;
+		album.update_sort(&self.db)
+	}
+}
+
+impl Library // Albums helpers
+{
+	/// Moves `photo` next to `other` in the manual order of an album,
+	/// `offset` is 0 to put it before `other` and 1 to put it after
+	fn move_photo_in_album(&self, album: u32, photo: u32, other: u32, offset: usize) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let album = self.get_album_from_id(album)?;
+		if album.is_smart() || photo == other
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		let mut photos = Album::list_photo_ids(db, album.id())?;
+		if !photos.contains(&photo) || !photos.contains(&other)
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
+		}
+		photos.retain(|id| *id != photo);
+		let index = photos.iter().position(|id| *id == other).unwrap_or_default() + offset;
+		photos.insert(index, photo);
+		album.update_positions(db, &photos)
 	}
 }
@@ -1,8 +1,12 @@
 use crate::Library;
-use crate::OsplError;
 use crate::Database;
+use crate::OsplError;
+use crate::Error;
 use crate::Collection;
 use crate::Album;
+use crate::element::collection::CollectionTree;
+use crate::transaction::LibraryTransaction;
+use crate::api::library::DeletePolicy;
 
 impl Library
 {
@@ -20,13 +24,16 @@ impl Library
 	/// ```
 	pub fn list_all_collections(&self) -> Result<Vec<Collection>, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		<Collection as crate::element::traits::ElementListing<Collection>>::list_all(&db, &self.fs)
+		let db = &self.db;
+		<Collection as crate::element::traits::ElementListing<Collection>>::list_all(db, &self.fs)
 	}
 
 
 	/// Creates a collection
 	///
+	/// The name cannot be `.` or `..` or contain a `/` or a `\`, and fails with AlreadyExists
+	/// when another collection at the root has it.
+	///
 	/// # Example
 	/// ```no_run
 	/// # use ospl::Library;
@@ -38,14 +45,46 @@ impl Library
 	///```
 	pub fn create_collection(&self, name: &str, comment: &str) -> Result<Collection, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		// TODO: Add checking to see if the collection has not been created.
+		self.create_collection_inside(name, comment, None)
+	}
+
+	/// Creates a collection inside another collection
+	///
+	/// The directory of the new collection is created inside the directory of its parent.
+	/// Collections inside different parents can have the same name, not collections inside the same one.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::create("/my/awesome/path.ospl/").unwrap();
+	/// let travels = library.create_collection("Travels", "").unwrap();
+	/// let japan = library.create_sub_collection("Japan", "Trips to Japan", travels.id()).unwrap();
+	/// assert_eq!(japan.parent_id(), Some(travels.id()));
+	///```
+	pub fn create_sub_collection(&self, name: &str, comment: &str, parent: u32) -> Result<Collection, OsplError>
+	{
+		let parent = self.get_collection_from_id(parent)?;
+		self.create_collection_inside(name, comment, Some(parent))
+	}
+
+	/// Creates a collection at the root of the library, or inside `parent`
+	fn create_collection_inside(&self, name: &str, comment: &str, parent: Option<Collection>) -> Result<Collection, OsplError>
+	{
+		let db = &self.db;
 
+		check_collection_name(name)?;
+		check_collection_name_free(db, name, parent.as_ref().map(Collection::id), None)?;
 		let mut collection = Collection::new_with_name(name, comment);
+		collection.set_parent(parent);
 
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
 		let id = db.insert(&collection)?;
 		collection.set_id(id);
+		transaction.step("create_collection.filesystem")?;
 		self.fs.insert(&collection)?;
+		transaction.created(collection.path(&self.fs));
+		transaction.step("create_collection.commit")?;
+		transaction.commit()?;
 		Ok(collection)
 	}
 
@@ -63,7 +102,7 @@ impl Library
 	///```
 	pub fn get_collection_from_id(&self, id: u32) -> Result<Collection, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let mut collection = Collection::default();
 		db.load_from_id(&mut collection, id)?;
 		Ok(collection)
@@ -71,6 +110,8 @@ impl Library
 
 	/// Rename a Collection element using its id
 	///
+	/// The new name follows the rules of [Library::create_collection()] among the collections of the same parent.
+	///
 	/// # Example
 	/// ```no_run
 	/// # use ospl::Library;
@@ -83,14 +124,22 @@ impl Library
 	/// ```
 	pub fn rename_collection_with_id(&self, id: u32, new_name: &str) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
+		check_collection_name(new_name)?;
 		let collection = self.get_collection_from_id(id)?;
+		check_collection_name_free(db, new_name, collection.parent_id(), Some(id))?;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
 		self.fs.rename(&collection, new_name)?;
+		transaction.moved(collection.path(&self.fs), collection.path(&self.fs).with_file_name(new_name));
+		transaction.step("rename_collection.database")?;
 		db.rename(&collection, new_name)?;
-		Ok(())
+		transaction.step("rename_collection.commit")?;
+		transaction.commit()
 	}
 
-	/// Deletes a collection with the given id
+	/// Deletes a collection with the given id, and all the collections and albums inside it
+	///
+	/// The photos of the albums stay in the library.
 	///
 	/// # Example
 	/// ```no_run
@@ -101,13 +150,189 @@ impl Library
 	///```
 	pub fn delete_collection_by_id(&self, id: u32) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		self.delete_collection_with_policy(id, DeletePolicy::Cascade)
+	}
+
+	/// Deletes a collection with the given id, `policy` tells what happens to the albums
+	/// and the collections directly inside it:
+	/// - [DeletePolicy::Cascade] deletes them, like [Library::delete_collection_by_id()]
+	/// - [DeletePolicy::Reassign] moves them into another collection, which cannot be inside the deleted one
+	/// - [DeletePolicy::Refuse] fails with [Error::NotEmpty] if there is any
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::library::DeletePolicy;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let archives = library.create_collection("Archives", "").unwrap();
+	/// library.delete_collection_with_policy(3, DeletePolicy::Reassign(archives.id())).unwrap();
+	///```
+	pub fn delete_collection_with_policy(&self, id: u32, policy: DeletePolicy) -> Result<(), OsplError>
+	{
+		let db = &self.db;
 		let collection = self.get_collection_from_id(id)?;
-		self.fs.remove(&collection)?;
-		db.delete(&collection)
+		let children = self.list_collections_in_collection(id)?;
+		let albums = self.list_albums_in_collection(id)?;
+		let target = match policy
+		{
+			DeletePolicy::Cascade => None,
+			DeletePolicy::Refuse if children.is_empty() && albums.is_empty() => None,
+			DeletePolicy::Refuse => return Err(OsplError::InternalError(Error::NotEmpty)),
+			DeletePolicy::Reassign(target) =>
+			{
+				let target = self.get_collection_from_id(target)?;
+				if Collection::descendants(db, id)?.contains(&target.id())
+				{
+					return Err(OsplError::InternalError(Error::MovedInsideItself));
+				}
+				for child in &children
+				{
+					check_collection_name_free(db, &child.name(), Some(target.id()), Some(child.id()))?;
+				}
+				Some(target)
+			}
+		};
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		if let Some(target) = &target
+		{
+			for child in &children
+			{
+				child.move_to(&self.fs, Some(target))?;
+				transaction.moved(child.path(&self.fs), target.path(&self.fs).join(child.name()));
+				child.assign_to(db, Some(target))?;
+			}
+			for album in &albums
+			{
+				album.move_to(&self.fs, target)?;
+				transaction.moved(album.get_full_path(&self.fs), target.path(&self.fs).join(album.name()));
+				album.assign_to(db, target)?;
+			}
+			transaction.step("delete_collection.reassign")?;
+		}
+		transaction.remove(collection.path(&self.fs))?;
+		transaction.step("delete_collection.database")?;
+		db.delete(&collection)?;
+		transaction.step("delete_collection.commit")?;
+		transaction.commit()
 	}
 
-		/// Get all albums in a Vec<Album>
+	/// Moves a collection, with everything inside it, into another collection.
+	/// With None as parent the collection is moved to the root.
+	///
+	/// A collection cannot be moved inside itself or inside one of its sub-collections,
+	/// nor next to a collection with the same name.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::create("/my/awesome/path.ospl/").unwrap();
+	/// let travels = library.create_collection("Travels", "").unwrap();
+	/// let japan = library.create_collection("Japan", "").unwrap();
+	/// library.move_collection_by_id(japan.id(), Some(travels.id())).unwrap();
+	/// library.move_collection_by_id(japan.id(), None).unwrap();
+	///```
+	pub fn move_collection_by_id(&self, id: u32, parent: Option<u32>) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let collection = self.get_collection_from_id(id)?;
+		let parent = match parent
+		{
+			Some(parent) => Some(self.get_collection_from_id(parent)?),
+			None => None,
+		};
+		if let Some(parent) = &parent
+		{
+			if Collection::descendants(db, collection.id())?.contains(&parent.id())
+			{
+				return Err(OsplError::InternalError(Error::MovedInsideItself));
+			}
+		}
+		check_collection_name_free(db, &collection.name(), parent.as_ref().map(Collection::id), Some(id))?;
+		let destination = match &parent
+		{
+			Some(parent) => parent.path(&self.fs),
+			None => self.fs.collections_path(),
+		};
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		collection.move_to(&self.fs, parent.as_ref())?;
+		transaction.moved(collection.path(&self.fs), destination.join(collection.name()));
+		transaction.step("move_collection.database")?;
+		collection.assign_to(db, parent.as_ref())?;
+		transaction.step("move_collection.commit")?;
+		transaction.commit()
+	}
+
+	/// Get the collections directly inside a collection
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for collection in library.list_collections_in_collection(1).unwrap()
+	/// {
+	///     println!("collection id: {} | name: {}", collection.id(), collection.name());
+	/// }
+	/// ```
+	pub fn list_collections_in_collection(&self, collection: u32) -> Result<Vec<Collection>, OsplError>
+	{
+		let db = &self.db;
+		<Collection as crate::element::traits::InsideElementListing<Collection>>::list_inside(db, collection)
+	}
+
+	/// Get a collection with its albums and, recursively, all the collections below it
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::element::collection::CollectionTree;
+	/// fn print_tree(tree: &CollectionTree, depth: usize)
+	/// {
+	///     println!("{}{}", "  ".repeat(depth), tree.collection.name());
+	///     for album in &tree.albums
+	///     {
+	///         println!("{}- {}", "  ".repeat(depth + 1), album.name());
+	///     }
+	///     for child in &tree.children
+	///     {
+	///         print_tree(child, depth + 1);
+	///     }
+	/// }
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// print_tree(&library.get_collection_tree(1).unwrap(), 0);
+	/// ```
+	pub fn get_collection_tree(&self, id: u32) -> Result<CollectionTree, OsplError>
+	{
+		let db = &self.db;
+		let collection = self.get_collection_from_id(id)?;
+		CollectionTree::load(db, collection)
+	}
+
+	/// Get the trees of all the collections at the root of the library
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for tree in library.list_collection_trees().unwrap()
+	/// {
+	///     println!("{} contains {} collections", tree.collection.name(), tree.children.len());
+	/// }
+	/// ```
+	pub fn list_collection_trees(&self) -> Result<Vec<CollectionTree>, OsplError>
+	{
+		let db = &self.db;
+		let mut trees = Vec::new();
+		for collection in self.list_all_collections()?
+		{
+			if collection.parent_id().is_none()
+			{
+				trees.push(CollectionTree::load(db, collection)?);
+			}
+		}
+		Ok(trees)
+	}
+
+	/// Get all albums in a Vec<Album>
 	///
 	/// # Example
 	/// ```no_run
@@ -121,8 +346,32 @@ impl Library
 	/// ``` 
 	pub fn list_albums_in_collection(&self, collection: u32) -> Result<Vec<Album>, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		<Collection as crate::element::traits::InsideElementListing<Album>>::list_inside(&db, collection)
+		let db = &self.db;
+		<Collection as crate::element::traits::InsideElementListing<Album>>::list_inside(db, collection)
 	}
 
 }
+
+/// Checks that a collection name is not empty and cannot leave the directory of its parent
+fn check_collection_name(name: &str) -> Result<(), OsplError>
+{
+	if name.is_empty()
+	{
+		return Err(OsplError::InternalError(Error::EmptyName));
+	}
+	if name == "." || name == ".." || name.contains(['/', '\\'])
+	{
+		return Err(OsplError::InternalError(Error::InvalidName));
+	}
+	Ok(())
+}
+
+/// Fails with AlreadyExists when another collection than `id` is named `name` under `parent`
+fn check_collection_name_free(db: &Database, name: &str, parent: Option<u32>, id: Option<u32>) -> Result<(), OsplError>
+{
+	match Collection::find(db, name, parent)?
+	{
+		Some(other) if Some(other) != id => Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists)),
+		_ => Ok(()),
+	}
+}
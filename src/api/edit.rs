@@ -0,0 +1,259 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Photo;
+use crate::edits;
+use crate::thumbnails;
+use crate::transaction::LibraryTransaction;
+use crate::api::thumbnail::{ThumbnailFormat, ThumbnailSettings};
+
+use image::DynamicImage;
+
+/// Separates the edits of a stack in the `edits` column of the photos table
+const EDIT_SEPARATOR: char = ';';
+
+/// A non-destructive change of a photo, the original file is never modified.
+///
+/// Edits are applied in order on the photo once its EXIF orientation is applied,
+/// so coordinates are always the ones of the photo as it is shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit
+{
+	/// Turns the photo clockwise, by a number of quarter turns from 1 to 3
+	Rotate(u32),
+	/// Keeps only a rectangle of the photo, from its top left corner
+	Crop { x: u32, y: u32, width: u32, height: u32 },
+	FlipHorizontal,
+	FlipVertical,
+	/// Exposure correction in stops, from -5.0 to 5.0
+	Exposure(f32),
+	/// From -1.0 (black) to 1.0 (white)
+	Brightness(f32),
+	/// From -1.0 (flat grey) to 1.0
+	Contrast(f32),
+	/// From -1.0 (black and white) to 1.0
+	Saturation(f32),
+	/// From -1.0 to 1.0, a warmer temperature is more yellow and a higher tint is more magenta
+	WhiteBalance { temperature: f32, tint: f32 },
+}
+
+impl Edit
+{
+	/// Checks the values of the edit are in their range
+	fn is_valid(&self) -> bool
+	{
+		let unit = |value: &f32| (-1.0..=1.0).contains(value);
+		match self
+		{
+			Edit::Rotate(quarters) => (1..=3).contains(quarters),
+			Edit::Crop { width, height, .. } => *width > 0 && *height > 0,
+			Edit::FlipHorizontal | Edit::FlipVertical => true,
+			Edit::Exposure(stops) => (-5.0..=5.0).contains(stops),
+			Edit::Brightness(value) | Edit::Contrast(value) | Edit::Saturation(value) => unit(value),
+			Edit::WhiteBalance { temperature, tint } => unit(temperature) && unit(tint),
+		}
+	}
+
+	/// Writes the edit the way it is stored in the database, like "crop 10 20 800 600"
+	fn to_setting(self) -> String
+	{
+		match self
+		{
+			Edit::Rotate(quarters) => format!("rotate {}", quarters),
+			Edit::Crop { x, y, width, height } => format!("crop {} {} {} {}", x, y, width, height),
+			Edit::FlipHorizontal => String::from("flip_horizontal"),
+			Edit::FlipVertical => String::from("flip_vertical"),
+			Edit::Exposure(stops) => format!("exposure {}", stops),
+			Edit::Brightness(value) => format!("brightness {}", value),
+			Edit::Contrast(value) => format!("contrast {}", value),
+			Edit::Saturation(value) => format!("saturation {}", value),
+			Edit::WhiteBalance { temperature, tint } => format!("white_balance {} {}", temperature, tint),
+		}
+	}
+
+	fn from_setting(value: &str) -> Option<Edit>
+	{
+		let fields: Vec<&str> = value.split(' ').collect();
+		Some(match fields[..]
+		{
+			["rotate", quarters] => Edit::Rotate(quarters.parse().ok()?),
+			["crop", x, y, width, height] => Edit::Crop
+			{
+				x: x.parse().ok()?,
+				y: y.parse().ok()?,
+				width: width.parse().ok()?,
+				height: height.parse().ok()?,
+			},
+			["flip_horizontal"] => Edit::FlipHorizontal,
+			["flip_vertical"] => Edit::FlipVertical,
+			["exposure", stops] => Edit::Exposure(stops.parse().ok()?),
+			["brightness", value] => Edit::Brightness(value.parse().ok()?),
+			["contrast", value] => Edit::Contrast(value.parse().ok()?),
+			["saturation", value] => Edit::Saturation(value.parse().ok()?),
+			["white_balance", temperature, tint] => Edit::WhiteBalance
+			{
+				temperature: temperature.parse().ok()?,
+				tint: tint.parse().ok()?,
+			},
+			_ => return None,
+		})
+	}
+}
+
+/// Writes an edit stack the way it is stored in the database, None when there is no edit
+pub(crate) fn stack_to_setting(stack: &[Edit]) -> Option<String>
+{
+	if stack.is_empty()
+	{
+		return None;
+	}
+	let edits: Vec<String> = stack.iter().map(|edit| edit.to_setting()).collect();
+	Some(edits.join(&EDIT_SEPARATOR.to_string()))
+}
+
+/// Reads an edit stack from the database, the edits that cannot be read are skipped
+pub(crate) fn stack_from_setting(value: Option<&str>) -> Vec<Edit>
+{
+	match value
+	{
+		Some(value) if !value.is_empty() => value.split(EDIT_SEPARATOR).filter_map(Edit::from_setting).collect(),
+		_ => Vec::new(),
+	}
+}
+
+impl Library
+{
+	/// Get the edits of a photo, in the order they are applied
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let edits = library.list_edits_of_photo(1).unwrap();
+	/// println!("photo 1 has {} edits", edits.len());
+	/// ```
+	pub fn list_edits_of_photo(&self, id: u32) -> Result<Vec<Edit>, OsplError>
+	{
+		Ok(self.get_photo_from_id(id)?.edits())
+	}
+
+	/// Adds an edit on top of the edits of a photo
+	///
+	/// The original file is not modified, the thumbnails of the photo are rendered again.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::edit::Edit;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.add_edit(1, Edit::Rotate(1)).unwrap();
+	/// library.add_edit(1, Edit::Exposure(0.5)).unwrap();
+	/// ```
+	pub fn add_edit(&self, id: u32, edit: Edit) -> Result<(), OsplError>
+	{
+		let mut stack = self.list_edits_of_photo(id)?;
+		stack.push(edit);
+		self.set_edits(id, &stack)
+	}
+
+	/// Removes the last edit of a photo, nothing is done if the photo is not edited
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::edit::Edit;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.add_edit(1, Edit::Saturation(-1.0)).unwrap();
+	/// library.undo_edit(1).unwrap();
+	/// ```
+	pub fn undo_edit(&self, id: u32) -> Result<(), OsplError>
+	{
+		let mut stack = self.list_edits_of_photo(id)?;
+		if stack.pop().is_none()
+		{
+			return Ok(());
+		}
+		self.set_edits(id, &stack)
+	}
+
+	/// Replaces all the edits of a photo
+	///
+	/// Fails with InvalidInput if a value is out of its range,
+	/// or if a crop does not fit in the photo as it is at this point of the stack.
+	/// The thumbnails of the photo are rendered again, the edits are only kept if they are.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::edit::Edit;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_edits(1, &[Edit::Crop { x: 0, y: 0, width: 800, height: 600 }, Edit::Contrast(0.2)]).unwrap();
+	/// ```
+	pub fn set_edits(&self, id: u32, stack: &[Edit]) -> Result<(), OsplError>
+	{
+		let mut photo = self.get_photo_from_id(id)?;
+		if !stack.iter().all(Edit::is_valid)
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		if let Some((width, height)) = photo.oriented_size()
+		{
+			if edits::dimensions(width, height, stack).is_none()
+			{
+				return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+			}
+		}
+		let mut transaction = LibraryTransaction::begin(&self.db, &self.fs)?;
+		photo.set_edits(stack);
+		photo.update_edits(&self.db)?;
+		transaction.step("set_edits.thumbnail")?;
+		self.replace_thumbnails(&mut transaction, &mut photo)?;
+		transaction.step("set_edits.commit")?;
+		transaction.commit()
+	}
+
+	/// Removes every edit of a photo, it is shown as its original again
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.revert_to_original(1).unwrap();
+	/// assert!(library.list_edits_of_photo(1).unwrap().is_empty());
+	/// ```
+	pub fn revert_to_original(&self, id: u32) -> Result<(), OsplError>
+	{
+		self.set_edits(id, &[])
+	}
+
+	/// Renders a photo with its orientation and its edits applied, scaled down to fit in `size` x `size`
+	///
+	/// Photos smaller than `size` are kept at their size.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let img = library.render_photo(1, 2048).unwrap();
+	/// img.save("/tmp/edited.png").unwrap();
+	/// ```
+	pub fn render_photo(&self, id: u32, size: u32) -> Result<DynamicImage, OsplError>
+	{
+		if size == 0
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		let photo = self.get_photo_from_id(id)?;
+		let settings = ThumbnailSettings { max_width: size, max_height: size, format: ThumbnailFormat::Jpeg, quality: 100 };
+		thumbnails::render(&self.open_edited(&photo)?, &settings)
+	}
+}
+
+impl Library // Edits helpers
+{
+	/// Decodes the original file of a photo, and applies its orientation and its edits
+	pub(crate) fn open_edited(&self, photo: &Photo) -> Result<DynamicImage, OsplError>
+	{
+		let img = thumbnails::orient(image::open(photo.original_path(&self.fs))?, photo.orientation());
+		Ok(edits::apply(img, &photo.edits()))
+	}
+}
@@ -1,14 +1,141 @@
 use crate::Library;
 use crate::Database;
+use crate::Filesystem;
 use crate::OsplError;
 use crate::thumbnails;
+use crate::perceptual_hash;
 use crate::Photo;
+use crate::Album;
+use crate::Error;
+use crate::element::photo::{check_importable, is_photo};
+use crate::transaction;
+use crate::transaction::LibraryTransaction;
+use crate::api::thumbnail::{ThumbnailPreset, ThumbnailSettings};
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use log::warn;
+
+/// What to do when an imported photo is already in the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy
+{
+	/// The photo is not imported and the id of the photo already in the library is returned
+	Skip,
+	/// The import fails with [OsplError::DuplicatePhoto] holding the id of the photo already in the library
+	Refuse,
+	/// The photo is imported again
+	ImportAnyway,
+}
+
+/// Where the imported photos go, besides the library itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportDestination
+{
+	/// The photos are only added to the library
+	Library,
+	/// The photos are also added to the album with this id, the import fails with InvalidInput for a smart album
+	Album(u32),
+	/// The photos are also added to an album named after the imported folder,
+	/// in the collection with this id. The album is created if the library has none with this name,
+	/// the import fails with AlreadyExists if another collection has an album with this name.
+	NewAlbum(u32),
+}
+
+/// Settings of a directory import
+#[derive(Debug, Clone)]
+pub struct ImportOptions
+{
+	/// What to do with the photos already in the library, skipped by default.
+	/// Skipped photos are still added to the destination album.
+	pub duplicates:	DuplicatePolicy,
+	/// Album receiving the imported photos, none by default
+	pub destination:	ImportDestination,
+	/// Also import the photos of the subdirectories, true by default
+	pub recursive:	bool,
+	/// Number of photos written to the database in one transaction, 100 by default
+	pub batch_size:	usize,
+	/// Number of threads hashing, decoding and scaling down the photos,
+	/// the number of available cores by default.
+	/// Database inserts always happen one at a time, in the order of the files.
+	pub workers:	usize,
+}
+
+impl Default for ImportOptions
+{
+	fn default() -> Self
+	{
+		ImportOptions
+		{
+			duplicates: DuplicatePolicy::Skip,
+			destination: ImportDestination::Library,
+			recursive: true,
+			batch_size: 100,
+			workers: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+		}
+	}
+}
+
+/// Counters of a running directory import, given to the progress callback after each file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportProgress
+{
+	/// Number of files found in the directory, including the ones not processed yet
+	pub total:		usize,
+	/// Number of files processed so far
+	pub seen:		usize,
+	pub imported:	usize,
+	/// Files that are not photos, and duplicates when they are skipped
+	pub skipped:	usize,
+	pub failed:		usize,
+}
+
+/// Result of a directory import
+#[derive(Debug, Default)]
+pub struct ImportReport
+{
+	/// Ids of the imported photos, in the order of the files
+	pub imported:	Vec<u32>,
+	/// Files that are not photos, and duplicates when they are skipped
+	pub skipped:	Vec<PathBuf>,
+	/// Files that could not be imported, with the reason
+	pub failed:		Vec<(PathBuf, OsplError)>,
+}
+
+/// What happened to a single imported file
+enum ImportOutcome
+{
+	Imported(u32),
+	/// The file is already in the library with this id
+	Duplicate(u32),
+}
+
+/// What an import worker made of a file
+enum PreparedFile
+{
+	/// The file is not a photo
+	NotAPhoto,
+	/// The file is already in the library with this id, it was not copied
+	Duplicate(u32),
+	Photo(Box<PreparedPhoto>),
+}
+
+/// A photo hashed, read and decoded by an import worker, ready to be written to the library
+struct PreparedPhoto
+{
+	photo:		Photo,
+	/// Grid thumbnail, already encoded
+	thumbnail:	Vec<u8>,
+}
 
-use std::path::Path;
-	
 impl Library
 {
     /// Imports a photo into the photo library
+	///
+	/// The photo is imported even if it is already in the library,
+	/// use [Library::import_photo_with_policy()] to avoid duplicates.
 	///
 	/// # Example
 	///
@@ -19,16 +146,496 @@ impl Library
 	///```
 	pub fn import_photo<P: AsRef<Path>>(&self, photo_path: P) -> Result<u32, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		if !photo_path.as_ref().exists()
+		self.import_photo_with_policy(photo_path, DuplicatePolicy::ImportAnyway)
+	}
+
+	/// Imports a photo into the photo library, checking first if the same file
+	/// has already been imported
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use ospl::{Library, OsplError};
+	/// # use ospl::api::import::DuplicatePolicy;
+	/// let library = Library::create("/my/awesome/path.ospl/").unwrap();
+	/// let id = library.import_photo("my_awesome_picture.jpg").unwrap();
+	/// let again = library.import_photo_with_policy("my_awesome_picture.jpg", DuplicatePolicy::Refuse);
+	/// assert_eq!(again.err().unwrap(), OsplError::DuplicatePhoto(id));
+	///```
+	pub fn import_photo_with_policy<P: AsRef<Path>>(&self, photo_path: P, policy: DuplicatePolicy)
+	-> Result<u32, OsplError>
+	{
+		self.import_photo_into(photo_path, policy, ImportDestination::Library)
+	}
+
+	/// Imports a photo into the photo library and puts it in an album
+	///
+	/// With [ImportDestination::NewAlbum] the album is named after the folder of the photo.
+	/// A photo skipped because it is already in the library is put in the album too.
+	/// Nothing is kept if a step fails: neither the photo, nor its place in the album, nor the created album.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::import::{DuplicatePolicy, ImportDestination};
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let id = library.import_photo_into("my_awesome_picture.jpg", DuplicatePolicy::Skip, ImportDestination::Album(3)).unwrap();
+	/// assert_eq!(library.list_photos_in_album(3).unwrap()[0].id(), id);
+	///```
+	pub fn import_photo_into<P: AsRef<Path>>(&self, photo_path: P, policy: DuplicatePolicy, destination: ImportDestination)
+	-> Result<u32, OsplError>
+	{
+		let db = &self.db;
+		let folder = photo_path.as_ref().parent().unwrap_or(Path::new(""));
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		let album = self.destination_album(db, &mut transaction, folder, destination)?;
+		let outcome = self.import_into(db, &mut transaction, photo_path.as_ref(), policy, album.as_ref())?;
+		transaction.step("import_photo.commit")?;
+		transaction.commit()?;
+		match outcome
+		{
+			ImportOutcome::Imported(id) => Ok(id),
+			ImportOutcome::Duplicate(id) => Ok(id),
+		}
+	}
+
+	/// Imports all the photos of a directory into the photo library
+	///
+	/// Files that are not photos are skipped, and a file that cannot be imported
+	/// does not stop the import: it is listed in the failed files of the returned report.
+	/// `progress` is called after each processed file.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::import::ImportOptions;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let report = library.import_directory("/media/sdcard/DCIM", &ImportOptions::default(), |progress|
+	/// {
+	///     println!("{}/{} files", progress.seen, progress.total);
+	/// }).unwrap();
+	/// println!("{} photos imported, {} failed", report.imported.len(), report.failed.len());
+	///```
+	pub fn import_directory<P, F>(&self, path: P, options: &ImportOptions, mut progress: F)
+	-> Result<ImportReport, OsplError>
+	where
+		P: AsRef<Path>,
+		F: FnMut(&ImportProgress),
+	{
+		let db = &self.db;
+		let files = list_files(path.as_ref(), options.recursive)?;
+		// The album is kept even if no file can be imported, like the files imported before a failure
+		let mut creation = LibraryTransaction::begin(db, &self.fs)?;
+		let album = self.destination_album(db, &mut creation, path.as_ref(), options.destination)?;
+		creation.commit()?;
+		let mut report = ImportReport::default();
+		let mut counters = ImportProgress { total: files.len(), ..Default::default() };
+		let batch_size = options.batch_size.max(1);
+
+		// The workers drop the files already in the library without copying them
+		let known = match options.duplicates
+		{
+			DuplicatePolicy::ImportAnyway => HashMap::new(),
+			_ => Photo::list_hashes(db)?,
+		};
+		let mut transaction = Some(db.connection.unchecked_transaction()?);
+		let settings = self.thumbnail_settings(ThumbnailPreset::Grid)?;
+		prepare_in_parallel(&self.fs, &files, options.workers, &settings, &known, |index, prepared|
+		{
+			self.import_prepared(db, &files[index], prepared, options.duplicates, album.as_ref(), &mut report);
+			counters.seen += 1;
+			counters.imported = report.imported.len();
+			counters.skipped = report.skipped.len();
+			counters.failed = report.failed.len();
+			progress(&counters);
+			if counters.seen % batch_size == 0
+			{
+				if let Some(transaction) = transaction.take()
+				{
+					transaction.commit()?;
+				}
+				transaction::step("import_directory.batch")?;
+				transaction = Some(db.connection.unchecked_transaction()?);
+			}
+			Ok(())
+		})?;
+		if let Some(transaction) = transaction
+		{
+			transaction.commit()?;
+		}
+		Ok(report)
+	}
+
+	/// Get the photos of the library that are exact copies of each other
+	///
+	/// Photos are grouped by their hash, each group contains at least two photos.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for group in library.find_exact_duplicates().unwrap()
+	/// {
+	///     let ids: Vec<u32> = group.iter().map(|photo| photo.id()).collect();
+	///     println!("same photo: {:?}", ids);
+	/// }
+	///```
+	pub fn find_exact_duplicates(&self) -> Result<Vec<Vec<Photo>>, OsplError>
+	{
+		let db = &self.db;
+		Photo::list_exact_duplicates(db)
+	}
+}
+
+impl Library // Import helpers
+{
+	/// Returns the album receiving the photos imported from `folder`, creating it in the transaction if needed
+	fn destination_album(&self, db: &Database, transaction: &mut LibraryTransaction, folder: &Path,
+		destination: ImportDestination) -> Result<Option<Album>, OsplError>
+	{
+		match destination
+		{
+			ImportDestination::Library => Ok(None),
+			ImportDestination::Album(id) =>
+			{
+				let album = self.get_album_from_id(id)?;
+				match album.is_smart()
+				{
+					true => Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)),
+					false => Ok(Some(album)),
+				}
+			}
+			ImportDestination::NewAlbum(collection) =>
+			{
+				let folder = std::fs::canonicalize(folder)?;
+				let name = match folder.file_name()
+				{
+					Some(name) => name.to_string_lossy().to_string(),
+					None => return Err(OsplError::InternalError(Error::EmptyName)),
+				};
+				match Album::find(db, &name)?
+				{
+					Some((id, found)) if found == collection => self.destination_album(db, transaction, &folder, ImportDestination::Album(id)),
+					Some(_) => Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists)),
+					None =>
+					{
+						let mut album = Album::new_with_name(&name, "", self.get_collection_from_id(collection)?);
+						album.set_id(db.insert(&album)?);
+						self.fs.insert(&album)?;
+						transaction.created(album.get_full_path(&self.fs));
+						Ok(Some(album))
+					}
+				}
+			}
+		}
+	}
+
+	/// Imports one photo: copies it in the library, writes it in the database, creates its thumbnail
+	/// and puts it in the album.
+	/// Every change is recorded in the transaction, so it is undone if the import does not go to the end.
+	fn import_into(&self, db: &Database, transaction: &mut LibraryTransaction, photo_path: &Path,
+		policy: DuplicatePolicy, album: Option<&Album>) -> Result<ImportOutcome, OsplError>
+	{
+		if !photo_path.exists()
 		{
 			return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
 		}
+		check_importable(photo_path)?;
+		transaction.step("import_photo.duplicates")?;
+		// The original is hashed while it is copied, the copy is dropped if the photo is a duplicate
 		let mut photo = Photo::default();
-		photo.from_file(&db, &photo_path)?;
-		let id = db.insert(&photo)?;
-		self.fs.insert(&photo)?;
-		thumbnails::create_thumbnail_from_path(photo_path, self.fs.thumbnails_path().join(photo.get_filename()))?;
+		let copy = photo.copy_into_library(&self.fs, photo_path)?;
+		transaction.created(&copy);
+		if let Some(id) = check_duplicate(db, photo.hash(), policy)?
+		{
+			transaction.remove(copy)?;
+			return self.keep_duplicate(db, transaction, id, album);
+		}
+		transaction.created(self.fs.pictures_path().join(photo.get_filename()));
+		photo.keep_copy(&self.fs, &copy)?;
+		let mut prepared = decode(&self.fs, photo, &self.thumbnail_settings(ThumbnailPreset::Grid)?)?;
+		Ok(ImportOutcome::Imported(self.store(db, transaction, &mut prepared, album)?))
+	}
+
+	/// Writes a prepared photo in the database, saves its thumbnail and puts it in the album
+	fn store(&self, db: &Database, transaction: &mut LibraryTransaction, prepared: &mut PreparedPhoto,
+		album: Option<&Album>) -> Result<u32, OsplError>
+	{
+		let id = db.insert(&prepared.photo)?;
+		prepared.photo.id = id;
+		transaction.step("import_photo.thumbnail")?;
+		let format = self.thumbnail_settings(ThumbnailPreset::Grid)?.format;
+		let thumbnail_path = self.fs.thumbnail_path(&prepared.photo.get_filename(), ThumbnailPreset::Grid, format);
+		let directory = self.fs.thumbnails_path().join(ThumbnailPreset::Grid.name());
+		if !directory.exists()
+		{
+			std::fs::create_dir(&directory)?;
+			transaction.created(directory);
+		}
+		transaction.created(&thumbnail_path);
+		std::fs::write(thumbnail_path, &prepared.thumbnail)?;
+		if let Some(album) = album
+		{
+			transaction.step("import_photo.album")?;
+			album.put(db, &prepared.photo)?;
+			transaction.created(album.get_full_path(&self.fs).join(prepared.photo.get_filename()));
+			album.add(&self.fs, &prepared.photo)?;
+		}
 		Ok(id)
 	}
+
+	/// Puts the photo already in the library with the id `existing` in the album unless it is already there
+	fn keep_duplicate(&self, db: &Database, transaction: &mut LibraryTransaction, existing: u32,
+		album: Option<&Album>) -> Result<ImportOutcome, OsplError>
+	{
+		if let Some(album) = album
+		{
+			if !album.contains(db, existing)?
+			{
+				let existing = self.get_photo_from_id(existing)?;
+				album.put(db, &existing)?;
+				let link = album.get_full_path(&self.fs).join(existing.get_filename());
+				if !link.exists()
+				{
+					album.add(&self.fs, &existing)?;
+					transaction.created(link);
+				}
+			}
+		}
+		Ok(ImportOutcome::Duplicate(existing))
+	}
+
+	/// Writes a file of a directory prepared by a worker and writes what happened in the report.
+	/// Each file has its own transaction so a failure only undoes its own changes.
+	fn import_prepared(&self, db: &Database, file: &Path, prepared: Result<PreparedFile, OsplError>,
+		policy: DuplicatePolicy, album: Option<&Album>, report: &mut ImportReport)
+	{
+		let result = match prepared
+		{
+			Ok(PreparedFile::NotAPhoto) =>
+			{
+				report.skipped.push(file.to_path_buf());
+				return;
+			}
+			Ok(PreparedFile::Duplicate(id)) => self.import_known_duplicate(db, id, policy, album),
+			Ok(PreparedFile::Photo(prepared)) => self.import_prepared_photo(db, *prepared, policy, album),
+			Err(e) => Err(e),
+		};
+		match result
+		{
+			Ok(ImportOutcome::Imported(id)) => report.imported.push(id),
+			Ok(ImportOutcome::Duplicate(_)) => report.skipped.push(file.to_path_buf()),
+			Err(e) => report.failed.push((file.to_path_buf(), e)),
+		}
+	}
+
+	/// Handles a file a worker found already in the library with the id `existing`, it was never copied
+	fn import_known_duplicate(&self, db: &Database, existing: u32, policy: DuplicatePolicy, album: Option<&Album>)
+	-> Result<ImportOutcome, OsplError>
+	{
+		if policy == DuplicatePolicy::Refuse
+		{
+			return Err(OsplError::DuplicatePhoto(existing));
+		}
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		transaction.step("import_photo.duplicates")?;
+		let outcome = self.keep_duplicate(db, &mut transaction, existing, album)?;
+		transaction.step("import_photo.commit")?;
+		transaction.commit()?;
+		Ok(outcome)
+	}
+
+	/// Writes a photo copied and decoded by a worker, the copy is removed if the photo is not kept
+	fn import_prepared_photo(&self, db: &Database, mut prepared: PreparedPhoto, policy: DuplicatePolicy,
+		album: Option<&Album>) -> Result<ImportOutcome, OsplError>
+	{
+		let copy = self.fs.pictures_path().join(prepared.photo.get_filename());
+		let mut transaction = match LibraryTransaction::begin(db, &self.fs)
+		{
+			Ok(transaction) => transaction,
+			Err(e) =>
+			{
+				let _ = std::fs::remove_file(copy);
+				return Err(e);
+			}
+		};
+		transaction.created(&copy);
+		transaction.step("import_photo.duplicates")?;
+		// Another file of the same import may have brought the photo in
+		let outcome = match check_duplicate(db, prepared.photo.hash(), policy)?
+		{
+			Some(id) =>
+			{
+				transaction.remove(copy)?;
+				self.keep_duplicate(db, &mut transaction, id, album)?
+			}
+			None => ImportOutcome::Imported(self.store(db, &mut transaction, &mut prepared, album)?),
+		};
+		transaction.step("import_photo.commit")?;
+		transaction.commit()?;
+		Ok(outcome)
+	}
+}
+
+/// Returns the id of the photo already in the library if the import must not go further,
+/// or an error if the policy refuses duplicates
+fn check_duplicate(db: &Database, hash: u128, policy: DuplicatePolicy) -> Result<Option<u32>, OsplError>
+{
+	if policy == DuplicatePolicy::ImportAnyway
+	{
+		return Ok(None);
+	}
+	match Photo::find_by_hash(db, hash)?
+	{
+		Some(id) if policy == DuplicatePolicy::Refuse => Err(OsplError::DuplicatePhoto(id)),
+		found => Ok(found),
+	}
+}
+
+/// Decodes the copy of a photo in the library to compute its perceptual hash and render its grid thumbnail
+fn decode(fs: &Filesystem, mut photo: Photo, settings: &ThumbnailSettings) -> Result<PreparedPhoto, OsplError>
+{
+	let img = thumbnails::orient(image::open(fs.pictures_path().join(photo.get_filename()))?, photo.orientation());
+	photo.set_perceptual_hash(perceptual_hash::dhash(&img));
+	let thumbnail = thumbnails::encode(&thumbnails::render(&img, settings)?, settings)?;
+	photo.set_thumbnail_hash(thumbnails::source_hash(&photo));
+	Ok(PreparedPhoto { photo, thumbnail })
+}
+
+/// Does all the work of an import that does not need the database:
+/// copying and hashing the file, reading its metadata and decoding it.
+/// The copy of a file whose hash is in `known`, the hashes of the photos already in the library, is removed.
+fn prepare_file(fs: &Filesystem, file: &Path, settings: &ThumbnailSettings, known: &HashMap<u128, u32>)
+-> Result<PreparedFile, OsplError>
+{
+	match is_photo(file)
+	{
+		Ok(true) => {},
+		Ok(false) | Err(OsplError::InternalError(_)) => return Ok(PreparedFile::NotAPhoto),
+		Err(e) => return Err(e),
+	}
+	let mut photo = Photo::default();
+	let temporary = photo.copy_into_library(fs, file)?;
+	if let Some(id) = known.get(&photo.hash())
+	{
+		std::fs::remove_file(temporary)?;
+		return Ok(PreparedFile::Duplicate(*id));
+	}
+	photo.keep_copy(fs, &temporary)?;
+	let copy = fs.pictures_path().join(photo.get_filename());
+	match decode(fs, photo, settings)
+	{
+		Ok(prepared) => Ok(PreparedFile::Photo(Box::new(prepared))),
+		Err(e) =>
+		{
+			let _ = std::fs::remove_file(copy);
+			Err(e)
+		}
+	}
+}
+
+/// Prepares the files with `workers` threads and gives the results to `handle`
+/// on the calling thread, in the order of the files.
+/// The first error returned by `handle` stops the workers and is returned, the photos they
+/// already copied into the library are removed.
+fn prepare_in_parallel<F>(fs: &Filesystem, files: &[PathBuf], workers: usize, settings: &ThumbnailSettings,
+	known: &HashMap<u128, u32>, mut handle: F) -> Result<(), OsplError>
+where
+	F: FnMut(usize, Result<PreparedFile, OsplError>) -> Result<(), OsplError>,
+{
+	let workers = workers.max(1);
+	let next = AtomicUsize::new(0);
+	// The bounded channel keeps the workers from getting too far ahead of the database
+	let (sender, receiver) = mpsc::sync_channel(workers);
+	std::thread::scope(|scope|
+	{
+		for _ in 0..workers
+		{
+			let sender = sender.clone();
+			let next = &next;
+			scope.spawn(move ||
+			{
+				loop
+				{
+					let index = next.fetch_add(1, Ordering::Relaxed);
+					if index >= files.len() || sender.send((index, prepare_file(fs, &files[index], settings, known))).is_err()
+					{
+						break;
+					}
+				}
+			});
+		}
+		drop(sender);
+
+		let mut pending = BTreeMap::new();
+		let mut expected = 0;
+		let mut result = Ok(());
+		// After an error the channel is still emptied, until every worker has stopped
+		for (index, prepared) in receiver
+		{
+			if result.is_err()
+			{
+				discard(fs, prepared);
+				continue;
+			}
+			pending.insert(index, prepared);
+			while let Some(prepared) = pending.remove(&expected)
+			{
+				if let Err(e) = handle(expected, prepared)
+				{
+					next.store(files.len(), Ordering::Relaxed);
+					std::mem::take(&mut pending).into_values().for_each(|prepared| discard(fs, prepared));
+					result = Err(e);
+					break;
+				}
+				expected += 1;
+			}
+		}
+		result
+	})
+}
+
+/// Removes the copy a worker made of a photo that will not be imported
+fn discard(fs: &Filesystem, prepared: Result<PreparedFile, OsplError>)
+{
+	if let Ok(PreparedFile::Photo(prepared)) = prepared
+	{
+		let copy = fs.pictures_path().join(prepared.photo.get_filename());
+		if let Err(e) = std::fs::remove_file(&copy)
+		{
+			warn!("could not remove the copy {:?}: {}", copy, e);
+		}
+	}
+}
+
+/// Lists the files of a directory sorted by path, and the files of its subdirectories if `recursive`
+fn list_files(directory: &Path, recursive: bool) -> Result<Vec<PathBuf>, OsplError>
+{
+	if !directory.is_dir()
+	{
+		return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
+	}
+	let mut entries: Vec<PathBuf> = std::fs::read_dir(directory)?
+		.map(|entry| entry.map(|entry| entry.path()))
+		.collect::<Result<_, _>>()?;
+	entries.sort();
+	let mut files = Vec::new();
+	for entry in entries
+	{
+		if entry.is_dir()
+		{
+			if recursive
+			{
+				files.append(&mut list_files(&entry, recursive)?);
+			}
+		}
+		else
+		{
+			files.push(entry);
+		}
+	}
+	Ok(files)
 }
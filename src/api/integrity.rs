@@ -0,0 +1,368 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Photo;
+use crate::Album;
+use crate::Tag;
+use crate::hash;
+use crate::thumbnails;
+use crate::api::thumbnail::{ThumbnailFormat, ThumbnailPreset};
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A difference between the database and the files of the library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue
+{
+	/// The original file of the photo with this id is missing from `pictures/`, or from `trash/`
+	/// for a photo in the trash
+	MissingOriginal(u32),
+	/// The original file of the photo does not match the hash stored when it was imported
+	CorruptedOriginal { id: u32, expected: u128, found: u128 },
+	/// The photo with this id has no grid thumbnail
+	MissingThumbnail(u32),
+	/// The grid thumbnail of the photo with this id is older than its original file,
+	/// or was rendered from another version of the photo
+	StaleThumbnail(u32),
+	/// A file of `pictures/`, `trash/`, `thumbnails/` or `collections/` that belongs to no photo
+	OrphanFile(PathBuf),
+	/// A row of photos_albums_map pointing to an album or a photo that does not exist
+	DanglingAlbumLink { album: u32, photo: u32 },
+	/// A row of photos_tags_map pointing to a tag or a photo that does not exist
+	DanglingTagLink { tag: u32, photo: u32 },
+	/// The directory of the collection with this id is missing
+	MissingCollectionDirectory(u32),
+	/// The directory of the album with this id is missing
+	MissingAlbumDirectory(u32),
+	/// A photo of an album has no hard link in the album directory
+	MissingAlbumLink { album: u32, photo: u32 },
+	/// A directory of `collections/` that matches no collection and no album
+	UnknownDirectory(PathBuf),
+	/// An entry of `journal/` left by an operation that did not finish: the files it removed,
+	/// they were neither put back nor deleted
+	LeftoverJournal(PathBuf),
+}
+
+impl IntegrityIssue
+{
+	/// Returns true if [Library::repair()] fixes this issue
+	pub fn is_repairable(&self) -> bool
+	{
+		match self
+		{
+			IntegrityIssue::MissingThumbnail(_)
+			| IntegrityIssue::StaleThumbnail(_)
+			| IntegrityIssue::DanglingAlbumLink { .. }
+			| IntegrityIssue::DanglingTagLink { .. }
+			| IntegrityIssue::MissingCollectionDirectory(_)
+			| IntegrityIssue::MissingAlbumDirectory(_)
+			| IntegrityIssue::MissingAlbumLink { .. } => true,
+			IntegrityIssue::OrphanFile(path) => path.starts_with("thumbnails"),
+			_ => false,
+		}
+	}
+}
+
+impl Library
+{
+	/// Compares the database with the files of the library and lists every difference
+	///
+	/// Every original file is hashed again, which can take a while on a big library.
+	/// Orphan files and journal leftovers are given relative to the library directory.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for issue in library.check_integrity().unwrap()
+	/// {
+	///     println!("{:?}", issue);
+	/// }
+	/// ```
+	pub fn check_integrity(&self) -> Result<Vec<IntegrityIssue>, OsplError>
+	{
+		let db = &self.db;
+		let mut issues = Vec::new();
+
+		let photos = self.list_all_photos()?;
+		let trash = self.list_trash()?;
+		let format = self.thumbnail_settings(ThumbnailPreset::Grid)?.format;
+		for photo in photos.iter().chain(&trash)
+		{
+			self.check_photo_files(photo, format, &mut issues)?;
+		}
+		let filenames: HashSet<String> = photos.iter().map(|photo| photo.get_filename()).collect();
+		let trashed: HashSet<String> = trash.iter().map(|photo| photo.get_filename()).collect();
+		let thumbnails: HashSet<String> = photos.iter().chain(&trash)
+			.flat_map(|photo| ThumbnailFormat::ALL.map(|format| thumbnail_name(photo, format)))
+			.collect();
+		// Thumbnails are only written in the directories of the presets
+		let nothing = HashSet::new();
+		let mut directories = vec![(self.fs.pictures_path(), &filenames), (self.fs.thumbnails_path(), &nothing)];
+		if self.fs.trash_path().is_dir()
+		{
+			directories.push((self.fs.trash_path(), &trashed));
+		}
+		for preset in ThumbnailPreset::ALL
+		{
+			let directory = self.fs.thumbnails_path().join(preset.name());
+			if directory.is_dir()
+			{
+				directories.push((directory, &thumbnails));
+			}
+		}
+		for (directory, expected) in directories
+		{
+			for file in list_directory(&directory)?
+			{
+				let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+				if !expected.contains(&name)
+				{
+					issues.push(IntegrityIssue::OrphanFile(self.relative_path(&file)));
+				}
+			}
+		}
+
+		// The journal of a transaction is removed when it ends, anything left comes from an interrupted operation
+		if self.fs.journal_path().is_dir()
+		{
+			let mut entries: Vec<PathBuf> = std::fs::read_dir(self.fs.journal_path())?
+				.map(|entry| entry.map(|entry| entry.path()))
+				.collect::<Result<_, _>>()?;
+			entries.sort();
+			issues.extend(entries.iter().map(|entry| IntegrityIssue::LeftoverJournal(self.relative_path(entry))));
+		}
+
+		for (album, photo) in Album::list_dangling_links(db)?
+		{
+			issues.push(IntegrityIssue::DanglingAlbumLink { album, photo });
+		}
+		for (tag, photo) in Tag::list_dangling_links(db)?
+		{
+			issues.push(IntegrityIssue::DanglingTagLink { tag, photo });
+		}
+
+		self.check_collections_tree(&mut issues)?;
+		Ok(issues)
+	}
+
+	/// Fixes the issues that do not need a decision from the user, and returns the ones left
+	///
+	/// Thumbnails are rendered again from the originals, the missing directories and
+	/// the hard links of the albums are created again, the dangling rows of the link tables and
+	/// the orphan thumbnails are removed.
+	/// Missing or corrupted originals, orphan files, unknown directories and journal leftovers are left untouched.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for issue in library.repair().unwrap()
+	/// {
+	///     println!("needs a manual fix: {:?}", issue);
+	/// }
+	/// ```
+	pub fn repair(&self) -> Result<Vec<IntegrityIssue>, OsplError>
+	{
+		let db = &self.db;
+		let mut issues = self.check_integrity()?;
+		// Directories first, so the links can be created inside them
+		issues.sort_by_key(|issue| !matches!(issue,
+			IntegrityIssue::MissingCollectionDirectory(_) | IntegrityIssue::MissingAlbumDirectory(_)));
+
+		for issue in issues.iter().filter(|issue| issue.is_repairable())
+		{
+			match issue
+			{
+				IntegrityIssue::MissingThumbnail(id) | IntegrityIssue::StaleThumbnail(id) =>
+				{
+					let photo = self.get_photo_from_id(*id)?;
+					if self.fs.pictures_path().join(photo.get_filename()).exists()
+					{
+						self.regenerate_thumbnail(*id)?;
+					}
+				}
+				IntegrityIssue::DanglingAlbumLink { album, photo } => Album::delete_link(db, *album, *photo)?,
+				IntegrityIssue::DanglingTagLink { tag, photo } => Tag::delete_link(db, *tag, *photo)?,
+				IntegrityIssue::MissingCollectionDirectory(id) =>
+				{
+					std::fs::create_dir_all(self.get_collection_from_id(*id)?.path(&self.fs))?;
+				}
+				IntegrityIssue::MissingAlbumDirectory(id) =>
+				{
+					let album = self.get_album_from_id(*id)?;
+					std::fs::create_dir_all(album.get_full_path(&self.fs))?;
+					if !album.is_smart()
+					{
+						for photo in self.list_photos_in_album(album.id())?
+						{
+							self.link_into_album(&album, &photo)?;
+						}
+					}
+				}
+				IntegrityIssue::MissingAlbumLink { album, photo } =>
+				{
+					self.link_into_album(&self.get_album_from_id(*album)?, &self.get_photo_from_id(*photo)?)?;
+				}
+				IntegrityIssue::OrphanFile(path) => std::fs::remove_file(self.fs.root_path().join(path))?,
+				_ => {},
+			}
+		}
+		self.check_integrity()
+	}
+}
+
+impl Library // Integrity helpers
+{
+	/// Checks the original file and the thumbnail of a photo, the photos in the trash have no thumbnail
+	fn check_photo_files(&self, photo: &Photo, format: ThumbnailFormat, issues: &mut Vec<IntegrityIssue>) -> Result<(), OsplError>
+	{
+		let original = photo.original_path(&self.fs);
+		let thumbnail = self.fs.thumbnail_path(&photo.get_filename(), ThumbnailPreset::Grid, format);
+		if !original.exists()
+		{
+			issues.push(IntegrityIssue::MissingOriginal(photo.id()));
+		}
+		else
+		{
+			let found = hash::hash_file(&original)?;
+			if found != photo.hash()
+			{
+				issues.push(IntegrityIssue::CorruptedOriginal { id: photo.id(), expected: photo.hash(), found });
+			}
+		}
+		if photo.is_in_trash()
+		{
+			return Ok(());
+		}
+		if !thumbnail.exists()
+		{
+			issues.push(IntegrityIssue::MissingThumbnail(photo.id()));
+		}
+		else if photo.thumbnail_hash() != Some(thumbnails::source_hash(photo))
+			|| (original.exists() && modified(&thumbnail)? < modified(&original)?)
+		{
+			issues.push(IntegrityIssue::StaleThumbnail(photo.id()));
+		}
+		Ok(())
+	}
+
+	/// Checks that `collections/` has a directory for each collection and album,
+	/// and a hard link for each photo of an album
+	fn check_collections_tree(&self, issues: &mut Vec<IntegrityIssue>) -> Result<(), OsplError>
+	{
+		let mut known_directories = HashSet::new();
+		// Files each directory is expected to contain, empty for the collections
+		let mut expected_files: Vec<(PathBuf, HashSet<String>)> = Vec::new();
+
+		for collection in self.list_all_collections()?
+		{
+			let path = collection.path(&self.fs);
+			if !path.is_dir()
+			{
+				issues.push(IntegrityIssue::MissingCollectionDirectory(collection.id()));
+			}
+			known_directories.insert(path.clone());
+			expected_files.push((path, HashSet::new()));
+
+			for album in self.list_albums_in_collection(collection.id())?
+			{
+				let path = album.get_full_path(&self.fs);
+				if !path.is_dir()
+				{
+					issues.push(IntegrityIssue::MissingAlbumDirectory(album.id()));
+				}
+				// The photos of a smart album are chosen by its query, they have no hard link
+				let mut files = HashSet::new();
+				let photos = match album.is_smart()
+				{
+					true => Vec::new(),
+					false => self.list_photos_in_album(album.id())?,
+				};
+				for photo in photos
+				{
+					if path.is_dir() && !path.join(photo.get_filename()).exists()
+					{
+						issues.push(IntegrityIssue::MissingAlbumLink { album: album.id(), photo: photo.id() });
+					}
+					files.insert(photo.get_filename());
+				}
+				known_directories.insert(path.clone());
+				expected_files.push((path, files));
+			}
+		}
+
+		for (directory, files) in expected_files.iter().filter(|(directory, _)| directory.is_dir())
+		{
+			for file in list_directory(directory)?
+			{
+				let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+				if !files.contains(&name)
+				{
+					issues.push(IntegrityIssue::OrphanFile(self.relative_path(&file)));
+				}
+			}
+		}
+		self.find_unknown_directories(&self.fs.collections_path(), &known_directories, issues)?;
+		Ok(())
+	}
+
+	fn find_unknown_directories(&self, directory: &Path, known: &HashSet<PathBuf>, issues: &mut Vec<IntegrityIssue>)
+	-> Result<(), OsplError>
+	{
+		let mut entries: Vec<PathBuf> = std::fs::read_dir(directory)?
+			.map(|entry| entry.map(|entry| entry.path()))
+			.collect::<Result<_, _>>()?;
+		entries.sort();
+		for entry in entries.into_iter().filter(|entry| entry.is_dir())
+		{
+			if known.contains(&entry)
+			{
+				self.find_unknown_directories(&entry, known, issues)?;
+			}
+			else
+			{
+				issues.push(IntegrityIssue::UnknownDirectory(self.relative_path(&entry)));
+			}
+		}
+		Ok(())
+	}
+
+	/// Creates the hard link of a photo in an album directory, if its original is still there.
+	/// Smart albums have no hard links.
+	fn link_into_album(&self, album: &Album, photo: &Photo) -> Result<(), OsplError>
+	{
+		if !album.is_smart() && self.fs.pictures_path().join(photo.get_filename()).exists()
+		{
+			album.add(&self.fs, photo)?;
+		}
+		Ok(())
+	}
+
+	fn relative_path(&self, path: &Path) -> PathBuf
+	{
+		path.strip_prefix(self.fs.root_path()).unwrap_or(path).to_path_buf()
+	}
+}
+
+/// Lists the files of a directory sorted by path, without its subdirectories
+fn list_directory(directory: &Path) -> Result<Vec<PathBuf>, OsplError>
+{
+	let mut files: Vec<PathBuf> = std::fs::read_dir(directory)?
+		.map(|entry| entry.map(|entry| entry.path()))
+		.collect::<Result<_, _>>()?;
+	files.retain(|file| !file.is_dir());
+	files.sort();
+	Ok(files)
+}
+
+/// Name of a thumbnail of the photo written in this format, in the directory of any preset
+fn thumbnail_name(photo: &Photo, format: ThumbnailFormat) -> String
+{
+	Path::new(&photo.get_filename()).with_extension(format.extension()).to_string_lossy().to_string()
+}
+
+fn modified(path: &Path) -> Result<std::time::SystemTime, OsplError>
+{
+	Ok(std::fs::metadata(path)?.modified()?)
+}
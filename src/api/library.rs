@@ -1,9 +1,25 @@
 use crate::Library;
 use crate::OsplError;
+use crate::Database;
 use crate::Filesystem;
 
 use std::path::Path;
 
+/// What happens to the content of an album, a collection or a tag when it is deleted.
+/// Photos are never deleted with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeletePolicy
+{
+	/// The content is deleted too: the albums and sub-collections of a collection,
+	/// the tags below a tag. The photos of an album only leave it.
+	#[default]
+	Cascade,
+	/// The content is given to the element of the same kind with this id
+	Reassign(u32),
+	/// The deletion fails with [crate::Error::NotEmpty] if there is any content
+	Refuse,
+}
+
 impl Library
 {
 	/// Creates a new empty ospl library.
@@ -24,14 +40,28 @@ impl Library
 	pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
 	{
 		std::fs::create_dir(&path)?;
+		let fs = Filesystem::create(path)?;
+		let db = Database::create(fs.database_path())?;
 		Ok(Library
 		{
-			fs: Filesystem::create(path)?,
+			fs,
+			db,
 		})
 	}
 
 	/// Loads an existing ospl Library
 	///
+	/// The directory must contain the database and the subfolders created by [Library::create()],
+	/// otherwise [crate::Error::NotALibrary] or [crate::Error::CorruptedLibrary] is returned.
+	/// Loading fails with [crate::Error::LibraryLocked] if another process is writing in the database.
+	///
+	/// A library written by an older version of ospl is migrated to the current version.
+	/// A library written by a newer version is refused with [crate::Error::LibraryTooNew].
+	///
+	/// Apart from the migrations, loading never changes the library:
+	/// the trash is only emptied by [Library::purge_trash()] and [Library::empty_trash()].
+	/// Migrating a library older than the thumbnail presets renders its grid thumbnails again.
+	///
 	/// # Example
 	/// ```
 	/// # use ospl::Library;
@@ -43,9 +73,14 @@ impl Library
 	/// ```
 	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
 	{	
-		Ok(Library
+		let fs = Filesystem::load(path)?;
+		let db = Database::load(fs.database_path())?;
+		let library = Library
 		{
-			fs: Filesystem::new(path)?,
-		})
+			fs,
+			db,
+		};
+		library.upgrade_legacy_thumbnails()?;
+		Ok(library)
 	}
 }
@@ -2,4 +2,12 @@ pub mod library;
 pub mod import;
 pub mod photo;
 pub mod collection;
-pub mod album;
\ No newline at end of file
+pub mod album;
+pub mod tag;
+pub mod integrity;
+pub mod thumbnail;
+pub mod edit;
+pub mod rating;
+pub mod query;
+pub mod search;
+pub mod trash;
\ No newline at end of file
@@ -1,10 +1,25 @@
 use crate::Library;
 use crate::OsplError;
-use crate::Database;
 use crate::Photo;
+use crate::Album;
+use crate::perceptual_hash;
+use crate::hash;
+use crate::transaction::LibraryTransaction;
+use crate::api::thumbnail::ThumbnailPreset;
 
 use std::path::PathBuf;
 
+/// A photo whose file in the library does not match the hash stored when it was imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashMismatch
+{
+	pub id:			u32,
+	/// Hash stored in the database
+	pub expected:	u128,
+	/// Hash of the file in the library, None when the file is missing
+	pub found:		Option<u128>,
+}
+
 impl Library
 {
 	/// Get a Photo element from an id
@@ -19,13 +34,13 @@ impl Library
 	///```
 	pub fn get_photo_from_id(&self, id: u32) -> Result<Photo, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
+		let db = &self.db;
 		let mut photo = Photo::default();
 		db.load_from_id(&mut photo, id)?;
 		Ok(photo)
 	}
 
-    /// Get all photos in a Vec, with only the id and the thumbnail path
+    /// Get all photos in a Vec, with only the id and the path of the grid thumbnail
 	///
 	/// This function is useful to show all photos consuming less memory
 	/// because it only contains the id and the thumbnail path.
@@ -43,8 +58,10 @@ impl Library
 	/// ```
 	pub fn list_all_thumbnails(&self) -> Result<Vec<(u32, PathBuf)>, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		<Photo as crate::element::traits::ElementListing<(u32, PathBuf)>>::list_all(&db, &self.fs)
+		let format = self.thumbnail_settings(ThumbnailPreset::Grid)?.format;
+		Ok(self.list_all_photos()?.iter()
+			.map(|photo| (photo.id(), self.fs.thumbnail_path(&photo.get_filename(), ThumbnailPreset::Grid, format)))
+			.collect())
 	}
 
 	/// Get all photos in a Vec<Photo>
@@ -65,13 +82,42 @@ impl Library
 	/// ```
 	pub fn list_all_photos(&self) -> Result<Vec<Photo>, OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		<Photo as crate::element::traits::ElementListing<Photo>>::list_all(&db, &self.fs)
+		let db = &self.db;
+		<Photo as crate::element::traits::ElementListing<Photo>>::list_all(db, &self.fs)
 	}
 
+	/// Gives a title to a photo, an empty title removes it
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_photo_title(1, "Sunset on the beach").unwrap();
+	/// assert_eq!(library.get_photo_from_id(1).unwrap().title().unwrap(), "Sunset on the beach");
+	///```
+	pub fn set_photo_title(&self, id: u32, title: &str) -> Result<(), OsplError>
+	{
+		Photo::update_column_of_photos(&self.db, &[id], "title", Some(title).filter(|title| !title.is_empty()))
+	}
 
+	/// Writes a comment on a photo, an empty comment removes it
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_photo_comment(1, "The first evening of the holidays").unwrap();
+	///```
+	pub fn set_photo_comment(&self, id: u32, comment: &str) -> Result<(), OsplError>
+	{
+		Photo::update_column_of_photos(&self.db, &[id], "comment", Some(comment).filter(|comment| !comment.is_empty()))
+	}
 
-	/// Deletes a photo with given id
+	/// Moves a photo with given id to the trash
+	///
+	/// Its original file goes to `trash/` and its thumbnails and album links are removed,
+	/// its metadata, tags and albums are kept so [Library::restore_from_trash()] can put it back.
+	/// Nothing is done if the photo is already in the trash.
 	///
 	/// # Example
 	/// ```no_run
@@ -82,9 +128,145 @@ impl Library
 	///```
 	pub fn delete_photo_by_id(&self, id: u32) -> Result<(), OsplError>
 	{
-		let db = Database::new(self.fs.database_path())?;
-		let photo = self.get_photo_from_id(id)?;
-		self.fs.remove(&photo)?;
-		db.delete(&photo)
+		let db = &self.db;
+		let mut photo = self.get_photo_from_id(id)?;
+		if photo.is_in_trash()
+		{
+			return Ok(());
+		}
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		if !self.fs.trash_path().is_dir()
+		{
+			std::fs::create_dir(self.fs.trash_path())?;
+			transaction.created(self.fs.trash_path());
+		}
+		let original = photo.original_path(&self.fs);
+		photo.set_deleted_datetime(Some(chrono::offset::Local::now().naive_local()));
+		std::fs::rename(&original, photo.original_path(&self.fs))?;
+		transaction.moved(original, photo.original_path(&self.fs));
+		transaction.step("delete_photo.thumbnail")?;
+		for thumbnail in self.cached_thumbnails(&photo)?
+		{
+			transaction.remove(thumbnail)?;
+		}
+		transaction.step("delete_photo.albums")?;
+		for album in Album::list_containing(db, id)?
+		{
+			let link = self.get_album_from_id(album)?.get_full_path(&self.fs).join(photo.get_filename());
+			if link.exists()
+			{
+				transaction.remove(link)?;
+			}
+		}
+		transaction.step("delete_photo.database")?;
+		photo.update_deleted_datetime(db)?;
+		transaction.step("delete_photo.commit")?;
+		transaction.commit()
+	}
+
+	/// Get the photos that look like the given photo, the most similar first
+	///
+	/// `max_distance` is the number of bits that can differ between the perceptual
+	/// hashes of two photos: 0 only finds visually identical photos, around 10 also finds
+	/// re-encoded, resized or slightly retouched copies.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for photo in library.find_similar_photos(1, 10).unwrap()
+	/// {
+	///     println!("photo {} looks like photo 1", photo.id());
+	/// }
+	///```
+	pub fn find_similar_photos(&self, id: u32, max_distance: u32) -> Result<Vec<Photo>, OsplError>
+	{
+		let db = &self.db;
+		let reference = match self.get_photo_from_id(id)?.perceptual_hash()
+		{
+			Some(hash) => hash,
+			None => return Ok(Vec::new()),
+		};
+		let mut similar: Vec<(u32, u32)> = Photo::list_perceptual_hashes(db)?
+			.into_iter()
+			.filter(|(photo, _)| *photo != id)
+			.map(|(photo, hash)| (perceptual_hash::distance(reference, hash), photo))
+			.filter(|(distance, _)| *distance <= max_distance)
+			.collect();
+		similar.sort();
+		let mut photos = Vec::new();
+		for (_, photo) in similar
+		{
+			photos.push(self.get_photo_from_id(photo)?);
+		}
+		Ok(photos)
+	}
+
+	/// Get all the groups of visually similar photos of the library
+	///
+	/// Two photos are in the same group when their perceptual hashes are at most
+	/// `max_distance` bits apart, or when they both look like another photo of the group.
+	/// Each group contains at least two photos.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for group in library.find_similar_photo_groups(10).unwrap()
+	/// {
+	///     let ids: Vec<u32> = group.iter().map(|photo| photo.id()).collect();
+	///     println!("similar photos: {:?}", ids);
+	/// }
+	///```
+	pub fn find_similar_photo_groups(&self, max_distance: u32) -> Result<Vec<Vec<Photo>>, OsplError>
+	{
+		let db = &self.db;
+		let hashes = Photo::list_perceptual_hashes(db)?;
+		let mut groups = Vec::new();
+		for ids in perceptual_hash::cluster(&hashes, max_distance)
+		{
+			let mut group = Vec::new();
+			for id in ids
+			{
+				group.push(self.get_photo_from_id(id)?);
+			}
+			groups.push(group);
+		}
+		Ok(groups)
+	}
+
+	/// Hashes again the file of every photo in the library and returns the ones
+	/// that changed or disappeared since they were imported
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for mismatch in library.verify_photos().unwrap()
+	/// {
+	///     match mismatch.found
+	///     {
+	///         Some(_) => println!("photo {} is corrupted", mismatch.id),
+	///         None => println!("photo {} is missing", mismatch.id),
+	///     }
+	/// }
+	///```
+	pub fn verify_photos(&self) -> Result<Vec<HashMismatch>, OsplError>
+	{
+		let mut mismatches = Vec::new();
+		for photo in self.list_all_photos()?
+		{
+			let found = match hash::hash_file(photo.original_path(&self.fs))
+			{
+				Ok(found) => Some(found),
+				Err(OsplError::IoError(std::io::ErrorKind::NotFound)) => None,
+				Err(e) => return Err(e),
+			};
+			if found != Some(photo.hash())
+			{
+				mismatches.push(HashMismatch { id: photo.id(), expected: photo.hash(), found });
+			}
+		}
+		Ok(mismatches)
 	}
 }
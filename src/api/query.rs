@@ -0,0 +1,616 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Photo;
+use crate::api::rating::{ColorLabel, PickFlag};
+
+use chrono::NaiveDate;
+use rusqlite::types::Value;
+
+/// Order of the photos found by a [PhotoQuery]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PhotoSort
+{
+	/// Order in which the photos were imported
+	#[default]
+	Id,
+	ImportDate,
+	/// Date the photos were taken, photos without a date are always last
+	CreationDate,
+	Rating,
+	Filename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder
+{
+	#[default]
+	Ascending,
+	Descending,
+}
+
+/// A condition of a query, a photo has to match all the filters of the query
+#[derive(Debug, Clone, PartialEq)]
+enum Filter
+{
+	/// Full path of a tag, the photos tagged with one of its children match too
+	Tag(String),
+	Album(u32),
+	AlbumName(String),
+	MinRating(u32),
+	MaxRating(u32),
+	Starred(bool),
+	ColorLabel(ColorLabel),
+	Flag(PickFlag),
+	/// Part of the make or of the model of the camera
+	Camera(String),
+	Lens(String),
+	Format(String),
+	TakenFrom(NaiveDate),
+	TakenUntil(NaiveDate),
+	Area { south: f64, west: f64, north: f64, east: f64 },
+	/// Part of the filename, the title or the comment
+	Text(String),
+}
+
+/// Describes the photos to search for, built with its methods or parsed from a text query.
+///
+/// # Example
+/// ```no_run
+/// # use ospl::Library;
+/// # use ospl::api::query::{PhotoQuery, PhotoSort, SortOrder};
+/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+/// let query = PhotoQuery::new()
+///     .tag("beach")
+///     .min_rating(4)
+///     .sort_by(PhotoSort::CreationDate, SortOrder::Descending)
+///     .limit(50);
+/// let photos = library.search_photos(&query).unwrap();
+/// let same = PhotoQuery::parse("tag:beach rating>=4 sort:-date").unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PhotoQuery
+{
+	filters:	Vec<Filter>,
+	sort:		PhotoSort,
+	order:		SortOrder,
+	limit:		Option<u32>,
+	offset:		u32,
+}
+
+impl PhotoQuery
+{
+	/// Returns a query matching every photo of the library
+	pub fn new() -> Self
+	{
+		PhotoQuery::default()
+	}
+
+	/// Photos tagged with the tag at `path`, like "Places/France", or with one of its children
+	pub fn tag(mut self, path: &str) -> Self
+	{
+		self.filters.push(Filter::Tag(path.trim_matches('/').to_string()));
+		self
+	}
+
+	/// Photos of an album
+	pub fn album(mut self, id: u32) -> Self
+	{
+		self.filters.push(Filter::Album(id));
+		self
+	}
+
+	/// Photos of the album with this name
+	pub fn album_named(mut self, name: &str) -> Self
+	{
+		self.filters.push(Filter::AlbumName(name.to_string()));
+		self
+	}
+
+	/// Photos rated at least `rating` stars
+	pub fn min_rating(mut self, rating: u32) -> Self
+	{
+		self.filters.push(Filter::MinRating(rating));
+		self
+	}
+
+	/// Photos rated at most `rating` stars, unrated photos included
+	pub fn max_rating(mut self, rating: u32) -> Self
+	{
+		self.filters.push(Filter::MaxRating(rating));
+		self
+	}
+
+	pub fn starred(mut self, starred: bool) -> Self
+	{
+		self.filters.push(Filter::Starred(starred));
+		self
+	}
+
+	pub fn color_label(mut self, label: ColorLabel) -> Self
+	{
+		self.filters.push(Filter::ColorLabel(label));
+		self
+	}
+
+	pub fn flag(mut self, flag: PickFlag) -> Self
+	{
+		self.filters.push(Filter::Flag(flag));
+		self
+	}
+
+	/// Photos taken with a camera whose make or model contains `camera`, ignoring the case
+	pub fn camera(mut self, camera: &str) -> Self
+	{
+		self.filters.push(Filter::Camera(camera.to_string()));
+		self
+	}
+
+	/// Photos taken with a lens whose name contains `lens`, ignoring the case
+	pub fn lens(mut self, lens: &str) -> Self
+	{
+		self.filters.push(Filter::Lens(lens.to_string()));
+		self
+	}
+
+	/// Photos whose original is in this format, as its usual extension (ex: "jpg")
+	pub fn format(mut self, format: &str) -> Self
+	{
+		self.filters.push(Filter::Format(format.to_lowercase()));
+		self
+	}
+
+	/// Photos taken between two days, both included. None leaves the range open on that side.
+	pub fn taken_between(mut self, from: Option<NaiveDate>, until: Option<NaiveDate>) -> Self
+	{
+		self.filters.extend(from.map(Filter::TakenFrom));
+		self.filters.extend(until.map(Filter::TakenUntil));
+		self
+	}
+
+	/// Photos taken inside an area, given by its south west and north east corners in degrees.
+	/// An area crossing the 180th meridian has its west side greater than its east side.
+	pub fn within_area(mut self, south: f64, west: f64, north: f64, east: f64) -> Self
+	{
+		self.filters.push(Filter::Area { south, west, north, east });
+		self
+	}
+
+	/// Photos whose filename, title or comment contains `text`, ignoring the case
+	pub fn text(mut self, text: &str) -> Self
+	{
+		self.filters.push(Filter::Text(text.to_string()));
+		self
+	}
+
+	pub fn sort_by(mut self, sort: PhotoSort, order: SortOrder) -> Self
+	{
+		self.sort = sort;
+		self.order = order;
+		self
+	}
+
+	/// Returns at most `limit` photos
+	pub fn limit(mut self, limit: u32) -> Self
+	{
+		self.limit = Some(limit);
+		self
+	}
+
+	/// Skips the first `offset` photos, used with [PhotoQuery::limit()] to get the photos page by page
+	pub fn offset(mut self, offset: u32) -> Self
+	{
+		self.offset = offset;
+		self
+	}
+}
+
+impl PhotoQuery // Text syntax
+{
+	/// Parses a text query made of filters separated by spaces, a photo has to match all of them.
+	///
+	/// - `tag:Places/France`: tagged with the tag or one of its children
+	/// - `album:"Pizza Party"`: in the album with this name
+	/// - `rating>=4`, `rating<2`, `rating:3`: rated with this number of stars
+	/// - `starred:yes`, `starred:no`
+	/// - `label:red`, `flag:picked`, `flag:rejected`, `flag:none`
+	/// - `album_id:3`: in the album with this id
+	/// - `camera:"X-T4"`, `lens:50mm`, `format:jpg`
+	/// - `date:2023-06..2023-08`, `date:2023`, `date:2023-06-18..`: taken in the range, days, months or years
+	/// - `area:48.81,2.22..48.91,2.47`: taken inside the area from its south west corner to its north east corner
+	/// - `sort:date`, `sort:-rating`: sorted by `id`, `imported`, `date`, `rating` or `name`, descending with a `-`
+	/// - any other word: in the filename, the title or the comment
+	///
+	/// Values with spaces are written between double quotes, with `\"` for a double quote
+	/// and `\\` for a backslash. A word starting with a double quote is always searched as text,
+	/// like `"IMG:1"`.
+	/// Fails with InvalidInput if a filter or a value is not known.
+	pub fn parse(text: &str) -> Result<PhotoQuery, OsplError>
+	{
+		let mut query = PhotoQuery::new();
+		for token in tokenize(text)?
+		{
+			query = query.parse_token(&token).ok_or(OsplError::IoError(std::io::ErrorKind::InvalidInput))?;
+		}
+		Ok(query)
+	}
+
+	fn parse_token(self, token: &Token) -> Option<PhotoQuery>
+	{
+		if token.literal
+		{
+			return Some(self.text(&token.text));
+		}
+		let token_text = token.text.as_str();
+		if let Some(rating) = token_text.strip_prefix("rating").filter(|rest| rest.starts_with(['<', '>', '=', ':']))
+		{
+			return self.parse_rating(rating);
+		}
+		let (key, value) = match token_text.split_once(':')
+		{
+			Some((key, value)) if !value.is_empty() || token.quoted => (key, value),
+			Some(_) => return None,
+			None => return Some(self.text(token_text)),
+		};
+		Some(match key
+		{
+			"tag" => self.tag(value),
+			"album" => self.album_named(value),
+			"album_id" => self.album(value.parse().ok()?),
+			"starred" => match value
+			{
+				"yes" | "true" => self.starred(true),
+				"no" | "false" => self.starred(false),
+				_ => return None,
+			},
+			"label" => self.color_label(ColorLabel::from_name(value)?),
+			"flag" => match value
+			{
+				"picked" => self.flag(PickFlag::Picked),
+				"rejected" => self.flag(PickFlag::Rejected),
+				"none" => self.flag(PickFlag::Unflagged),
+				_ => return None,
+			},
+			"camera" => self.camera(value),
+			"lens" => self.lens(value),
+			"format" => self.format(value),
+			"date" =>
+			{
+				let (from, until) = value.split_once("..").unwrap_or((value, value));
+				let from = if from.is_empty() { None } else { Some(parse_date(from, false)?) };
+				let until = if until.is_empty() { None } else { Some(parse_date(until, true)?) };
+				self.taken_between(from, until)
+			}
+			"area" =>
+			{
+				let (south_west, north_east) = value.split_once("..")?;
+				let (south, west) = parse_coordinates(south_west)?;
+				let (north, east) = parse_coordinates(north_east)?;
+				self.within_area(south, west, north, east)
+			}
+			"sort" =>
+			{
+				let (name, order) = match value.strip_prefix('-')
+				{
+					Some(name) => (name, SortOrder::Descending),
+					None => (value, SortOrder::Ascending),
+				};
+				let sort = match name
+				{
+					"id" => PhotoSort::Id,
+					"imported" => PhotoSort::ImportDate,
+					"date" => PhotoSort::CreationDate,
+					"rating" => PhotoSort::Rating,
+					"name" => PhotoSort::Filename,
+					_ => return None,
+				};
+				self.sort_by(sort, order)
+			}
+			_ => return None,
+		})
+	}
+
+	/// Parses what follows "rating" in a token, like ">=4"
+	fn parse_rating(self, rest: &str) -> Option<PhotoQuery>
+	{
+		let (operator, value) = rest.split_at(rest.find(|c: char| c.is_ascii_digit())?);
+		let value: u32 = value.parse().ok()?;
+		Some(match operator
+		{
+			">=" => self.min_rating(value),
+			">" => self.min_rating(value.checked_add(1)?),
+			"<=" => self.max_rating(value),
+			"<" => self.max_rating(value.checked_sub(1)?),
+			"=" | ":" => self.min_rating(value).max_rating(value),
+			_ => return None,
+		})
+	}
+}
+
+impl PhotoQuery // Text syntax
+{
+	/// Writes the query with the syntax of [PhotoQuery::parse()], its limit and its offset are not written.
+	///
+	/// Parsing the text gives back the same filters and the same order, except for the values
+	/// the syntax cannot hold: coordinates outside of the globe and dates after the year 9999.
+	pub fn to_text(&self) -> String
+	{
+		let mut tokens: Vec<String> = self.filters.iter().map(|filter| match filter
+		{
+			Filter::Tag(path) => format!("tag:{}", quote(path)),
+			Filter::Album(id) => format!("album_id:{}", id),
+			Filter::AlbumName(name) => format!("album:{}", quote(name)),
+			Filter::MinRating(rating) => format!("rating>={}", rating),
+			Filter::MaxRating(rating) => format!("rating<={}", rating),
+			Filter::Starred(true) => String::from("starred:yes"),
+			Filter::Starred(false) => String::from("starred:no"),
+			Filter::ColorLabel(label) => format!("label:{}", label.name()),
+			Filter::Flag(PickFlag::Picked) => String::from("flag:picked"),
+			Filter::Flag(PickFlag::Rejected) => String::from("flag:rejected"),
+			Filter::Flag(PickFlag::Unflagged) => String::from("flag:none"),
+			Filter::Camera(camera) => format!("camera:{}", quote(camera)),
+			Filter::Lens(lens) => format!("lens:{}", quote(lens)),
+			Filter::Format(format) => format!("format:{}", quote(format)),
+			Filter::TakenFrom(day) => format!("date:{}..", day),
+			Filter::TakenUntil(day) => format!("date:..{}", day),
+			Filter::Area { south, west, north, east } => format!("area:{},{}..{},{}", south, west, north, east),
+			Filter::Text(text) => quote(text),
+		}).collect();
+		if self.sort != PhotoSort::Id || self.order != SortOrder::Ascending
+		{
+			let name = match self.sort
+			{
+				PhotoSort::Id => "id",
+				PhotoSort::ImportDate => "imported",
+				PhotoSort::CreationDate => "date",
+				PhotoSort::Rating => "rating",
+				PhotoSort::Filename => "name",
+			};
+			let order = if self.order == SortOrder::Descending { "-" } else { "" };
+			tokens.push(format!("sort:{}{}", order, name));
+		}
+		tokens.join(" ")
+	}
+
+	/// Writes the query like [PhotoQuery::to_text()] to store it,
+	/// fails with InvalidInput if parsing the text would not give back the same query
+	pub(crate) fn to_stored_text(&self) -> Result<String, OsplError>
+	{
+		let text = self.to_text();
+		let unpaged = PhotoQuery { limit: None, offset: 0, ..self.clone() };
+		match PhotoQuery::parse(&text)? == unpaged
+		{
+			true => Ok(text),
+			false => Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)),
+		}
+	}
+}
+
+impl PhotoQuery // SQL
+{
+	/// Writes the WHERE clause of the query and the values bound to its parameters
+	fn to_condition(&self) -> (String, Vec<Value>)
+	{
+		let mut values: Vec<Value> = Vec::new();
+		let mut bind = |value: Value| -> String
+		{
+			values.push(value);
+			format!("?{}", values.len())
+		};
+		let mut conditions = Vec::new();
+		for filter in &self.filters
+		{
+			conditions.push(match filter
+			{
+				Filter::Tag(path) =>
+				{
+					let path = bind(Value::Text(path.clone()));
+					format!("id IN (SELECT contained_photo FROM photos_tags_map \
+						INNER JOIN tags_paths ON tags_paths.id = photos_tags_map.containing_tag \
+						WHERE tags_paths.path = {path} OR substr(tags_paths.path, 1, length({path}) + 1) = {path} || '/')")
+				}
+				Filter::Album(id) => format!("id IN (SELECT contained_photo FROM photos_albums_map \
+					WHERE containing_album = {})", bind(Value::Integer(*id as i64))),
+				Filter::AlbumName(name) => format!("id IN (SELECT contained_photo FROM photos_albums_map \
+					INNER JOIN albums ON albums.id = photos_albums_map.containing_album WHERE albums.name = {})",
+					bind(Value::Text(name.clone()))),
+				Filter::MinRating(rating) => format!("IFNULL(rating, 0) >= {}", bind(Value::Integer(*rating as i64))),
+				Filter::MaxRating(rating) => format!("IFNULL(rating, 0) <= {}", bind(Value::Integer(*rating as i64))),
+				Filter::Starred(starred) => format!("IFNULL(starred, 0) = {}", bind(Value::Integer(*starred as i64))),
+				Filter::ColorLabel(label) => format!("color_label = {}", bind(Value::Text(label.name().to_string()))),
+				Filter::Flag(flag) => format!("IFNULL(flag, 0) = {}", bind(Value::Integer(flag.to_value() as i64))),
+				Filter::Camera(camera) =>
+				{
+					let camera = bind(Value::Text(camera.clone()));
+					format!("(instr(lower(make), lower({camera})) > 0 OR instr(lower(model), lower({camera})) > 0)")
+				}
+				Filter::Lens(lens) => format!("instr(lower(lens), lower({})) > 0", bind(Value::Text(lens.clone()))),
+				Filter::Format(format) => format!("lower(format) = {}", bind(Value::Text(format.clone()))),
+				Filter::TakenFrom(day) => format!("date(creation_datetime) >= {}", bind(Value::Text(day.to_string()))),
+				Filter::TakenUntil(day) => format!("date(creation_datetime) <= {}", bind(Value::Text(day.to_string()))),
+				Filter::Area { south, west, north, east } =>
+				{
+					let latitude = format!("latitude BETWEEN {} AND {}", bind(Value::Real(*south)), bind(Value::Real(*north)));
+					let (west, east) = (bind(Value::Real(*west)), bind(Value::Real(*east)));
+					// The area crosses the 180th meridian when its west side is greater than its east side
+					format!("({latitude} AND CASE WHEN {west} <= {east} THEN longitude BETWEEN {west} AND {east} \
+						ELSE longitude >= {west} OR longitude <= {east} END)")
+				}
+				Filter::Text(text) =>
+				{
+					let text = bind(Value::Text(text.clone()));
+					format!("(instr(lower(filename), lower({text})) > 0 OR instr(lower(IFNULL(title, '')), lower({text})) > 0 \
+						OR instr(lower(IFNULL(comment, '')), lower({text})) > 0)")
+				}
+			});
+		}
+		if conditions.is_empty()
+		{
+			conditions.push(String::from("1"));
+		}
+		(conditions.join(" AND "), values)
+	}
+
+	/// Writes the ORDER BY and LIMIT clauses of the query, the photos are always in the same order
+	fn to_order(&self) -> String
+	{
+		let order = match self.order
+		{
+			SortOrder::Ascending => "ASC",
+			SortOrder::Descending => "DESC",
+		};
+		let column = match self.sort
+		{
+			PhotoSort::Id => "",
+			PhotoSort::ImportDate => "import_datetime",
+			PhotoSort::CreationDate => "creation_datetime IS NULL, creation_datetime",
+			PhotoSort::Rating => "IFNULL(rating, 0)",
+			PhotoSort::Filename => "filename COLLATE NOCASE",
+		};
+		let mut clause = match column
+		{
+			"" => format!(" ORDER BY id {}", order),
+			column => format!(" ORDER BY {} {}, id {}", column, order, order),
+		};
+		if self.limit.is_some() || self.offset > 0
+		{
+			clause += &format!(" LIMIT {} OFFSET {}", self.limit.map(|limit| limit as i64).unwrap_or(-1), self.offset);
+		}
+		clause
+	}
+}
+
+impl Library
+{
+	/// Get the photos matching a query, in the order and the page asked by the query
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::query::PhotoQuery;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let query = PhotoQuery::parse("camera:\"EOS 70D\" date:2021-06..2021-08").unwrap();
+	/// for photo in library.search_photos(&query).unwrap()
+	/// {
+	///     println!("photo id: {} | name: {}", photo.id(), photo.get_filename());
+	/// }
+	/// ```
+	pub fn search_photos(&self, query: &PhotoQuery) -> Result<Vec<Photo>, OsplError>
+	{
+		let (condition, values) = query.to_condition();
+		Photo::list_where(&self.db, &(condition + &query.to_order()), rusqlite::params_from_iter(values))
+	}
+
+	/// Get the number of photos matching a query, its limit and its offset are not used
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::query::PhotoQuery;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let count = library.count_photos(&PhotoQuery::new().starred(true)).unwrap();
+	/// println!("{} favourites", count);
+	/// ```
+	pub fn count_photos(&self, query: &PhotoQuery) -> Result<u32, OsplError>
+	{
+		let (condition, values) = query.to_condition();
+		Photo::count_where(&self.db, &condition, rusqlite::params_from_iter(values))
+	}
+}
+
+/// A word of a text query, without its quotes
+#[derive(Default)]
+struct Token
+{
+	text:		String,
+	/// Part of the word was between double quotes
+	quoted:		bool,
+	/// The word starts with a double quote, it is only text
+	literal:	bool,
+}
+
+/// Splits a text query on the spaces that are not between double quotes, and removes the quotes.
+/// Between double quotes, a backslash keeps the next character as it is.
+fn tokenize(text: &str) -> Result<Vec<Token>, OsplError>
+{
+	let mut tokens = Vec::new();
+	let mut token = Token::default();
+	let mut quoted = false;
+	let mut chars = text.chars();
+	while let Some(c) = chars.next()
+	{
+		match c
+		{
+			'"' =>
+			{
+				token.literal |= !token.quoted && token.text.is_empty();
+				token.quoted = true;
+				quoted = !quoted;
+			}
+			'\\' if quoted => token.text.push(chars.next().ok_or(OsplError::IoError(std::io::ErrorKind::InvalidInput))?),
+			c if c.is_whitespace() && !quoted =>
+			{
+				if !token.text.is_empty() || token.quoted
+				{
+					tokens.push(std::mem::take(&mut token));
+				}
+			}
+			c => token.text.push(c),
+		}
+	}
+	if quoted
+	{
+		return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+	}
+	if !token.text.is_empty() || token.quoted
+	{
+		tokens.push(token);
+	}
+	Ok(tokens)
+}
+
+/// Writes a value between double quotes when it would not be read back as it is:
+/// when it is empty or contains spaces, quotes, or characters separating a filter from its value
+fn quote(value: &str) -> String
+{
+	match value.is_empty() || value.contains(|c: char| c.is_whitespace() || ":<>=\"\\".contains(c))
+	{
+		true => format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+		false => value.to_string(),
+	}
+}
+
+/// Parses a latitude and a longitude separated by a comma, like "48.85,2.35"
+fn parse_coordinates(text: &str) -> Option<(f64, f64)>
+{
+	let (latitude, longitude) = text.split_once(',')?;
+	let (latitude, longitude): (f64, f64) = (latitude.parse().ok()?, longitude.parse().ok()?);
+	match (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+	{
+		true => Some((latitude, longitude)),
+		false => None,
+	}
+}
+
+/// Parses a year, a month or a day like "2023", "2023-06" or "2023-06-18",
+/// as its first day or as its last day when `end` is true
+fn parse_date(text: &str, end: bool) -> Option<NaiveDate>
+{
+	let fields: Vec<&str> = text.split('-').collect();
+	let year: i32 = fields[0].parse().ok()?;
+	match fields[..]
+	{
+		[_] if end => NaiveDate::from_ymd_opt(year, 12, 31),
+		[_] => NaiveDate::from_ymd_opt(year, 1, 1),
+		[_, month] =>
+		{
+			let first = NaiveDate::from_ymd_opt(year, month.parse().ok()?, 1)?;
+			match end
+			{
+				true => first.checked_add_months(chrono::Months::new(1))?.pred_opt(),
+				false => Some(first),
+			}
+		}
+		[_, month, day] => NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?),
+		_ => None,
+	}
+}
@@ -0,0 +1,256 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Photo;
+
+/// Highest rating a photo can have, 0 means it is not rated
+pub const MAX_RATING: u32 = 5;
+
+/// Colors a photo can be labelled with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorLabel
+{
+	Red,
+	Orange,
+	Yellow,
+	Green,
+	Blue,
+	Purple,
+}
+
+/// Whether a photo was kept or discarded while sorting them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PickFlag
+{
+	#[default]
+	Unflagged,
+	Picked,
+	Rejected,
+}
+
+impl ColorLabel
+{
+	pub const ALL: [ColorLabel; 6] = [ColorLabel::Red, ColorLabel::Orange, ColorLabel::Yellow,
+		ColorLabel::Green, ColorLabel::Blue, ColorLabel::Purple];
+
+	/// Name of the color, the way it is stored in the database
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			ColorLabel::Red => "red",
+			ColorLabel::Orange => "orange",
+			ColorLabel::Yellow => "yellow",
+			ColorLabel::Green => "green",
+			ColorLabel::Blue => "blue",
+			ColorLabel::Purple => "purple",
+		}
+	}
+
+	pub(crate) fn from_name(name: &str) -> Option<ColorLabel>
+	{
+		ColorLabel::ALL.into_iter().find(|label| label.name() == name)
+	}
+}
+
+impl PickFlag
+{
+	/// Value of the `flag` column of the photos table
+	pub(crate) fn to_value(self) -> i32
+	{
+		match self
+		{
+			PickFlag::Unflagged => 0,
+			PickFlag::Picked => 1,
+			PickFlag::Rejected => -1,
+		}
+	}
+
+	pub(crate) fn from_value(value: i32) -> PickFlag
+	{
+		match value
+		{
+			1 => PickFlag::Picked,
+			-1 => PickFlag::Rejected,
+			_ => PickFlag::Unflagged,
+		}
+	}
+}
+
+impl Library
+{
+	/// Rates a photo from 1 to [MAX_RATING] stars, 0 removes its rating
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_rating(1, 4).unwrap();
+	/// assert_eq!(library.get_photo_from_id(1).unwrap().rating(), 4);
+	/// ```
+	pub fn set_rating(&self, id: u32, rating: u32) -> Result<(), OsplError>
+	{
+		self.set_rating_of_photos(&[id], rating)
+	}
+
+	/// Same as [Library::set_rating()] for many photos at once,
+	/// none of them is changed if one of them is not found
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_rating_of_photos(&[1, 2, 3], 5).unwrap();
+	/// ```
+	pub fn set_rating_of_photos(&self, ids: &[u32], rating: u32) -> Result<(), OsplError>
+	{
+		if rating > MAX_RATING
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		Photo::update_column_of_photos(&self.db, ids, "rating", rating)
+	}
+
+	/// Marks a photo as a favourite, or removes it from the favourites
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_starred(1, true).unwrap();
+	/// assert!(library.get_photo_from_id(1).unwrap().starred());
+	/// ```
+	pub fn set_starred(&self, id: u32, starred: bool) -> Result<(), OsplError>
+	{
+		self.set_starred_of_photos(&[id], starred)
+	}
+
+	/// Same as [Library::set_starred()] for many photos at once,
+	/// none of them is changed if one of them is not found
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_starred_of_photos(&[1, 2, 3], true).unwrap();
+	/// ```
+	pub fn set_starred_of_photos(&self, ids: &[u32], starred: bool) -> Result<(), OsplError>
+	{
+		Photo::update_column_of_photos(&self.db, ids, "starred", starred)
+	}
+
+	/// Labels a photo with a color, None removes its label
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::ColorLabel;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_color_label(1, Some(ColorLabel::Green)).unwrap();
+	/// ```
+	pub fn set_color_label(&self, id: u32, label: Option<ColorLabel>) -> Result<(), OsplError>
+	{
+		self.set_color_label_of_photos(&[id], label)
+	}
+
+	/// Same as [Library::set_color_label()] for many photos at once,
+	/// none of them is changed if one of them is not found
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::ColorLabel;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_color_label_of_photos(&[1, 2, 3], Some(ColorLabel::Red)).unwrap();
+	/// ```
+	pub fn set_color_label_of_photos(&self, ids: &[u32], label: Option<ColorLabel>) -> Result<(), OsplError>
+	{
+		Photo::update_column_of_photos(&self.db, ids, "color_label", label.map(|label| label.name()))
+	}
+
+	/// Picks or rejects a photo, [PickFlag::Unflagged] removes its flag
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::PickFlag;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_flag(1, PickFlag::Rejected).unwrap();
+	/// ```
+	pub fn set_flag(&self, id: u32, flag: PickFlag) -> Result<(), OsplError>
+	{
+		self.set_flag_of_photos(&[id], flag)
+	}
+
+	/// Same as [Library::set_flag()] for many photos at once,
+	/// none of them is changed if one of them is not found
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::PickFlag;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_flag_of_photos(&[1, 2, 3], PickFlag::Picked).unwrap();
+	/// ```
+	pub fn set_flag_of_photos(&self, ids: &[u32], flag: PickFlag) -> Result<(), OsplError>
+	{
+		Photo::update_column_of_photos(&self.db, ids, "flag", flag.to_value())
+	}
+
+	/// Get the photos rated at least `min_rating` stars, the best rated first
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for photo in library.list_photos_with_rating(4).unwrap()
+	/// {
+	///     println!("photo {} has {} stars", photo.id(), photo.rating());
+	/// }
+	/// ```
+	pub fn list_photos_with_rating(&self, min_rating: u32) -> Result<Vec<Photo>, OsplError>
+	{
+		Photo::list_where(&self.db, "rating >= ?1 ORDER BY rating DESC, id", [min_rating])
+	}
+
+	/// Get the favourite photos
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let favourites = library.list_starred_photos().unwrap();
+	/// println!("{} favourites", favourites.len());
+	/// ```
+	pub fn list_starred_photos(&self) -> Result<Vec<Photo>, OsplError>
+	{
+		Photo::list_where(&self.db, "starred = 1 ORDER BY id", [])
+	}
+
+	/// Get the photos labelled with a color
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::ColorLabel;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let red = library.list_photos_with_color_label(ColorLabel::Red).unwrap();
+	/// ```
+	pub fn list_photos_with_color_label(&self, label: ColorLabel) -> Result<Vec<Photo>, OsplError>
+	{
+		Photo::list_where(&self.db, "color_label = ?1 ORDER BY id", [label.name()])
+	}
+
+	/// Get the photos having a flag, [PickFlag::Unflagged] gets the photos that were not sorted yet
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::rating::PickFlag;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let rejected = library.list_photos_with_flag(PickFlag::Rejected).unwrap();
+	/// ```
+	pub fn list_photos_with_flag(&self, flag: PickFlag) -> Result<Vec<Photo>, OsplError>
+	{
+		Photo::list_where(&self.db, "IFNULL(flag, 0) = ?1 ORDER BY id", [flag.to_value()])
+	}
+}
@@ -0,0 +1,99 @@
+use crate::Library;
+use crate::OsplError;
+
+/// Written before each matching word of a snippet
+pub const HIGHLIGHT_START: &str = "<mark>";
+/// Written after each matching word of a snippet
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+/// Kind of element found by [Library::search_text()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchHitKind
+{
+	Photo,
+	Album,
+	Collection,
+	Tag,
+}
+
+/// An element whose name, title or comment matches a text search
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit
+{
+	pub kind:		SearchHitKind,
+	/// Id of the photo, album, collection or tag
+	pub id:			u32,
+	/// The part of the text that matches, with the matching words between
+	/// [HIGHLIGHT_START] and [HIGHLIGHT_END]
+	pub snippet:	String,
+}
+
+impl SearchHitKind
+{
+	/// Finds the element of a row of the search index, see database.sql
+	fn from_rowid(rowid: i64) -> (SearchHitKind, u32)
+	{
+		let kind = match rowid % 4
+		{
+			0 => SearchHitKind::Photo,
+			1 => SearchHitKind::Album,
+			2 => SearchHitKind::Collection,
+			_ => SearchHitKind::Tag,
+		};
+		(kind, (rowid / 4) as u32)
+	}
+}
+
+impl Library
+{
+	/// Searches the names, titles and comments of the photos, albums, collections and tags,
+	/// the best matches first
+	///
+	/// Every word of `query` has to be found, words are matched from their start and
+	/// without case or accents: "eté plag" finds "Été à la plage".
+	/// Names count more than titles, and titles more than comments.
+	/// The photos in the trash are not searched.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for hit in library.search_text("pizza party").unwrap()
+	/// {
+	///     println!("{:?} {}: {}", hit.kind, hit.id, hit.snippet);
+	/// }
+	/// ```
+	pub fn search_text(&self, query: &str) -> Result<Vec<SearchHit>, OsplError>
+	{
+		let query = match fts_query(query)
+		{
+			Some(query) => query,
+			None => return Ok(Vec::new()),
+		};
+		let mut stmt = self.db.connection.prepare_cached("SELECT rowid, snippet(search_index, -1, ?2, ?3, '…', 12) \
+			FROM search_index WHERE search_index MATCH ?1 \
+			AND (rowid % 4 != 0 OR rowid / 4 IN (SELECT id FROM photos WHERE deleted_datetime IS NULL)) ORDER BY bm25(search_index, 10.0, 5.0, 1.0), rowid")?;
+		let mut rows = stmt.query((query, HIGHLIGHT_START, HIGHLIGHT_END))?;
+		let mut hits = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			let (kind, id) = SearchHitKind::from_rowid(row.get(0)?);
+			hits.push(SearchHit { kind, id, snippet: row.get(1)? });
+		}
+		Ok(hits)
+	}
+}
+
+/// Turns the words typed by the user into an FTS5 query matching all of them as prefixes,
+/// so the FTS5 operators and quotes they contain are searched like any other character
+fn fts_query(text: &str) -> Option<String>
+{
+	let words: Vec<String> = text.split_whitespace()
+		.map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+		.collect();
+	if words.is_empty()
+	{
+		return None;
+	}
+	Some(words.join(" "))
+}
@@ -0,0 +1,348 @@
+use crate::Library;
+use crate::Database;
+use crate::OsplError;
+use crate::Error;
+use crate::Photo;
+use crate::Tag;
+use crate::element::tag::TAG_PATH_SEPARATOR;
+use crate::api::library::DeletePolicy;
+
+impl Library
+{
+	/// Creates a tag with the provided name
+	///
+	/// The name can be a path like "Places/France/Paris", the missing tags
+	/// of the path are created and the last one is returned.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let tag = library.create_tag("beach").unwrap();
+	/// assert_eq!(tag.name(), "beach");
+	/// let paris = library.create_tag("Places/France/Paris").unwrap();
+	/// assert_eq!(paris.name(), "Paris");
+	/// assert_eq!(paris.path(), "Places/France/Paris");
+	/// ```
+	pub fn create_tag(&self, name: &str) -> Result<Tag, OsplError>
+	{
+		let db = &self.db;
+
+		let names = split_tag_path(name)?;
+		let (leaf, ancestors) = names.split_last().unwrap();
+		let mut parent = None;
+		for ancestor in ancestors
+		{
+			parent = match Tag::find(db, ancestor, parent)?
+			{
+				Some(id) => Some(id),
+				None => Some(db.insert(&Tag::new_with_name(ancestor, parent))?),
+			};
+		}
+		if Tag::find(db, leaf, parent)?.is_some()
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists));
+		}
+		let id = db.insert(&Tag::new_with_name(leaf, parent))?;
+		self.get_tag_from_id(id)
+	}
+
+	/// Get a Tag element from its full path, like "Places/France/Paris"
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.create_tag("Places/France/Paris").unwrap();
+	/// let france = library.get_tag_from_path("Places/France").unwrap();
+	/// assert_eq!(france.name(), "France");
+	/// ```
+	pub fn get_tag_from_path(&self, path: &str) -> Result<Tag, OsplError>
+	{
+		let db = &self.db;
+
+		let mut parent = None;
+		for name in split_tag_path(path)?
+		{
+			parent = match Tag::find(db, name, parent)?
+			{
+				Some(id) => Some(id),
+				None => return Err(OsplError::IoError(std::io::ErrorKind::NotFound)),
+			};
+		}
+		self.get_tag_from_id(parent.unwrap())
+	}
+
+	/// Get a Tag element with its id
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let tag = library.get_tag_from_id(3).unwrap();
+	/// println!("tag id: {} | path: {}", tag.id(), tag.path());
+	/// ```
+	pub fn get_tag_from_id(&self, id: u32) -> Result<Tag, OsplError>
+	{
+		let db = &self.db;
+		let mut tag = Tag::default();
+		db.load_from_id(&mut tag, id)?;
+		Ok(tag)
+	}
+
+	/// Get all tags in a Vec<Tag>, sorted by path
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for tag in library.list_all_tags().unwrap()
+	/// {
+	///     println!("tag id: {} | name: {}", tag.id(), tag.name());
+	/// }
+	/// ```
+	pub fn list_all_tags(&self) -> Result<Vec<Tag>, OsplError>
+	{
+		let db = &self.db;
+		<Tag as crate::element::traits::ElementListing<Tag>>::list_all(db, &self.fs)
+	}
+
+	/// Rename a tag using its id
+	///
+	/// The spaces around the name are removed. Fails with AlreadyExists if a tag
+	/// with the same parent already has this name.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let tag = library.create_tag("beach").unwrap();
+	/// library.rename_tag_with_id(tag.id(), "seaside").unwrap();
+	/// assert_eq!(library.get_tag_from_id(tag.id()).unwrap().name(), "seaside");
+	/// ```
+	pub fn rename_tag_with_id(&self, id: u32, new_name: &str) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+
+		let new_name = new_name.trim();
+		if new_name.is_empty()
+		{
+			return Err(OsplError::InternalError(Error::EmptyName));
+		}
+		if new_name.contains(TAG_PATH_SEPARATOR)
+		{
+			return Err(OsplError::InternalError(Error::InvalidName));
+		}
+		let tag = self.get_tag_from_id(id)?;
+		check_tag_name_free(db, new_name, tag.parent(), id)?;
+		db.rename(&tag, new_name)
+	}
+
+	/// Moves a tag, with all the tags below it, under another tag.
+	/// With None as parent the tag is moved to the root of the hierarchy.
+	/// Fails with AlreadyExists if a tag with the same name is already there.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let places = library.create_tag("Places").unwrap();
+	/// let paris = library.create_tag("Paris").unwrap();
+	/// library.move_tag_by_id(paris.id(), Some(places.id())).unwrap();
+	/// assert_eq!(library.get_tag_from_id(paris.id()).unwrap().path(), "Places/Paris");
+	/// ```
+	pub fn move_tag_by_id(&self, id: u32, parent: Option<u32>) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let tag = self.get_tag_from_id(id)?;
+		if let Some(parent) = parent
+		{
+			self.get_tag_from_id(parent)?;
+		}
+		check_tag_name_free(db, &tag.name(), parent, id)?;
+		tag.move_to(db, parent)
+	}
+
+	/// Get the tags directly below a tag, sorted by name
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let places = library.get_tag_from_path("Places").unwrap();
+	/// for tag in library.list_tag_children(places.id()).unwrap()
+	/// {
+	///     println!("tag: {}", tag.path());
+	/// }
+	/// ```
+	pub fn list_tag_children(&self, id: u32) -> Result<Vec<Tag>, OsplError>
+	{
+		let db = &self.db;
+		<Tag as crate::element::traits::InsideElementListing<Tag>>::list_inside(db, id)
+	}
+
+	/// Merges a tag into another one
+	///
+	/// Every photo tagged with `source` gets tagged with `target`, the tags below `source`
+	/// are moved below `target`, then `source` is deleted.
+	/// A tag below `source` named like a tag below `target` is merged into it.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let seaside = library.create_tag("seaside").unwrap();
+	/// let beach = library.create_tag("beach").unwrap();
+	/// library.merge_tags(seaside.id(), beach.id()).unwrap();
+	/// ```
+	pub fn merge_tags(&self, source: u32, target: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let source = self.get_tag_from_id(source)?;
+		let target = self.get_tag_from_id(target)?;
+		if source.id() == target.id()
+		{
+			return Ok(());
+		}
+		if Tag::descendants(db, source.id())?.contains(&target.id())
+		{
+			return Err(OsplError::InternalError(Error::MovedInsideItself));
+		}
+		source.merge_into(db, &target)
+	}
+
+	/// Deletes a tag with its id and all the tags below it, photos are only detached from them
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.delete_tag_by_id(3).unwrap();
+	/// ```
+	pub fn delete_tag_by_id(&self, id: u32) -> Result<(), OsplError>
+	{
+		self.delete_tag_with_policy(id, DeletePolicy::Cascade)
+	}
+
+	/// Deletes a tag with its id, `policy` tells what happens to its photos and the tags below it:
+	/// - [DeletePolicy::Cascade] deletes the tags below it, like [Library::delete_tag_by_id()]
+	/// - [DeletePolicy::Reassign] merges the tag into another one, see [Library::merge_tags()]
+	/// - [DeletePolicy::Refuse] fails with [Error::NotEmpty] if the tag has photos or tags below it
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::library::DeletePolicy;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.delete_tag_with_policy(3, DeletePolicy::Refuse).unwrap();
+	/// ```
+	pub fn delete_tag_with_policy(&self, id: u32, policy: DeletePolicy) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let tag = self.get_tag_from_id(id)?;
+		match policy
+		{
+			DeletePolicy::Cascade => db.delete(&tag),
+			DeletePolicy::Reassign(target) if target == id => Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)),
+			DeletePolicy::Reassign(target) => self.merge_tags(id, target),
+			DeletePolicy::Refuse =>
+			{
+				if Tag::is_attached(db, id)? || !self.list_tag_children(id)?.is_empty()
+				{
+					return Err(OsplError::InternalError(Error::NotEmpty));
+				}
+				db.delete(&tag)
+			}
+		}
+	}
+
+	/// Tags all the given photos
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let tag = library.create_tag("beach").unwrap();
+	/// library.tag_photos(&[1, 2, 3], tag.id()).unwrap();
+	/// ```
+	pub fn tag_photos(&self, photos: &[u32], tag: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let tag = self.get_tag_from_id(tag)?;
+		for photo in photos
+		{
+			self.get_photo_from_id(*photo)?;
+		}
+		tag.attach(db, photos)
+	}
+
+	/// Removes a tag from all the given photos
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.untag_photos(&[1, 2], 3).unwrap();
+	/// ```
+	pub fn untag_photos(&self, photos: &[u32], tag: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let tag = self.get_tag_from_id(tag)?;
+		tag.detach(db, photos)
+	}
+
+	/// Get all tags of a photo, sorted by path
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for tag in library.list_tags_of_photo(1).unwrap()
+	/// {
+	///     println!("tag: {}", tag.name());
+	/// }
+	/// ```
+	pub fn list_tags_of_photo(&self, photo: u32) -> Result<Vec<Tag>, OsplError>
+	{
+		let db = &self.db;
+		<Photo as crate::element::traits::InsideElementListing<Tag>>::list_inside(db, photo)
+	}
+
+	/// Get all photos tagged with a tag or with any tag below it
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for photo in library.list_photos_with_tag(3).unwrap()
+	/// {
+	///     println!("photo id: {} | name: {}", photo.id(), photo.get_filename());
+	/// }
+	/// ```
+	pub fn list_photos_with_tag(&self, tag: u32) -> Result<Vec<Photo>, OsplError>
+	{
+		let db = &self.db;
+		<Tag as crate::element::traits::InsideElementListing<Photo>>::list_inside(db, tag)
+	}
+}
+
+/// Fails with AlreadyExists if a tag other than `id` is named `name` below `parent`
+fn check_tag_name_free(db: &Database, name: &str, parent: Option<u32>, id: u32) -> Result<(), OsplError>
+{
+	match Tag::find(db, name, parent)?
+	{
+		Some(other) if other != id => Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists)),
+		_ => Ok(()),
+	}
+}
+
+/// Splits a tag path like "Places/France/Paris" into the names of its tags
+fn split_tag_path(path: &str) -> Result<Vec<&str>, OsplError>
+{
+	let names: Vec<&str> = path.split(TAG_PATH_SEPARATOR).map(|name| name.trim()).collect();
+	if names.iter().any(|name| name.is_empty())
+	{
+		return Err(OsplError::InternalError(Error::EmptyName));
+	}
+	Ok(names)
+}
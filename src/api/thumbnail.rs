@@ -0,0 +1,355 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Error;
+use crate::Photo;
+use crate::thumbnails;
+use crate::transaction::LibraryTransaction;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use log::warn;
+
+/// The sizes a photo can be shown at, each one has its own thumbnails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThumbnailPreset
+{
+	/// Small thumbnails of the photo grid, rendered when the photo is imported
+	Grid,
+	/// Bigger thumbnails for a preview pane, rendered when they are first asked for
+	Preview,
+	/// Thumbnails as big as a screen, rendered when they are first asked for
+	FullScreen,
+}
+
+/// The file formats thumbnails can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailFormat
+{
+	Jpeg,
+	/// Always lossless: the quality of its settings must be 100, the library cannot encode lossy WebP
+	WebP,
+}
+
+/// How the thumbnails of a preset are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailSettings
+{
+	/// The thumbnail fits in `max_width` x `max_height`, photos are never scaled up
+	pub max_width:	u32,
+	pub max_height:	u32,
+	pub format:		ThumbnailFormat,
+	/// JPEG quality, from 1 to 100. WebP thumbnails are lossless, their quality is always 100
+	pub quality:	u8,
+}
+
+impl ThumbnailPreset
+{
+	pub const ALL: [ThumbnailPreset; 3] = [ThumbnailPreset::Grid, ThumbnailPreset::Preview, ThumbnailPreset::FullScreen];
+
+	/// Name of the preset, used for its directory in `thumbnails/`
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			ThumbnailPreset::Grid => "grid",
+			ThumbnailPreset::Preview => "preview",
+			ThumbnailPreset::FullScreen => "fullscreen",
+		}
+	}
+
+	/// Settings used until [Library::set_thumbnail_settings()] is called
+	pub fn default_settings(&self) -> ThumbnailSettings
+	{
+		match self
+		{
+			ThumbnailPreset::Grid => ThumbnailSettings { max_width: u32::MAX, max_height: 325, format: ThumbnailFormat::Jpeg, quality: 85 },
+			ThumbnailPreset::Preview => ThumbnailSettings { max_width: 1280, max_height: 1280, format: ThumbnailFormat::Jpeg, quality: 85 },
+			ThumbnailPreset::FullScreen => ThumbnailSettings { max_width: 3840, max_height: 2160, format: ThumbnailFormat::Jpeg, quality: 90 },
+		}
+	}
+
+	fn setting_name(&self) -> String
+	{
+		format!("thumbnail.{}", self.name())
+	}
+}
+
+impl ThumbnailFormat
+{
+	pub const ALL: [ThumbnailFormat; 2] = [ThumbnailFormat::Jpeg, ThumbnailFormat::WebP];
+
+	/// Extension of the thumbnail files
+	pub fn extension(&self) -> &'static str
+	{
+		match self
+		{
+			ThumbnailFormat::Jpeg => "jpg",
+			ThumbnailFormat::WebP => "webp",
+		}
+	}
+}
+
+impl ThumbnailSettings
+{
+	/// Writes the settings the way they are stored in the settings table, like "1280 1280 jpg 85"
+	fn to_setting(self) -> String
+	{
+		format!("{} {} {} {}", self.max_width, self.max_height, self.format.extension(), self.quality)
+	}
+
+	fn from_setting(value: &str) -> Option<ThumbnailSettings>
+	{
+		let fields: Vec<&str> = value.split(' ').collect();
+		match fields[..]
+		{
+			[max_width, max_height, format, quality] => Some(ThumbnailSettings
+			{
+				max_width: max_width.parse().ok()?,
+				max_height: max_height.parse().ok()?,
+				format: ThumbnailFormat::ALL.into_iter().find(|f| f.extension() == format)?,
+				quality: quality.parse().ok()?,
+			}),
+			_ => None,
+		}
+	}
+}
+
+impl Library
+{
+	/// Get the settings the thumbnails of a preset are rendered with
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::thumbnail::ThumbnailPreset;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let settings = library.thumbnail_settings(ThumbnailPreset::Grid).unwrap();
+	/// println!("grid thumbnails are {}px high", settings.max_height);
+	/// ```
+	pub fn thumbnail_settings(&self, preset: ThumbnailPreset) -> Result<ThumbnailSettings, OsplError>
+	{
+		Ok(self.db.setting(&preset.setting_name())?
+			.and_then(|value| ThumbnailSettings::from_setting(&value))
+			.unwrap_or_else(|| preset.default_settings()))
+	}
+
+	/// Changes the settings of a preset, they are saved in the library
+	///
+	/// The thumbnails already rendered for the preset are deleted. Grid thumbnails are
+	/// rendered again for every photo right away, which can take a while on a big library,
+	/// the other presets are rendered again when they are asked for.
+	///
+	/// Fails with [Error::InvalidSettings] if a size is 0, if the quality is not between 1 and 100,
+	/// or if the quality of WebP thumbnails is not 100.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::thumbnail::{ThumbnailFormat, ThumbnailPreset, ThumbnailSettings};
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let settings = ThumbnailSettings { max_width: 800, max_height: 800, format: ThumbnailFormat::WebP, quality: 100 };
+	/// library.set_thumbnail_settings(ThumbnailPreset::Preview, settings).unwrap();
+	/// ```
+	pub fn set_thumbnail_settings(&self, preset: ThumbnailPreset, settings: ThumbnailSettings) -> Result<(), OsplError>
+	{
+		if settings.max_width == 0 || settings.max_height == 0 || settings.quality == 0 || settings.quality > 100
+		{
+			return Err(OsplError::InternalError(Error::InvalidSettings));
+		}
+		if settings.format == ThumbnailFormat::WebP && settings.quality != 100
+		{
+			return Err(OsplError::InternalError(Error::InvalidSettings));
+		}
+		self.db.set_setting(&preset.setting_name(), &settings.to_setting())?;
+		let directory = self.fs.thumbnails_path().join(preset.name());
+		if directory.exists()
+		{
+			std::fs::remove_dir_all(directory)?;
+		}
+		if preset == ThumbnailPreset::Grid
+		{
+			for photo in self.list_all_photos()?
+			{
+				self.render_thumbnail(&photo, preset)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Get the path of the thumbnail of a photo, rendering it if it is not in the cache yet
+	///
+	/// Thumbnails rendered from an older version of the photo are all deleted,
+	/// the grid thumbnail and the asked one are rendered again.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// # use ospl::api::thumbnail::ThumbnailPreset;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let path = library.get_thumbnail(1, ThumbnailPreset::Preview).unwrap();
+	/// println!("preview of photo 1: {:?}", path);
+	/// ```
+	pub fn get_thumbnail(&self, id: u32, preset: ThumbnailPreset) -> Result<PathBuf, OsplError>
+	{
+		let mut photo = self.get_photo_from_id(id)?;
+		if photo.thumbnail_hash() != Some(thumbnails::source_hash(&photo))
+		{
+			self.clear_thumbnails(&mut photo)?;
+			// The grid thumbnail is expected to always be there
+			if preset != ThumbnailPreset::Grid
+			{
+				self.render_thumbnail(&photo, ThumbnailPreset::Grid)?;
+			}
+		}
+		let path = self.fs.thumbnail_path(&photo.get_filename(), preset, self.thumbnail_settings(preset)?.format);
+		if !path.exists()
+		{
+			return self.render_thumbnail(&photo, preset);
+		}
+		Ok(path)
+	}
+
+	/// Deletes every thumbnail of a photo and renders its grid thumbnail again
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.regenerate_thumbnail(1).unwrap();
+	/// ```
+	pub fn regenerate_thumbnail(&self, id: u32) -> Result<(), OsplError>
+	{
+		let mut photo = self.get_photo_from_id(id)?;
+		let mut transaction = LibraryTransaction::begin(&self.db, &self.fs)?;
+		self.replace_thumbnails(&mut transaction, &mut photo)?;
+		transaction.commit()
+	}
+
+	/// Deletes every thumbnail of the library and renders the grid thumbnails again
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.regenerate_all_thumbnails().unwrap();
+	/// ```
+	pub fn regenerate_all_thumbnails(&self) -> Result<(), OsplError>
+	{
+		for photo in self.list_all_photos()?
+		{
+			self.regenerate_thumbnail(photo.id())?;
+		}
+		Ok(())
+	}
+}
+
+impl Library // Thumbnails helpers
+{
+	/// Replaces the thumbnails of a library older than the presets, kept at the root of `thumbnails/`
+	/// in the format of their photo, by grid thumbnails.
+	///
+	/// Each photo is upgraded in its own transaction, an interrupted upgrade goes on at the next load.
+	pub(crate) fn upgrade_legacy_thumbnails(&self) -> Result<(), OsplError>
+	{
+		let mut legacy = HashSet::new();
+		for entry in std::fs::read_dir(self.fs.thumbnails_path())?
+		{
+			let path = entry?.path();
+			if path.is_file()
+			{
+				legacy.insert(path);
+			}
+		}
+		if legacy.is_empty()
+		{
+			return Ok(());
+		}
+		for mut photo in self.list_all_photos()?
+		{
+			let old = self.fs.thumbnails_path().join(photo.get_filename());
+			if !legacy.contains(&old)
+			{
+				continue;
+			}
+			let mut transaction = LibraryTransaction::begin(&self.db, &self.fs)?;
+			// A photo that cannot be rendered is left without thumbnail, the integrity check reports it
+			if let Err(e) = self.replace_thumbnails(&mut transaction, &mut photo)
+			{
+				warn!("could not render the thumbnail of {}: {:?}", photo.get_filename(), e);
+			}
+			transaction.remove(&old)?;
+			transaction.commit()?;
+		}
+		// Thumbnails of the photos in the trash or of no photo at all
+		for old in legacy.iter().filter(|old| old.exists())
+		{
+			std::fs::remove_file(old)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the thumbnails of a photo found in the cache, for every preset and every format
+	pub(crate) fn cached_thumbnails(&self, photo: &Photo) -> Result<Vec<PathBuf>, OsplError>
+	{
+		let mut thumbnails = Vec::new();
+		for preset in ThumbnailPreset::ALL
+		{
+			for format in ThumbnailFormat::ALL
+			{
+				let path = self.fs.thumbnail_path(&photo.get_filename(), preset, format);
+				if path.exists()
+				{
+					thumbnails.push(path);
+				}
+			}
+		}
+		Ok(thumbnails)
+	}
+
+	/// Deletes the cached thumbnails of a photo and marks them as rendered from its current version
+	fn clear_thumbnails(&self, photo: &mut Photo) -> Result<(), OsplError>
+	{
+		for thumbnail in self.cached_thumbnails(photo)?
+		{
+			std::fs::remove_file(thumbnail)?;
+		}
+		photo.set_thumbnail_hash(thumbnails::source_hash(photo));
+		photo.update_thumbnail_hash(&self.db)
+	}
+
+	/// Deletes every thumbnail of a photo and renders its grid thumbnail again, in the transaction.
+	/// The new thumbnail is rendered before anything is deleted.
+	pub(crate) fn replace_thumbnails(&self, transaction: &mut LibraryTransaction, photo: &mut Photo) -> Result<(), OsplError>
+	{
+		let settings = self.thumbnail_settings(ThumbnailPreset::Grid)?;
+		let data = thumbnails::encode(&thumbnails::render(&self.open_edited(photo)?, &settings)?, &settings)?;
+		for thumbnail in self.cached_thumbnails(photo)?
+		{
+			transaction.remove(thumbnail)?;
+		}
+		photo.set_thumbnail_hash(thumbnails::source_hash(photo));
+		photo.update_thumbnail_hash(&self.db)?;
+		let directory = self.fs.thumbnails_path().join(ThumbnailPreset::Grid.name());
+		if !directory.exists()
+		{
+			std::fs::create_dir_all(&directory)?;
+			transaction.created(directory);
+		}
+		let path = self.fs.thumbnail_path(&photo.get_filename(), ThumbnailPreset::Grid, settings.format);
+		transaction.created(&path);
+		std::fs::write(path, data)?;
+		Ok(())
+	}
+
+	/// Renders the thumbnail of a photo for a preset from its edited original, and returns its path
+	fn render_thumbnail(&self, photo: &Photo, preset: ThumbnailPreset) -> Result<PathBuf, OsplError>
+	{
+		let settings = self.thumbnail_settings(preset)?;
+		let img = self.open_edited(photo)?;
+		let data = thumbnails::encode(&thumbnails::render(&img, &settings)?, &settings)?;
+		let path = self.fs.thumbnail_path(&photo.get_filename(), preset, settings.format);
+		std::fs::create_dir_all(self.fs.thumbnails_path().join(preset.name()))?;
+		std::fs::write(&path, data)?;
+		Ok(path)
+	}
+}
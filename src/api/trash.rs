@@ -0,0 +1,187 @@
+use crate::Library;
+use crate::OsplError;
+use crate::Album;
+use crate::Photo;
+use crate::transaction::LibraryTransaction;
+
+use chrono::Duration;
+
+/// Days a photo stays in the trash when [Library::set_trash_retention()] was never called
+pub const DEFAULT_TRASH_RETENTION: u32 = 30;
+
+/// Name of the retention period in the settings table
+const RETENTION_SETTING: &str = "trash.retention_days";
+/// Value of the retention setting when the trash is never purged
+const RETENTION_FOREVER: &str = "forever";
+
+impl Library
+{
+	/// Get the photos in the trash, the most recently deleted first
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// for photo in library.list_trash().unwrap()
+	/// {
+	///     println!("photo {} deleted on {:?}", photo.id(), photo.deleted_datetime());
+	/// }
+	/// ```
+	pub fn list_trash(&self) -> Result<Vec<Photo>, OsplError>
+	{
+		Photo::list_trash(&self.db)
+	}
+
+	/// Puts a photo of the trash back in the library, in the albums it was in
+	///
+	/// Fails with InvalidInput if the photo is not in the trash.
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.delete_photo_by_id(1).unwrap();
+	/// library.restore_from_trash(1).unwrap();
+	/// assert!(!library.get_photo_from_id(1).unwrap().is_in_trash());
+	/// ```
+	pub fn restore_from_trash(&self, id: u32) -> Result<(), OsplError>
+	{
+		let db = &self.db;
+		let mut photo = self.get_photo_from_id(id)?;
+		if !photo.is_in_trash()
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		}
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		let trashed = photo.original_path(&self.fs);
+		photo.set_deleted_datetime(None);
+		std::fs::rename(&trashed, photo.original_path(&self.fs))?;
+		transaction.moved(trashed, photo.original_path(&self.fs));
+		transaction.step("restore_from_trash.albums")?;
+		for album in Album::list_containing(db, id)?
+		{
+			let album = self.get_album_from_id(album)?;
+			let link = album.get_full_path(&self.fs).join(photo.get_filename());
+			if album.get_full_path(&self.fs).is_dir() && !link.exists()
+			{
+				album.add(&self.fs, &photo)?;
+				transaction.created(link);
+			}
+		}
+		transaction.step("restore_from_trash.database")?;
+		photo.update_deleted_datetime(db)?;
+		transaction.step("restore_from_trash.commit")?;
+		transaction.commit()?;
+		self.regenerate_thumbnail(id)
+	}
+
+	/// Deletes for good every photo of the trash, returns how many were deleted
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// let deleted = library.empty_trash().unwrap();
+	/// println!("{} photos deleted", deleted);
+	/// ```
+	pub fn empty_trash(&self) -> Result<u32, OsplError>
+	{
+		self.delete_permanently(&self.list_trash()?)
+	}
+
+	/// Get the number of days a photo stays in the trash before it is deleted for good,
+	/// None when the trash is only emptied by [Library::empty_trash()]
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// println!("deleted photos are kept {:?} days", library.trash_retention().unwrap());
+	/// ```
+	pub fn trash_retention(&self) -> Result<Option<u32>, OsplError>
+	{
+		Ok(match self.db.setting(RETENTION_SETTING)?
+		{
+			Some(value) if value == RETENTION_FOREVER => None,
+			Some(value) => Some(value.parse().unwrap_or(DEFAULT_TRASH_RETENTION)),
+			None => Some(DEFAULT_TRASH_RETENTION),
+		})
+	}
+
+	/// Changes the number of days a photo stays in the trash, it is saved in the library.
+	/// None keeps the photos until [Library::empty_trash()] is called.
+	///
+	/// The expired photos are deleted by [Library::purge_trash()].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.set_trash_retention(Some(7)).unwrap();
+	/// ```
+	pub fn set_trash_retention(&self, days: Option<u32>) -> Result<(), OsplError>
+	{
+		let value = days.map(|days| days.to_string()).unwrap_or_else(|| String::from(RETENTION_FOREVER));
+		self.db.set_setting(RETENTION_SETTING, &value)
+	}
+
+	/// Deletes for good the photos that stayed in the trash longer than the retention period,
+	/// returns how many were deleted
+	///
+	/// Applications usually call it once after [Library::load()].
+	///
+	/// # Example
+	/// ```no_run
+	/// # use ospl::Library;
+	/// let library = Library::load("/my/awesome/path.ospl/").unwrap();
+	/// library.purge_trash().unwrap();
+	/// ```
+	pub fn purge_trash(&self) -> Result<u32, OsplError>
+	{
+		let days = match self.trash_retention()?
+		{
+			Some(days) => days,
+			None => return Ok(0),
+		};
+		// A retention reaching before the earliest representable date keeps everything
+		let limit = match chrono::offset::Local::now().naive_local().checked_sub_signed(Duration::days(days as i64))
+		{
+			Some(limit) => limit,
+			None => return Ok(0),
+		};
+		let expired: Vec<Photo> = self.list_trash()?.into_iter()
+			.filter(|photo| matches!(photo.deleted_datetime(), Some(deleted) if deleted <= limit))
+			.collect();
+		self.delete_permanently(&expired)
+	}
+}
+
+impl Library // Trash helpers
+{
+	/// Removes photos from the database with their original files and their thumbnails
+	fn delete_permanently(&self, photos: &[Photo]) -> Result<u32, OsplError>
+	{
+		if photos.is_empty()
+		{
+			return Ok(0);
+		}
+		let db = &self.db;
+		let mut transaction = LibraryTransaction::begin(db, &self.fs)?;
+		for photo in photos
+		{
+			let original = photo.original_path(&self.fs);
+			if original.exists()
+			{
+				transaction.remove(original)?;
+			}
+			for thumbnail in self.cached_thumbnails(photo)?
+			{
+				transaction.remove(thumbnail)?;
+			}
+			db.delete(photo)?;
+		}
+		transaction.step("empty_trash.commit")?;
+		transaction.commit()?;
+		Ok(photos.len() as u32)
+	}
+}
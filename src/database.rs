@@ -19,6 +19,7 @@
 */
 
 use super::OsplError;
+use crate::Error;
 use crate::element::traits::ElementDatabase;
 
 use std::path::{Path};
@@ -26,6 +27,34 @@ use rusqlite::{Connection};
 
 static DATABASE_SQL: &str = include_str!("../database.sql");
 
+// The version of the schema is stored in the `user_version` pragma of the database.
+// database.sql always creates the latest schema, and each migration moves a database
+// from the version of its index to the next one: MIGRATIONS[0] goes from 0 to 1.
+// Version 0 is the schema of ospl 0.2.0, released without a version number.
+// A migration is never modified once released, a schema change always adds a new one.
+static MIGRATIONS: &[&str] = &[
+	include_str!("../migrations/001_hierarchies_and_perceptual_hash.sql"),
+	include_str!("../migrations/002_display_dimensions.sql"),
+	include_str!("../migrations/003_edit_stack.sql"),
+	include_str!("../migrations/004_color_labels_and_flags.sql"),
+	include_str!("../migrations/005_full_text_search.sql"),
+	include_str!("../migrations/006_smart_albums.sql"),
+	include_str!("../migrations/007_trash.sql"),
+	include_str!("../migrations/008_cascading_deletes.sql"),
+	include_str!("../migrations/009_album_order_and_cover.sql"),
+	include_str!("../migrations/010_collection_names_per_parent.sql"),
+];
+
+/// Version of the schema written by this version of the library
+pub(crate) const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Number of prepared statements kept by the connection, enough for every query of the library
+const STATEMENT_CACHE_CAPACITY: usize = 128;
+
+/// Tables every version of the schema has
+static REQUIRED_TABLES: &[&str] = &["settings", "photos", "albums", "photos_albums_map",
+	"collections", "tags", "photos_tags_map"];
+
 /// The database structure manages the connection to the db and every db entry.
 #[derive(Debug)]
 pub struct Database
@@ -38,9 +67,16 @@ impl Database
 	/// Creates a database object, and returns it with a open connection
 	pub(crate) fn new<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
 	{
+		let connection = Connection::open(path.as_ref())?;
+		// WAL lets the frontends read the library while an import is writing in it,
+		// and with synchronous NORMAL a commit no longer waits for the disk.
+		connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+		connection.pragma_update(None, "synchronous", "NORMAL")?;
+		connection.pragma_update(None, "foreign_keys", true)?;
+		connection.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 		Ok(Database
 		{
-			connection: Connection::open(path.as_ref())?,
+			connection,
 		})
 	}
 
@@ -49,8 +85,87 @@ impl Database
 	{
 		let db = Self::new(path)?;
 		db.connection.execute_batch(DATABASE_SQL)?;
+		db.connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
 		Ok(db)
 	}
+
+	/// Opens the database of an existing library, checks it and migrates it to the latest schema
+	///
+	/// Fails with [Error::CorruptedLibrary] if the file is not a sqlite database, is damaged
+	/// or lacks some tables, and with [Error::LibraryLocked] if another process is writing in it.
+	pub(crate) fn load<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
+	{
+		let db = Self::new(path).map_err(load_error)?;
+		db.check().map_err(load_error)?;
+		db.migrate().map_err(load_error)?;
+		Ok(db)
+	}
+
+	/// Checks the database file is sane, has the tables of a library and is not locked
+	fn check(&self) -> Result<(), OsplError>
+	{
+		let result: String = self.connection.pragma_query_value(None, "quick_check", |row| row.get(0))?;
+		if result != "ok"
+		{
+			return Err(OsplError::InternalError(Error::CorruptedLibrary));
+		}
+		for table in REQUIRED_TABLES
+		{
+			let mut stmt = self.connection.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")?;
+			if !stmt.exists([table])?
+			{
+				return Err(OsplError::InternalError(Error::CorruptedLibrary));
+			}
+		}
+		// Takes the write lock for an instant, the migrations and every operation will need it
+		self.connection.execute_batch("BEGIN IMMEDIATE; ROLLBACK;")?;
+		Ok(())
+	}
+
+	/// Returns the version of the schema of the database
+	pub(crate) fn version(&self) -> Result<u32, OsplError>
+	{
+		Ok(self.connection.pragma_query_value(None, "user_version", |row| row.get(0))?)
+	}
+
+	/// Brings the schema of the database to the latest version, in a single transaction.
+	///
+	/// Fails with [Error::LibraryTooNew] if the database comes from a newer version of the library.
+	pub(crate) fn migrate(&self) -> Result<(), OsplError>
+	{
+		let version = self.version()?;
+		if version > SCHEMA_VERSION
+		{
+			return Err(OsplError::InternalError(Error::LibraryTooNew));
+		}
+		if version == SCHEMA_VERSION
+		{
+			return Ok(());
+		}
+		// Tables are rebuilt by some migrations, the foreign keys are checked once at the end.
+		// This pragma has no effect inside a transaction.
+		self.connection.execute_batch("PRAGMA foreign_keys = OFF")?;
+		let result = self.apply_migrations(version);
+		self.connection.execute_batch("PRAGMA foreign_keys = ON")?;
+		result
+	}
+
+	fn apply_migrations(&self, from: u32) -> Result<(), OsplError>
+	{
+		let transaction = self.connection.unchecked_transaction()?;
+		for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize)
+		{
+			transaction.execute_batch(migration)?;
+			transaction.pragma_update(None, "user_version", version as u32 + 1)?;
+		}
+		if transaction.prepare("PRAGMA foreign_key_check")?.exists(())?
+		{
+			return Err(OsplError::DatabaseError(rusqlite::Error::SqliteFailure(
+				rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY), None)));
+		}
+		transaction.commit()?;
+		Ok(())
+	}
 }
 
 impl Database
@@ -87,3 +202,65 @@ impl Database
 		object.delete(self)
 	}
 }
+
+impl Database
+{
+	/// Returns the value of a row of the settings table, None if it was never set
+	pub(crate) fn setting(&self, name: &str) -> Result<Option<String>, OsplError>
+	{
+		let mut stmt = self.connection.prepare_cached("SELECT value FROM settings WHERE name = ?1")?;
+		let mut rows = stmt.query([name])?;
+		match rows.next()?
+		{
+			Some(row) => Ok(row.get(0)?),
+			None => Ok(None),
+		}
+	}
+
+	/// Writes a row of the settings table, replacing the previous value
+	pub(crate) fn set_setting(&self, name: &str, value: &str) -> Result<(), OsplError>
+	{
+		self.connection.execute("INSERT OR REPLACE INTO settings (name, value) VALUES (?1, ?2)", (name, value))?;
+		Ok(())
+	}
+
+	/// Runs `f` in a savepoint, rolled back if `f` fails.
+	///
+	/// Unlike a transaction, a savepoint can be opened while a transaction is running,
+	/// so the elements can use it inside a library operation.
+	pub(crate) fn savepoint<T, F>(&self, f: F) -> Result<T, OsplError>
+	where
+		F: FnOnce() -> Result<T, OsplError>,
+	{
+		self.connection.execute_batch("SAVEPOINT element")?;
+		match f()
+		{
+			Ok(value) =>
+			{
+				self.connection.execute_batch("RELEASE element")?;
+				Ok(value)
+			}
+			Err(e) =>
+			{
+				let _ = self.connection.execute_batch("ROLLBACK TO element; RELEASE element");
+				Err(e)
+			}
+		}
+	}
+}
+
+/// Turns the sqlite errors met while loading a library into the errors of the library
+fn load_error(error: OsplError) -> OsplError
+{
+	use rusqlite::ErrorCode;
+	match &error
+	{
+		OsplError::DatabaseError(rusqlite::Error::SqliteFailure(failure, _)) => match failure.code
+		{
+			ErrorCode::NotADatabase | ErrorCode::DatabaseCorrupt => OsplError::InternalError(Error::CorruptedLibrary),
+			ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => OsplError::InternalError(Error::LibraryLocked),
+			_ => error,
+		},
+		_ => error,
+	}
+}
@@ -0,0 +1,120 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform photo library management that can be used
+	to store and sort all your photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+use crate::api::edit::Edit;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+// Colour edits work on 8 bits per channel, like the thumbnails.
+// Photos with more bits per channel lose them once edited, the original is kept anyway.
+
+/// Gains applied on the red and blue channels, or on the green channel, by a full white balance correction
+const WHITE_BALANCE_STRENGTH: f32 = 0.3;
+
+/// Returns the size of a photo of `width` x `height` once the edits are applied,
+/// None if a crop does not fit in the photo as it is at its point of the stack
+pub(crate) fn dimensions(width: u32, height: u32, stack: &[Edit]) -> Option<(u32, u32)>
+{
+	let (mut width, mut height) = (width, height);
+	for edit in stack
+	{
+		match *edit
+		{
+			Edit::Rotate(quarters) if quarters % 2 == 1 => (width, height) = (height, width),
+			Edit::Crop { x, y, width: crop_width, height: crop_height } =>
+			{
+				if x.checked_add(crop_width)? > width || y.checked_add(crop_height)? > height
+				{
+					return None;
+				}
+				(width, height) = (crop_width, crop_height);
+			}
+			_ => {}
+		}
+	}
+	Some((width, height))
+}
+
+/// Applies the edits on an oriented photo, in order
+pub(crate) fn apply(img: DynamicImage, stack: &[Edit]) -> DynamicImage
+{
+	stack.iter().fold(img, apply_one)
+}
+
+fn apply_one(img: DynamicImage, edit: &Edit) -> DynamicImage
+{
+	match *edit
+	{
+		Edit::Rotate(1) => img.rotate90(),
+		Edit::Rotate(2) => img.rotate180(),
+		Edit::Rotate(3) => img.rotate270(),
+		Edit::Rotate(_) => img,
+		// The crop is reduced to the photo if it goes past it
+		Edit::Crop { x, y, width, height } => img.crop_imm(x, y, width, height),
+		Edit::FlipHorizontal => img.fliph(),
+		Edit::FlipVertical => img.flipv(),
+		Edit::Exposure(stops) =>
+		{
+			let gain = 2f32.powf(stops);
+			map_colors(&img, |c| c * gain)
+		}
+		Edit::Brightness(value) => map_colors(&img, |c| c + value * 255.0),
+		Edit::Contrast(value) =>
+		{
+			// 1.0 makes the midtones twice as far from the middle grey
+			let factor = 1.0 + value;
+			map_colors(&img, |c| (c - 127.5) * factor + 127.5)
+		}
+		Edit::Saturation(value) => map_pixels(&img, |[r, g, b]|
+		{
+			let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+			let factor = 1.0 + value;
+			[luma + (r - luma) * factor, luma + (g - luma) * factor, luma + (b - luma) * factor]
+		}),
+		Edit::WhiteBalance { temperature, tint } => map_pixels(&img, |[r, g, b]|
+		{
+			let warm = temperature * WHITE_BALANCE_STRENGTH;
+			[r * (1.0 + warm), g * (1.0 - tint * WHITE_BALANCE_STRENGTH), b * (1.0 - warm)]
+		}),
+	}
+}
+
+/// Applies the same change on the red, green and blue channels of every pixel
+fn map_colors<F: Fn(f32) -> f32>(img: &DynamicImage, f: F) -> DynamicImage
+{
+	map_pixels(img, |[r, g, b]| [f(r), f(g), f(b)])
+}
+
+/// Changes the colour of every pixel, the channels go from 0.0 to 255.0 and the alpha is kept
+fn map_pixels<F: Fn([f32; 3]) -> [f32; 3]>(img: &DynamicImage, f: F) -> DynamicImage
+{
+	let mut rgba: RgbaImage = img.to_rgba8();
+	for pixel in rgba.pixels_mut()
+	{
+		let Rgba([r, g, b, a]) = *pixel;
+		let [r, g, b] = f([r as f32, g as f32, b as f32]);
+		*pixel = Rgba([clamp(r), clamp(g), clamp(b), a]);
+	}
+	DynamicImage::ImageRgba8(rgba)
+}
+
+fn clamp(channel: f32) -> u8
+{
+	channel.round().clamp(0.0, 255.0) as u8
+}
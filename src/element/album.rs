@@ -26,6 +26,8 @@ use crate::element::traits::ElementFilesystem;
 use crate::Database;
 use crate::Filesystem;
 use crate::OsplError;
+use crate::api::query::PhotoQuery;
+use crate::api::album::AlbumSort;
 
 use std::path::PathBuf;
 use chrono::naive::NaiveDateTime;
@@ -41,6 +43,9 @@ pub struct Album
 	pub(crate) name:					String,
 	pub(crate) comment:				String,
 	pub(crate) collection:				Collection,
+	pub(crate) query:					Option<String>,
+	pub(crate) cover:					Option<u32>,
+	pub(crate) sort:					AlbumSort,
 }
 
 impl Default for Album
@@ -65,6 +70,9 @@ impl Album
 			name: String::from(""),
 			comment: String::from(""),
 			collection: Collection::new(),
+			query: None,
+			cover: None,
+			sort: AlbumSort::Manual,
 		}
 	}
 
@@ -79,6 +87,9 @@ impl Album
 			name:					String::from(name),
 			comment:				String::from(comment),
 			collection,
+			query:					None,
+			cover:					None,
+			sort:					AlbumSort::Manual,
 		}
 	}
 }
@@ -110,6 +121,31 @@ impl Album
 	{
 		self.collection.id()
 	}
+
+	/// True when the photos of the album are the ones matching a query
+	pub fn is_smart(&self) -> bool
+	{
+		self.query.is_some()
+	}
+
+	/// Query of a smart album, None for an album whose photos are chosen one by one
+	/// or if the query cannot be read by this version of the library
+	pub fn query(&self) -> Option<PhotoQuery>
+	{
+		PhotoQuery::parse(self.query.as_ref()?).ok()
+	}
+
+	/// Id of the photo chosen to be shown for the album, see [crate::Library::get_album_cover()]
+	pub fn cover(&self) -> Option<u32>
+	{
+		self.cover
+	}
+
+	/// How the photos of the album are sorted
+	pub fn sort(&self) -> AlbumSort
+	{
+		self.sort
+	}
 }
 
 // Internal helpers
@@ -117,10 +153,10 @@ impl Album
 {
 	fn get_collection_path(&self, fs: &Filesystem) -> PathBuf
 	{
-		fs.collections_path().join(self.collection.name())
+		self.collection.path(fs)
 	}
 
-	fn get_full_path(&self, fs: &Filesystem) -> PathBuf
+	pub(crate) fn get_full_path(&self, fs: &Filesystem) -> PathBuf
 	{
 		self.get_collection_path(fs).join(self.name())
 	}
@@ -128,6 +164,7 @@ impl Album
 
 impl ElementDatabase for Album
 {
+	/// Deletes the album, its photos are only taken out of it
 	fn delete(&self, db: &Database) -> Result<(), OsplError>
 	{
 		db.connection.execute("DELETE FROM albums WHERE id = ?1", [&self.id])?;
@@ -136,8 +173,9 @@ impl ElementDatabase for Album
 
 	fn insert_into(&self, db: &Database) -> Result<u32, OsplError>
 	{
-		db.connection.execute("INSERT INTO albums (name, comment, creation_datetime, modification_datetime, collection) VALUES (?1, ?2, ?3, ?4, ?5)",
-		(&self.name, &self.comment, &self.creation_datetime, &self.modification_datetime, self.collection.id()))?;
+		db.connection.execute("INSERT INTO albums (name, comment, creation_datetime, modification_datetime, collection, query) \
+			VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+		(&self.name, &self.comment, &self.creation_datetime, &self.modification_datetime, self.collection.id(), &self.query))?;
 		Ok(db.connection.last_insert_rowid() as u32)
 	}
 
@@ -150,7 +188,7 @@ impl ElementDatabase for Album
 	fn load_from_id(&mut self, db: &Database, id: u32) -> Result<(), OsplError>
 	{
 		// fill self with the albums table from the database with the id
-		let mut stmt = db.connection.prepare("SELECT * FROM albums WHERE id = ?1")?;
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM albums WHERE id = ?1")?;
 		let mut rows = stmt.query([&id])?;
 
 		while let Some(row) = rows.next()?
@@ -161,6 +199,9 @@ impl ElementDatabase for Album
 			self.creation_datetime = row.get(3)?;
 			self.modification_datetime = row.get(4)?;
 			db.load_from_id(&mut self.collection, row.get(5)?)?;
+			self.query = row.get("query")?;
+			self.cover = row.get("cover")?;
+			self.sort = AlbumSort::from_setting(row.get::<_, Option<String>>("sort")?.as_deref());
 		}
 		
 		if self.id == 0
@@ -179,11 +220,6 @@ impl ElementFilesystem for Album
 		Ok(())
 	}
 
-	fn remove_from(&self, fs: &Filesystem) -> Result<(), OsplError>
-	{
-		Ok(std::fs::remove_dir_all(self.get_full_path(fs))?)
-	}
-
 	fn rename(&self, fs: &Filesystem, new_name: &str) -> Result<(), OsplError>
 	{
 		let path_old = self.get_collection_path(fs).join(self.name());
@@ -198,17 +234,14 @@ impl Album
 	pub fn move_to(&self, fs: &Filesystem, collection: &Collection) -> Result<(), OsplError>
 	{
 		let path_old = self.get_collection_path(fs).join(self.name());
-		let path_new = fs.collections_path().join(collection.name()).join(self.name());
+		let path_new = collection.path(fs).join(self.name());
 		Ok(std::fs::rename(path_old, path_new)?)
 	}
 
 	pub fn add(&self, fs: &Filesystem, photo: &Photo) -> Result<(), OsplError>
 	{
 		let photo_path = fs.pictures_path().join(photo.get_filename());
-		let link_path = fs.collections_path()
-									.join(self.collection.name())
-									.join(self.name())
-									.join(photo.get_filename());
+		let link_path = self.get_full_path(fs).join(photo.get_filename());
 		if !link_path.exists()
 		{
 			std::fs::hard_link(photo_path, link_path)?;
@@ -230,27 +263,145 @@ impl Album
 		Ok(())
 	}
 
+	/// Puts the photo at the end of the album, nothing is done if it is already in it
 	pub fn put(&self, db: &Database, photo: &Photo) -> Result<(), OsplError>
 	{
-		db.connection.execute("INSERT INTO photos_albums_map (containing_album, contained_photo) VALUES (?1, ?2)",
+		db.connection.execute("INSERT OR IGNORE INTO photos_albums_map (containing_album, contained_photo, position) \
+			VALUES (?1, ?2, (SELECT IFNULL(MAX(position), -1) + 1 FROM photos_albums_map WHERE containing_album = ?1))",
 		(self.id(), photo.id))?;
 		Ok(())
 	}
+
+	/// Stores the cover photo of the album
+	pub(crate) fn update_cover(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE albums SET cover = ?1 WHERE id = ?2", (&self.cover, &self.id))?;
+		Ok(())
+	}
+
+	/// Stores how the photos of the album are sorted
+	pub(crate) fn update_sort(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE albums SET sort = ?1 WHERE id = ?2", (self.sort.to_setting(), &self.id))?;
+		Ok(())
+	}
+
+	/// Gives the photos of the album their position, from the order of `photos`
+	pub(crate) fn update_positions(&self, db: &Database, photos: &[u32]) -> Result<(), OsplError>
+	{
+		db.savepoint(||
+		{
+			let mut stmt = db.connection.prepare_cached("UPDATE photos_albums_map SET position = ?1 \
+				WHERE containing_album = ?2 AND contained_photo = ?3")?;
+			for (position, photo) in photos.iter().enumerate()
+			{
+				stmt.execute((position as u32, &self.id, photo))?;
+			}
+			Ok(())
+		})
+	}
+
+	/// Stores the query of a smart album, None turns it into an album whose photos are chosen one by one
+	pub(crate) fn update_query(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE albums SET query = ?1 WHERE id = ?2", (&self.query, &self.id))?;
+		Ok(())
+	}
+
+	/// Returns true if the photo is already in the album
+	pub fn contains(&self, db: &Database, photo: u32) -> Result<bool, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT 1 FROM photos_albums_map WHERE containing_album = ?1 AND contained_photo = ?2")?;
+		Ok(stmt.exists((self.id, photo))?)
+	}
+
+	/// Returns the ids of the photos assigned to the album, the photos in the trash included
+	pub(crate) fn list_photo_ids(db: &Database, album: u32) -> Result<Vec<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT contained_photo FROM photos_albums_map \
+			WHERE containing_album = ?1 ORDER BY position, rowid")?;
+		let mut rows = stmt.query([album])?;
+		let mut photos = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			photos.push(row.get(0)?);
+		}
+		Ok(photos)
+	}
+
+	/// Returns the ids of the albums the photo was assigned to
+	pub(crate) fn list_containing(db: &Database, photo: u32) -> Result<Vec<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT DISTINCT containing_album FROM photos_albums_map \
+			WHERE contained_photo = ?1 ORDER BY containing_album")?;
+		let mut rows = stmt.query([photo])?;
+		let mut albums = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			albums.push(row.get(0)?);
+		}
+		Ok(albums)
+	}
+
+	/// Returns the (album, photo) rows of photos_albums_map pointing to an album or a photo that does not exist
+	pub(crate) fn list_dangling_links(db: &Database) -> Result<Vec<(u32, u32)>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT containing_album, contained_photo FROM photos_albums_map \
+			WHERE containing_album NOT IN (SELECT id FROM albums) OR contained_photo NOT IN (SELECT id FROM photos) \
+			ORDER BY containing_album, contained_photo")?;
+		let mut rows = stmt.query(())?;
+		let mut links = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			links.push((row.get(0)?, row.get(1)?));
+		}
+		Ok(links)
+	}
+
+	/// Removes the rows of photos_albums_map linking the album and the photo
+	pub(crate) fn delete_link(db: &Database, album: u32, photo: u32) -> Result<(), OsplError>
+	{
+		db.connection.execute("DELETE FROM photos_albums_map WHERE containing_album = ?1 AND contained_photo = ?2", (album, photo))?;
+		Ok(())
+	}
+
+	/// Returns the id of the album with this name and the id of its collection, if there is one.
+	/// Album names are unique in the whole library.
+	pub(crate) fn find(db: &Database, name: &str) -> Result<Option<(u32, u32)>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT id, collection FROM albums WHERE name = ?1")?;
+		let mut rows = stmt.query([name])?;
+		match rows.next()?
+		{
+			Some(row) => Ok(Some((row.get(0)?, row.get(1)?))),
+			None => Ok(None),
+		}
+	}
 }
 
-impl InsideElementListing<Photo> for Album
+impl Album
 {
-	fn list_inside(db: &Database, id: u32) -> Result<Vec<Photo>, OsplError>
+	/// Lists the photos assigned to the album sorted by `sort`, the photos in the trash are left out
+	pub(crate) fn list_sorted(db: &Database, id: u32, sort: AlbumSort) -> Result<Vec<Photo>, OsplError>
 	{
-		let mut stmt = db.connection.prepare("SELECT contained_photo FROM photos_albums_map WHERE containing_album = ?1")?;
+		let mut stmt = db.connection.prepare_cached(&format!("SELECT photos.* FROM photos INNER JOIN photos_albums_map \
+			ON photos_albums_map.contained_photo = photos.id WHERE photos_albums_map.containing_album = ?1 \
+			AND photos.deleted_datetime IS NULL ORDER BY {}", sort.to_order()))?;
 		let mut rows = stmt.query([&id])?;
 		let mut photos = Vec::new();
 		while let Some(row) = rows.next()?
 		{
-			let mut photo = Photo::default();
-			db.load_from_id(&mut photo, row.get(0)?)?;
-			photos.push(photo);
+			photos.push(Photo::from_row(row)?);
 		}
 		Ok(photos)
 	}
 }
+
+impl InsideElementListing<Photo> for Album
+{
+	/// Lists the photos assigned to the album in the order chosen by the user
+	fn list_inside(db: &Database, id: u32) -> Result<Vec<Photo>, OsplError>
+	{
+		Album::list_sorted(db, id, AlbumSort::Manual)
+	}
+}
@@ -26,11 +26,14 @@ use crate::Filesystem;
 use crate::OsplError;
 use crate::element::traits::ElementListing;
 use crate::element::traits::InsideElementListing;
+use crate::api::album::AlbumSort;
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use chrono::naive::NaiveDateTime;
 
 /// Structure containing a replica of sqlite data
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Collection
 {
 	id:						u32,
@@ -38,6 +41,7 @@ pub struct Collection
 	modification_datetime:	Option<NaiveDateTime>,
 	name:					String,
 	comment:				String,
+	parent:					Option<Box<Collection>>,
 }
 impl Default for Collection
 {
@@ -47,6 +51,15 @@ impl Default for Collection
 	}
 }
 
+/// A collection with its albums and all the collections below it
+#[derive(Debug)]
+pub struct CollectionTree
+{
+	pub collection:	Collection,
+	pub albums:		Vec<Album>,
+	pub children:	Vec<CollectionTree>,
+}
+
 // Constructors
 impl Collection
 {
@@ -60,6 +73,7 @@ impl Collection
 			modification_datetime: None,
 			name: String::from(""),
 			comment: String::from(""),
+			parent: None,
 		}
 	}
 
@@ -73,6 +87,7 @@ impl Collection
 			modification_datetime:	Some(chrono::offset::Local::now().naive_local()),
 			name:					String::from(name),
 			comment:				String::from(comment),
+			parent:					None,
 		}
 	}
 
@@ -96,21 +111,107 @@ impl Collection
 		self.comment.clone()
 	}
 
+	/// Puts the collection inside another one, or at the root with None
+	pub fn set_parent(&mut self, parent: Option<Collection>)
+	{
+		self.parent = parent.map(Box::new);
+	}
+
+	/// Returns the id of the collection containing this one, None for a root collection
+	pub fn parent_id(&self) -> Option<u32>
+	{
+		self.parent.as_ref().map(|parent| parent.id())
+	}
+
+	/// Returns the path of the collection directory, nested inside its parents directories
+	pub(crate) fn path(&self, fs: &Filesystem) -> PathBuf
+	{
+		match &self.parent
+		{
+			Some(parent) => parent.path(fs).join(&self.name),
+			None => fs.collections_path().join(&self.name),
+		}
+	}
+
 	/* TODO: Implement a from_folder function for importing. */
 }
 
+impl Collection // Database helpers
+{
+	/// Builds a collection from a row of the collections table, and returns the id of its parent
+	/// so the caller can attach a parent it already loaded
+	fn from_row_without_parent(row: &rusqlite::Row) -> Result<(Collection, Option<u32>), OsplError>
+	{
+		Ok((Collection
+		{
+			id: row.get("id")?,
+			name: row.get("name")?,
+			comment: row.get("comment")?,
+			creation_datetime: row.get("creation_datetime")?,
+			modification_datetime: row.get("modification_datetime")?,
+			parent: None,
+		}, row.get("parent")?))
+	}
+
+	/// Attaches its parents to the collection `id`, using the collections already read
+	fn with_parents(id: u32, rows: &HashMap<u32, (Collection, Option<u32>)>) -> Collection
+	{
+		let (collection, parent) = &rows[&id];
+		let mut collection = collection.clone();
+		collection.parent = parent.filter(|parent| rows.contains_key(parent))
+			.map(|parent| Box::new(Collection::with_parents(parent, rows)));
+		collection
+	}
+
+	/// Returns the id of the collection with this name under `parent`, if there is one
+	pub(crate) fn find(db: &Database, name: &str, parent: Option<u32>) -> Result<Option<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT id FROM collections WHERE name = ?1 AND IFNULL(parent, 0) = ?2")?;
+		let mut rows = stmt.query((name, parent.unwrap_or(0)))?;
+		match rows.next()?
+		{
+			Some(row) => Ok(Some(row.get(0)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the ids of the collection and of all the collections below it
+	pub(crate) fn descendants(db: &Database, id: u32) -> Result<Vec<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("WITH RECURSIVE descendants(id) AS \
+			(SELECT ?1 UNION SELECT collections.id FROM collections \
+			INNER JOIN descendants ON collections.parent = descendants.id) \
+			SELECT id FROM descendants")?;
+		let mut rows = stmt.query([&id])?;
+		let mut ids = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			ids.push(row.get(0)?);
+		}
+		Ok(ids)
+	}
+}
+
 impl ElementDatabase for Collection
 {
+	/// Deletes the collection, all the collections below it and their albums
 	fn delete(&self, db: &Database) -> Result<(), OsplError>
 	{
-		db.connection.execute("DELETE FROM collections WHERE id = ?1", [&self.id])?;
-		Ok(())
+		db.savepoint(||
+		{
+			for id in Collection::descendants(db, self.id)?.iter().rev()
+			{
+				db.connection.execute("DELETE FROM albums WHERE collection = ?1", [id])?;
+				db.connection.execute("DELETE FROM collections WHERE id = ?1", [id])?;
+			}
+			Ok(())
+		})
 	}
 
 	fn insert_into(&self, db: &Database) -> Result<u32, OsplError>
 	{
-		db.connection.execute("INSERT INTO collections (name, comment, creation_datetime, modification_datetime) VALUES (?1, ?2, ?3, ?4)",
-		(&self.name, &self.comment, &self.creation_datetime, &self.modification_datetime))?;
+		db.connection.execute("INSERT INTO collections (name, comment, creation_datetime, modification_datetime, parent) VALUES (?1, ?2, ?3, ?4, ?5)",
+		(&self.name, &self.comment, &self.creation_datetime, &self.modification_datetime, self.parent_id()))?;
 		Ok(db.connection.last_insert_rowid() as u32)
 	}
 
@@ -122,38 +223,74 @@ impl ElementDatabase for Collection
 
 	fn load_from_id(&mut self, db: &Database, id: u32) -> Result<(), OsplError>
 	{
-		// fill self with the Collection table from the database with the id
-		let mut stmt = db.connection.prepare("SELECT * FROM collections WHERE id = ?1")?;
+		// The collection and all its parents are read at once, then the parents are attached to it
+		let mut stmt = db.connection.prepare_cached("WITH RECURSIVE ancestors(id) AS \
+			(SELECT ?1 UNION SELECT collections.parent FROM collections \
+			INNER JOIN ancestors ON collections.id = ancestors.id WHERE collections.parent IS NOT NULL) \
+			SELECT collections.* FROM collections INNER JOIN ancestors ON collections.id = ancestors.id")?;
 		let mut rows = stmt.query([&id])?;
 
+		let mut collections = HashMap::new();
 		while let Some(row) = rows.next()?
 		{
-			self.id = row.get(0)?;
-			self.name = row.get(1)?;
-			self.comment = row.get(2)?;
-			self.creation_datetime = row.get(3)?;
-			self.modification_datetime = row.get(4)?;
+			let (collection, parent) = Collection::from_row_without_parent(row)?;
+			collections.insert(collection.id, (collection, parent));
 		}
-		if self.id == 0
+		if !collections.contains_key(&id)
 		{
 			return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
 		}
+		*self = Collection::with_parents(id, &collections);
+		Ok(())
+	}
+}
+
+// Specific Database functions
+impl Collection
+{
+	/// Puts the collection inside `parent` in the database, or at the root with None
+	pub fn assign_to(&self, db: &Database, parent: Option<&Collection>) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE collections SET parent = ?1 WHERE id = ?2",
+		(parent.map(|parent| parent.id()), &self.id))?;
 		Ok(())
 	}
 }
 
+// Specific Filesystem functions
+impl Collection
+{
+	/// Moves the collection directory inside the `parent` directory, or at the root with None
+	pub fn move_to(&self, fs: &Filesystem, parent: Option<&Collection>) -> Result<(), OsplError>
+	{
+		let path_new = match parent
+		{
+			Some(parent) => parent.path(fs).join(self.name()),
+			None => fs.collections_path().join(self.name()),
+		};
+		Ok(std::fs::rename(self.path(fs), path_new)?)
+	}
+}
+
 impl InsideElementListing<Album> for Collection
 {
 	fn list_inside(db: &Database, collection: u32)-> Result<Vec<Album>, OsplError>
 	{
-		let mut stmt = db.connection.prepare("SELECT * FROM albums WHERE collection = ?1")?;
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM albums WHERE collection = ?1")?;
 		let mut rows = stmt.query([&collection])?;
 
+		// Every album shares the same collection, it is loaded once
+		let mut parent: Option<Collection> = None;
 		let mut albums = Vec::new();
 		while let Some(row) = rows.next()?
 		{
-			let mut c = Collection::default();
-			c.load_from_id(db, row.get(5)?)?;
+			if parent.is_none()
+			{
+				let mut c = Collection::default();
+				c.load_from_id(db, collection)?;
+				parent = Some(c);
+			}
+			let c = parent.clone().unwrap();
 			let album = Album
 			{
 				id: row.get(0)?,
@@ -162,6 +299,9 @@ impl InsideElementListing<Album> for Collection
 				creation_datetime: Some(row.get(3)?),
 				modification_datetime: Some(row.get(4)?),
 				collection: c,
+				query: row.get("query")?,
+				cover: row.get("cover")?,
+				sort: AlbumSort::from_setting(row.get::<_, Option<String>>("sort")?.as_deref()),
 			};
 			albums.push(album);
 		}	
@@ -169,50 +309,84 @@ impl InsideElementListing<Album> for Collection
 	}
 }
 
+impl InsideElementListing<Collection> for Collection
+{
+	/// Lists the collections directly inside the collection
+	fn list_inside(db: &Database, collection: u32)-> Result<Vec<Collection>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM collections WHERE parent = ?1")?;
+		let mut rows = stmt.query([&collection])?;
+
+		let mut parent: Option<Collection> = None;
+		let mut collections = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			if parent.is_none()
+			{
+				let mut c = Collection::default();
+				c.load_from_id(db, collection)?;
+				parent = Some(c);
+			}
+			let (mut child, _) = Collection::from_row_without_parent(row)?;
+			child.parent = parent.clone().map(Box::new);
+			collections.push(child);
+		}
+		Ok(collections)
+	}
+}
 
 impl ElementListing<Collection> for Collection
 {
 	fn list_all(db: &Database, _fs: &Filesystem) -> Result<Vec<Collection>, OsplError>
 	{
-		let mut stmt = db.connection.prepare("SELECT * FROM collections")?;
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM collections ORDER BY id")?;
 		let mut rows = stmt.query(())?;
 
-		let mut collections = Vec::new();
+		// The parents are all in the rows, they are attached without querying them again
+		let mut ids = Vec::new();
+		let mut collections = HashMap::new();
 		while let Some(row) = rows.next()?
 		{
-			let collection = Collection
-			{
-				id: row.get(0)?,
-				name: row.get(1)?,
-				comment: row.get(2)?,
-				creation_datetime: Some(row.get(3)?),
-				modification_datetime: Some(row.get(4)?),
-			};
-			collections.push(collection);
-		}	
-		Ok(collections)
+			let (collection, parent) = Collection::from_row_without_parent(row)?;
+			ids.push(collection.id);
+			collections.insert(collection.id, (collection, parent));
+		}
+		Ok(ids.into_iter().map(|id| Collection::with_parents(id, &collections)).collect())
 	}
 }
 
+impl CollectionTree
+{
+	/// Loads the collection with its albums, then all the collections below it
+	pub(crate) fn load(db: &Database, collection: Collection) -> Result<CollectionTree, OsplError>
+	{
+		let albums = <Collection as InsideElementListing<Album>>::list_inside(db, collection.id())?;
+		let mut children = Vec::new();
+		for child in <Collection as InsideElementListing<Collection>>::list_inside(db, collection.id())?
+		{
+			children.push(CollectionTree::load(db, child)?);
+		}
+		Ok(CollectionTree
+		{
+			collection,
+			albums,
+			children,
+		})
+	}
+}
 
 impl ElementFilesystem for Collection
 {
 	fn insert_into(&self, fs: &Filesystem) -> Result<(), OsplError>
 	{
-		let path = fs.collections_path().join(&self.name);
-		std::fs::create_dir(path)?;
+		std::fs::create_dir(self.path(fs))?;
 		Ok(())
 	}
 
-	fn remove_from(&self, fs: &Filesystem) -> Result<(), OsplError>
-	{
-		Ok(std::fs::remove_dir_all(fs.collections_path())?)
-	}
-
 	fn rename(&self, fs: &Filesystem, new_name: &str) -> Result<(), OsplError>
 	{
-		let path_old = fs.collections_path().join(self.name());
-		let path_new = fs.collections_path().join(new_name);
+		let path_old = self.path(fs);
+		let path_new = path_old.with_file_name(new_name);
 		Ok(std::fs::rename(path_old, path_new)?)
 	}
 }
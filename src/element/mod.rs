@@ -1,4 +1,5 @@
 pub mod traits;
 pub mod photo;
 pub mod collection;
-pub mod album;
\ No newline at end of file
+pub mod album;
+pub mod tag;
\ No newline at end of file
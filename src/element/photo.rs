@@ -19,15 +19,19 @@
 */
 
 use crate::element::traits::ElementDatabase;
-use crate::element::traits::ElementFilesystem;
 use crate::Database;
 use crate::Filesystem;
 use crate::{Error, OsplError};
 use crate::element::traits::ElementListing;
+use crate::metadata::Metadata;
+use crate::api::edit::{self, Edit};
+use crate::edits;
+use crate::api::rating::{ColorLabel, PickFlag};
 
 use chrono::naive::NaiveDateTime;
-use xxhash_rust::xxh3::xxh3_128;
+use crate::hash;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Structure containing a replica of sqlite data
@@ -41,8 +45,15 @@ pub struct Photo
 	import_datetime:	Option<NaiveDateTime>,
 	rating:				u32,
 	starred:			bool,
-
-	path_on_fs:			PathBuf,
+	color_label:		Option<ColorLabel>,
+	flag:				PickFlag,
+	title:				Option<String>,
+	comment:			Option<String>,
+	metadata:			Metadata,
+	perceptual_hash:	Option<u64>,
+	thumbnail_hash:		Option<String>,
+	edits:				Vec<Edit>,
+	deleted_datetime:	Option<NaiveDateTime>,
 }
 
 impl Default for Photo
@@ -66,32 +77,56 @@ impl Photo
 			import_datetime:	None,
 			rating:				0,
 			starred:			false,
-
-			path_on_fs:			Path::new("").to_path_buf(),
+			color_label:		None,
+			flag:				PickFlag::Unflagged,
+			title:				None,
+			comment:			None,
+			metadata:			Metadata::default(),
+			perceptual_hash:	None,
+			thumbnail_hash:		None,
+			edits:				Vec::new(),
+			deleted_datetime:	None,
 		}
 	}
 
-	/// Gets data from an image file and fills self with basic data:
-	/// - filename
-	/// - hash using xxh algorithm
-	/// - import datetime
-	/// - the current path on the filesystem
-	pub fn from_file<P: AsRef<Path>>(&mut self, _db: &Database, photo_path: P)
-	-> Result <(), OsplError>
+	/// Copies an image file into the library under a temporary name and fills self with basic data:
+	/// its filename, its xxh3 hash and the import datetime.
+	///
+	/// The file is hashed while it is copied, so the original file is only read once.
+	/// Returns the path of the copy, which [Photo::keep_copy()] renames once the photo is kept
+	/// and the caller removes otherwise, for instance when the photo is a duplicate.
+	pub(crate) fn copy_into_library<P: AsRef<Path>>(&mut self, fs: &Filesystem, photo_path: P)
+	-> Result <PathBuf, OsplError>
+	{
+		check_importable(&photo_path)?;
+		self.filename = get_filename_from(&photo_path);
+		self.import_datetime = Some(chrono::offset::Local::now().naive_local());
+		let copy = fs.pictures_path().join(format!(".{}.part", self.get_filename()));
+		self.hash = hash::copy_and_hash(&photo_path, &copy)?;
+		Ok(copy)
+	}
+
+	/// Gives the temporary copy made by [Photo::copy_into_library()] its final name
+	/// and reads the image information and camera settings found in its EXIF data.
+	///
+	/// The copy is removed if something fails.
+	pub(crate) fn keep_copy(&mut self, fs: &Filesystem, copy: &Path) -> Result<(), OsplError>
 	{
-		if photo_path.as_ref().is_dir()
+		let path = fs.pictures_path().join(self.get_filename());
+		if let Err(e) = std::fs::rename(copy, &path)
 		{
-			return Err(OsplError::InternalError(Error::IsADirectory));
+			let _ = std::fs::remove_file(copy);
+			return Err(e.into());
 		}
-		if !is_photo(&photo_path)?
+		self.metadata = match Metadata::from_path(&path)
 		{
-			return Err(OsplError::InternalError(Error::NotAnImage));
-		}
-		self.filename = get_filename_from(&photo_path);
-		self.hash = xxh3_128(&std::fs::read(&photo_path)?);
-		self.import_datetime = Some(chrono::offset::Local::now().naive_local());
-		self.path_on_fs = photo_path.as_ref().to_path_buf();
-		println!("import from file:\n{:#?}", &self);
+			Ok(metadata) => metadata,
+			Err(e) =>
+			{
+				let _ = std::fs::remove_file(&path);
+				return Err(e);
+			}
+		};
 		Ok(())
 	}
 }
@@ -107,6 +142,215 @@ impl Photo
 	{
 		self.filename.clone()
 	}
+
+	/// xxh3 hash of the original file
+	pub fn hash(&self) -> u128
+	{
+		self.hash
+	}
+
+	/// Number of stars given to the photo, from 0 (not rated) to [crate::api::rating::MAX_RATING]
+	pub fn rating(&self) -> u32
+	{
+		self.rating
+	}
+
+	/// True when the photo is a favourite
+	pub fn starred(&self) -> bool
+	{
+		self.starred
+	}
+
+	pub fn color_label(&self) -> Option<ColorLabel>
+	{
+		self.color_label
+	}
+
+	/// Whether the photo was picked or rejected
+	pub fn flag(&self) -> PickFlag
+	{
+		self.flag
+	}
+
+	pub fn title(&self) -> Option<String>
+	{
+		self.title.clone()
+	}
+
+	pub fn comment(&self) -> Option<String>
+	{
+		self.comment.clone()
+	}
+
+	/// Difference hash of the picture, close values mean visually similar photos
+	pub fn perceptual_hash(&self) -> Option<u64>
+	{
+		self.perceptual_hash
+	}
+
+	pub(crate) fn set_perceptual_hash(&mut self, perceptual_hash: u64)
+	{
+		self.perceptual_hash = Some(perceptual_hash);
+	}
+
+	/// Identifies what the cached thumbnails were rendered from, None if they were never rendered
+	pub(crate) fn thumbnail_hash(&self) -> Option<String>
+	{
+		self.thumbnail_hash.clone()
+	}
+
+	pub(crate) fn set_thumbnail_hash(&mut self, thumbnail_hash: String)
+	{
+		self.thumbnail_hash = Some(thumbnail_hash);
+	}
+
+	/// Non-destructive edits applied to the original, in order
+	pub fn edits(&self) -> Vec<Edit>
+	{
+		self.edits.clone()
+	}
+
+	pub(crate) fn set_edits(&mut self, edits: &[Edit])
+	{
+		self.edits = edits.to_vec();
+	}
+
+	/// When the photo was moved to the trash, None if it is not in the trash
+	pub fn deleted_datetime(&self) -> Option<NaiveDateTime>
+	{
+		self.deleted_datetime
+	}
+
+	pub fn is_in_trash(&self) -> bool
+	{
+		self.deleted_datetime.is_some()
+	}
+
+	pub(crate) fn set_deleted_datetime(&mut self, deleted_datetime: Option<NaiveDateTime>)
+	{
+		self.deleted_datetime = deleted_datetime;
+	}
+
+	pub fn height(&self) -> Option<u32>
+	{
+		self.metadata.height
+	}
+
+	pub fn width(&self) -> Option<u32>
+	{
+		self.metadata.width
+	}
+
+	/// Date and time the photo was taken, as written by the camera
+	pub fn creation_datetime(&self) -> Option<NaiveDateTime>
+	{
+		self.metadata.creation_datetime
+	}
+
+	/// File format of the original, as its usual extension (ex: "jpg")
+	pub fn format(&self) -> Option<String>
+	{
+		self.metadata.format.clone()
+	}
+
+	/// EXIF orientation, from 1 (normal) to 8
+	pub fn orientation(&self) -> Option<u32>
+	{
+		self.metadata.orientation
+	}
+
+	/// Height of the photo once its orientation and its edits are applied, the way it is shown
+	pub fn display_height(&self) -> Option<u32>
+	{
+		self.display_size().map(|(_, height)| height)
+	}
+
+	/// Width of the photo once its orientation and its edits are applied, the way it is shown
+	pub fn display_width(&self) -> Option<u32>
+	{
+		self.display_size().map(|(width, _)| width)
+	}
+
+	fn display_size(&self) -> Option<(u32, u32)>
+	{
+		let (width, height) = self.oriented_size()?;
+		Some(edits::dimensions(width, height, &self.edits).unwrap_or((width, height)))
+	}
+
+	/// Width and height of the photo once its orientation is applied, before its edits
+	pub(crate) fn oriented_size(&self) -> Option<(u32, u32)>
+	{
+		Some((self.metadata.display_width?, self.metadata.display_height?))
+	}
+
+	pub fn make(&self) -> Option<String>
+	{
+		self.metadata.make.clone()
+	}
+
+	pub fn model(&self) -> Option<String>
+	{
+		self.metadata.model.clone()
+	}
+
+	pub fn lens(&self) -> Option<String>
+	{
+		self.metadata.lens.clone()
+	}
+
+	/// Aperture as an f-number (ex: 2.8 for f/2.8)
+	pub fn aperture(&self) -> Option<f64>
+	{
+		self.metadata.aperture
+	}
+
+	/// Focal length in millimeters
+	pub fn focal_length(&self) -> Option<f64>
+	{
+		self.metadata.focal_length
+	}
+
+	/// Exposure time in seconds, written like "1/250"
+	pub fn exposure_time(&self) -> Option<String>
+	{
+		self.metadata.exposure_time.clone()
+	}
+
+	/// EXIF exposure mode: 0 auto, 1 manual, 2 auto bracket
+	pub fn exposure_mode(&self) -> Option<u32>
+	{
+		self.metadata.exposure_mode
+	}
+
+	/// ISO sensitivity
+	pub fn sensitivity(&self) -> Option<u32>
+	{
+		self.metadata.sensitivity
+	}
+
+	/// Latitude where the photo was taken in degrees, negative in the south
+	pub fn latitude(&self) -> Option<f64>
+	{
+		self.metadata.latitude
+	}
+
+	/// Longitude where the photo was taken in degrees, negative in the west
+	pub fn longitude(&self) -> Option<f64>
+	{
+		self.metadata.longitude
+	}
+
+	/// Raw EXIF flash value, the lowest bit tells if the flash fired
+	pub fn flash(&self) -> Option<u32>
+	{
+		self.metadata.flash
+	}
+
+	/// EXIF metering mode (ex: 2 center weighted, 5 pattern)
+	pub fn metering_mode(&self) -> Option<u32>
+	{
+		self.metadata.metering_mode
+	}
 }
 
 impl Photo // Private function only useful to the local functions
@@ -123,11 +367,205 @@ impl Photo // Public function to get information about the photo
 	{
 		self.get_time_formatted() + "_" + &self.filename
 	}
+
+	/// Path of the original file in the library, in `trash/` once the photo is deleted
+	pub(crate) fn original_path(&self, fs: &Filesystem) -> PathBuf
+	{
+		match self.deleted_datetime
+		{
+			Some(_) => fs.trash_path().join(self.get_filename()),
+			None => fs.pictures_path().join(self.get_filename()),
+		}
+	}
+}
+
+impl Photo // Database helpers
+{
+	/// Builds a photo from a row of the photos table
+	pub(crate) fn from_row(row: &rusqlite::Row) -> Result<Photo, rusqlite::Error>
+	{
+		let orientation: Option<String> = row.get("orientation")?;
+		Ok(Photo
+		{
+			id:					row.get("id")?,
+			filename:			row.get("filename")?,
+			hash:				u128::from_ne_bytes(row.get("hash")?),
+			import_datetime:	row.get("import_datetime")?,
+			rating:				row.get::<_, Option<u32>>("rating")?.unwrap_or(0),
+			starred:			row.get::<_, Option<bool>>("starred")?.unwrap_or(false),
+			color_label:		row.get::<_, Option<String>>("color_label")?.and_then(|name| ColorLabel::from_name(&name)),
+			flag:				PickFlag::from_value(row.get::<_, Option<i32>>("flag")?.unwrap_or(0)),
+			title:				row.get("title")?,
+			comment:			row.get("comment")?,
+			metadata:			Metadata
+			{
+				height:				row.get("height")?,
+				width:				row.get("width")?,
+				creation_datetime:	row.get("creation_datetime")?,
+				format:				row.get("format")?,
+				orientation:		orientation.and_then(|o| o.parse().ok()),
+				display_height:		row.get("display_height")?,
+				display_width:		row.get("display_width")?,
+				make:				row.get("make")?,
+				model:				row.get("model")?,
+				lens:				row.get("lens")?,
+				aperture:			row.get("aperture")?,
+				focal_length:		row.get("focal_length")?,
+				exposure_time:		row.get("exposure_time")?,
+				exposure_mode:		row.get("exposure_mode")?,
+				sensitivity:		row.get("sensitivity")?,
+				flash:				row.get("flash")?,
+				metering_mode:		row.get("metering_mode")?,
+				latitude:			row.get("latitude")?,
+				longitude:			row.get("longitude")?,
+			},
+			perceptual_hash:	row.get::<_, Option<i64>>("perceptual_hash")?.map(|hash| hash as u64),
+			thumbnail_hash:		row.get("thumbnail_hash")?,
+			edits:				edit::stack_from_setting(row.get::<_, Option<String>>("edits")?.as_deref()),
+			deleted_datetime:	row.get("deleted_datetime")?,
+
+		})
+	}
+
+	/// Returns the id of the first photo of the library having this hash, the photos in the trash are not counted
+	pub(crate) fn find_by_hash(db: &Database, hash: u128) -> Result<Option<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT id FROM photos WHERE hash = ?1 AND deleted_datetime IS NULL \
+			ORDER BY id LIMIT 1")?;
+		let mut rows = stmt.query([&hash.to_ne_bytes()])?;
+		match rows.next()?
+		{
+			Some(row) => Ok(Some(row.get(0)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the hash of every photo out of the trash with the id of the first photo having it
+	pub(crate) fn list_hashes(db: &Database) -> Result<HashMap<u128, u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT hash, id FROM photos WHERE deleted_datetime IS NULL ORDER BY id")?;
+		let mut rows = stmt.query(())?;
+		let mut hashes = HashMap::new();
+		while let Some(row) = rows.next()?
+		{
+			hashes.entry(u128::from_ne_bytes(row.get(0)?)).or_insert(row.get(1)?);
+		}
+		Ok(hashes)
+	}
+
+	/// Stores what the thumbnails of the photo were rendered from
+	pub(crate) fn update_thumbnail_hash(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE photos SET thumbnail_hash = ?1 WHERE id = ?2", (&self.thumbnail_hash, &self.id))?;
+		Ok(())
+	}
+
+	/// Stores when the photo was moved to the trash
+	pub(crate) fn update_deleted_datetime(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE photos SET deleted_datetime = ?1 WHERE id = ?2", (&self.deleted_datetime, &self.id))?;
+		Ok(())
+	}
+
+	/// Stores the edits of the photo
+	pub(crate) fn update_edits(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE photos SET edits = ?1 WHERE id = ?2", (edit::stack_to_setting(&self.edits), &self.id))?;
+		Ok(())
+	}
+
+	/// Sets a column of the photos table for every photo of `ids`, in a savepoint.
+	/// Fails with NotFound and changes nothing if one of the photos does not exist.
+	pub(crate) fn update_column_of_photos<T: rusqlite::ToSql>(db: &Database, ids: &[u32], column: &str, value: T)
+	-> Result<(), OsplError>
+	{
+		db.savepoint(||
+		{
+			let mut stmt = db.connection.prepare_cached(&format!("UPDATE photos SET {} = ?1 WHERE id = ?2", column))?;
+			for id in ids
+			{
+				if stmt.execute((&value, id))? == 0
+				{
+					return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
+				}
+			}
+			Ok(())
+		})
+	}
+
+	/// Returns the photos matching an SQL condition, which can end with an ORDER BY clause.
+	/// The photos in the trash are left out.
+	pub(crate) fn list_where<P: rusqlite::Params>(db: &Database, condition: &str, params: P) -> Result<Vec<Photo>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached(&format!("SELECT * FROM photos WHERE deleted_datetime IS NULL AND {}", condition))?;
+		let mut rows = stmt.query(params)?;
+		let mut photos = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			photos.push(Photo::from_row(row)?);
+		}
+		Ok(photos)
+	}
+
+	/// Returns the number of photos matching an SQL condition, the photos in the trash are left out
+	pub(crate) fn count_where<P: rusqlite::Params>(db: &Database, condition: &str, params: P) -> Result<u32, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached(&format!("SELECT COUNT(*) FROM photos WHERE deleted_datetime IS NULL AND {}", condition))?;
+		Ok(stmt.query_row(params, |row| row.get(0))?)
+	}
+
+	/// Returns the photos in the trash, the most recently deleted first
+	pub(crate) fn list_trash(db: &Database) -> Result<Vec<Photo>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM photos WHERE deleted_datetime IS NOT NULL \
+			ORDER BY deleted_datetime DESC, id")?;
+		let mut rows = stmt.query(())?;
+		let mut photos = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			photos.push(Photo::from_row(row)?);
+		}
+		Ok(photos)
+	}
+
+	/// Returns the id and the perceptual hash of every photo having one, out of the trash
+	pub(crate) fn list_perceptual_hashes(db: &Database) -> Result<Vec<(u32, u64)>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT id, perceptual_hash FROM photos \
+			WHERE perceptual_hash IS NOT NULL AND deleted_datetime IS NULL ORDER BY id")?;
+		let mut rows = stmt.query(())?;
+		let mut hashes = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			hashes.push((row.get(0)?, row.get::<_, i64>(1)? as u64));
+		}
+		Ok(hashes)
+	}
+
+	/// Returns the photos sharing the same hash, grouped together, the photos in the trash are left out
+	pub(crate) fn list_exact_duplicates(db: &Database) -> Result<Vec<Vec<Photo>>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM photos WHERE deleted_datetime IS NULL AND hash IN \
+			(SELECT hash FROM photos WHERE deleted_datetime IS NULL GROUP BY hash HAVING COUNT(*) > 1) ORDER BY hash, id")?;
+		let mut rows = stmt.query(())?;
+		let mut groups: Vec<Vec<Photo>> = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			let photo = Photo::from_row(row)?;
+			match groups.last_mut()
+			{
+				Some(group) if group[0].hash == photo.hash => group.push(photo),
+				_ => groups.push(vec![photo]),
+			}
+		}
+		groups.sort_by_key(|group| group[0].id);
+		Ok(groups)
+	}
 }
 
 impl ElementDatabase for Photo
 {
-	/// Deletes the photo from the database with its id
+	/// Deletes the photo from the database with its id, its links to its tags and its albums go with it
 	fn delete(&self, db: &Database) -> Result<(), OsplError>
 	{
 		db.connection.execute("DELETE FROM photos WHERE id = ?1", [&self.id])?;
@@ -137,8 +575,19 @@ impl ElementDatabase for Photo
 	/// Insert a photo into the database, returns the id of it.
 	fn insert_into(&self, db: &Database) -> Result<u32, OsplError>
 	{
-		db.connection.execute("INSERT INTO photos (filename, hash, import_datetime) VALUES (?1, ?2, ?3)",
-		(&self.filename, &self.hash.to_ne_bytes(), &self.import_datetime))?;
+		let metadata = &self.metadata;
+		db.connection.execute("INSERT INTO photos (filename, hash, import_datetime, height, width, \
+			creation_datetime, format, orientation, make, model, lens, aperture, focal_length, \
+			exposure_time, exposure_mode, sensitivity, flash, metering_mode, perceptual_hash, thumbnail_hash, \
+			display_height, display_width, latitude, longitude) \
+			VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
+		rusqlite::params![&self.filename, &self.hash.to_ne_bytes(), &self.import_datetime,
+			metadata.height, metadata.width, metadata.creation_datetime, metadata.format,
+			metadata.orientation.map(|o| o.to_string()), metadata.make, metadata.model, metadata.lens,
+			metadata.aperture, metadata.focal_length, metadata.exposure_time, metadata.exposure_mode,
+			metadata.sensitivity, metadata.flash, metadata.metering_mode,
+			self.perceptual_hash.map(|hash| hash as i64), &self.thumbnail_hash,
+			metadata.display_height, metadata.display_width, metadata.latitude, metadata.longitude])?;
 		Ok(db.connection.last_insert_rowid() as u32)
 	}
 
@@ -151,14 +600,11 @@ impl ElementDatabase for Photo
 	/// loads the photo object with data from db with its id
 	fn load_from_id(&mut self, db: &Database, id: u32) -> Result<(), OsplError>
 	{
-		let mut stmt = db.connection.prepare("SELECT * FROM photos WHERE id = ?1")?;
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM photos WHERE id = ?1")?;
 		let mut rows = stmt.query([&id])?;
 		while let Some(row) = rows.next()?
 		{
-			self.id = row.get(0)?;
-			self.filename = row.get(1)?;
-			self.hash = u128::from_ne_bytes(row.get(2)?);
-			self.import_datetime = row.get(4)?;
+			*self = Photo::from_row(row)?;
 		}
 		if self.id == 0
 		{
@@ -168,34 +614,22 @@ impl ElementDatabase for Photo
 	}
 }
 
-impl ElementFilesystem for Photo
+/// Checks if the file is an image
+/// Fails if the file cannot be imported: when it is a directory or not a photo
+pub(crate) fn check_importable<P: AsRef<Path>>(path: P) -> Result<(), OsplError>
 {
-	/// Inserts a photo into the filesystem using `self.path_on_fs` variable
-	fn insert_into(&self, fs: &Filesystem) -> Result<(), OsplError>
+	if path.as_ref().is_dir()
 	{
-		std::fs::copy(&self.path_on_fs, fs.pictures_path().join(self.get_filename()))?;
-		Ok(())
+		return Err(OsplError::InternalError(Error::IsADirectory));
 	}
-
-	/// Remove everything related to a photo from the filesystem.
-	///
-	/// this includes the thumbnail, and in the future every reference to it in the albums
-	fn remove_from(&self, fs: &Filesystem) -> Result<(), OsplError>
-	{
-		std::fs::remove_file(fs.pictures_path().join(self.get_filename()))?;
-		std::fs::remove_file(fs.thumbnails_path().join(self.get_filename()))?;
-		Ok(())
-	}
-
-	#[cfg(not(tarpaulin_include))]
-	fn rename(&self, _fs: &Filesystem, _new_name: &str) -> Result<(), OsplError>
+	if !is_photo(&path)?
 	{
-		unimplemented!()
+		return Err(OsplError::InternalError(Error::NotAnImage));
 	}
+	Ok(())
 }
 
-/// Checks if the file is an image
-fn is_photo<P: AsRef<Path>>(path: P) -> Result<bool, OsplError>
+pub(crate) fn is_photo<P: AsRef<Path>>(path: P) -> Result<bool, OsplError>
 {
 	match infer::get_from_path(path)?
 	{
@@ -213,51 +647,15 @@ fn is_photo<P: AsRef<Path>>(path: P) -> Result<bool, OsplError>
 
 impl ElementListing<Photo> for Photo
 {
+	/// Lists the photos of the library, the photos in the trash are left out
 	fn list_all(db: &Database, _fs: &Filesystem) -> Result<Vec<Photo>, OsplError>
 	{
 		let mut photos: Vec<Photo> = Vec::new();
-		let mut stmt = db.connection.prepare("SELECT * FROM photos")?;
-		let mut rows = stmt.query(())?;
-		while let Some(row) = rows.next()?
-		{
-			let photo = Photo
-			{
-				id:					row.get(0)?,
-				filename:			row.get(1)?,
-				hash:				u128::from_ne_bytes(row.get(2)?),
-				import_datetime:	row.get(4)?,
-				rating:				row.get(10)?,
-				starred:			row.get(11)?,
-
-				path_on_fs:			Path::new("").to_path_buf(),
-			};
-			photos.push(photo);
-		}
-		Ok(photos)
-	}
-}
-impl ElementListing<(u32, PathBuf)> for Photo
-{
-	fn list_all(db: &Database, fs: &Filesystem) -> Result<Vec<(u32, PathBuf)>, OsplError>
-	{
-		let mut photos: Vec<(u32, PathBuf)> = Vec::new();
-		let mut stmt = db.connection.prepare("SELECT * FROM photos")?;
+		let mut stmt = db.connection.prepare_cached("SELECT * FROM photos WHERE deleted_datetime IS NULL")?;
 		let mut rows = stmt.query(())?;
 		while let Some(row) = rows.next()?
 		{
-			let photo = Photo
-			{
-				id:					row.get(0)?,
-				filename:			row.get(1)?,
-				hash:				u128::from_ne_bytes(row.get(2)?),
-				import_datetime:	row.get(4)?,
-				rating:				row.get(10)?,
-				starred:			row.get(11)?,
-
-				path_on_fs:			Path::new("").to_path_buf(),
-			};
-			let thumbnail_path = fs.thumbnails_path().join(photo.get_filename());
-			photos.push((photo.id(), thumbnail_path));
+			photos.push(Photo::from_row(row)?);
 		}
 		Ok(photos)
 	}
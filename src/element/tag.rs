@@ -0,0 +1,384 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform Photo library management that can be used
+	to store and sort all your Photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+
+use crate::element::photo::Photo;
+use crate::element::traits::ElementDatabase;
+use crate::element::traits::ElementListing;
+use crate::element::traits::InsideElementListing;
+use crate::Database;
+use crate::Filesystem;
+use crate::{Error, OsplError};
+
+/// Separator between the tags names in a tag path, like "Places/France/Paris"
+pub static TAG_PATH_SEPARATOR: char = '/';
+
+/// Structure containing a replica of sqlite data
+#[derive(Debug)]
+pub struct Tag
+{
+	id:		u32,
+	name:	String,
+	parent:	Option<u32>,
+	path:	String,
+}
+
+impl Default for Tag
+{
+	fn default() -> Self
+	{
+		Tag::new()
+	}
+}
+
+// Constructors
+impl Tag
+{
+	/// Returns an empty Tag element
+	pub fn new() -> Self
+	{
+		Tag
+		{
+			id: 0,
+			name: String::from(""),
+			parent: None,
+			path: String::from(""),
+		}
+	}
+
+	/// Returns a tag element filled with its name and its parent tag id
+	pub fn new_with_name(name: &str, parent: Option<u32>) -> Self
+	{
+		Tag
+		{
+			id: 0,
+			name: String::from(name),
+			parent,
+			path: String::from(name),
+		}
+	}
+}
+
+// Getters and setters
+impl Tag
+{
+	pub fn set_id(&mut self, id: u32)
+	{
+		self.id = id;
+	}
+
+	pub fn id(&self) -> u32
+	{
+		self.id
+	}
+
+	pub fn name(&self) -> String
+	{
+		self.name.clone()
+	}
+
+	/// Returns the id of the parent tag, None for a tag at the root
+	pub fn parent(&self) -> Option<u32>
+	{
+		self.parent
+	}
+
+	/// Returns the full path of the tag, like "Places/France/Paris"
+	pub fn path(&self) -> String
+	{
+		self.path.clone()
+	}
+
+	/// Returns the path the way Lightroom writes it in `lr:hierarchicalSubject`,
+	/// like "Places|France|Paris"
+	pub fn hierarchical_subject(&self) -> String
+	{
+		self.path.replace(TAG_PATH_SEPARATOR, "|")
+	}
+}
+
+impl Tag // Database helpers
+{
+	fn from_row(row: &rusqlite::Row) -> Result<Tag, rusqlite::Error>
+	{
+		Ok(Tag
+		{
+			id: row.get("id")?,
+			name: row.get("name")?,
+			parent: row.get("parent")?,
+			path: row.get("path")?,
+		})
+	}
+
+	/// Returns the id of the tag with this name under `parent`, if there is one
+	pub(crate) fn find(db: &Database, name: &str, parent: Option<u32>) -> Result<Option<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT id FROM tags WHERE name = ?1 AND IFNULL(parent, 0) = ?2")?;
+		let mut rows = stmt.query((name, parent.unwrap_or(0)))?;
+		match rows.next()?
+		{
+			Some(row) => Ok(Some(row.get(0)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the ids of the tag and of all the tags below it in the hierarchy
+	pub(crate) fn descendants(db: &Database, id: u32) -> Result<Vec<u32>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("WITH RECURSIVE descendants(id) AS \
+			(SELECT ?1 UNION SELECT tags.id FROM tags INNER JOIN descendants ON tags.parent = descendants.id) \
+			SELECT id FROM descendants")?;
+		let mut rows = stmt.query([&id])?;
+		let mut ids = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			ids.push(row.get(0)?);
+		}
+		Ok(ids)
+	}
+
+	/// Deletes the tag and the tags below it, without opening a savepoint
+	fn delete_with_descendants(&self, db: &Database) -> Result<(), OsplError>
+	{
+		for id in Tag::descendants(db, self.id)?.iter().rev()
+		{
+			db.connection.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+		}
+		Ok(())
+	}
+}
+
+impl ElementDatabase for Tag
+{
+	/// Deletes the tag with all the tags below it, and detaches them from every photo
+	fn delete(&self, db: &Database) -> Result<(), OsplError>
+	{
+		db.savepoint(|| self.delete_with_descendants(db))
+	}
+
+	fn insert_into(&self, db: &Database) -> Result<u32, OsplError>
+	{
+		db.connection.execute("INSERT INTO tags (name, parent) VALUES (?1, ?2)", (&self.name, &self.parent))?;
+		Ok(db.connection.last_insert_rowid() as u32)
+	}
+
+	fn rename(&self, db: &Database, new_name: &str) -> Result<(), OsplError>
+	{
+		db.connection.execute("UPDATE tags SET name = ?1 WHERE id = ?2", (new_name, &self.id))?;
+		Ok(())
+	}
+
+	fn load_from_id(&mut self, db: &Database, id: u32) -> Result<(), OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT tags.*, tags_paths.path FROM tags \
+			INNER JOIN tags_paths ON tags_paths.id = tags.id WHERE tags.id = ?1")?;
+		let mut rows = stmt.query([&id])?;
+		while let Some(row) = rows.next()?
+		{
+			*self = Tag::from_row(row)?;
+		}
+		if self.id == 0
+		{
+			return Err(OsplError::IoError(std::io::ErrorKind::NotFound));
+		}
+		Ok(())
+	}
+}
+
+// Specific Database functions
+impl Tag
+{
+	/// Attaches the tag to every given photo, photos already tagged are left untouched
+	pub fn attach(&self, db: &Database, photos: &[u32]) -> Result<(), OsplError>
+	{
+		db.savepoint(||
+		{
+			for photo in photos
+			{
+				db.connection.execute("INSERT INTO photos_tags_map (containing_tag, contained_photo) \
+					SELECT ?1, ?2 WHERE NOT EXISTS (SELECT 1 FROM photos_tags_map \
+					WHERE containing_tag = ?1 AND contained_photo = ?2)", (&self.id, photo))?;
+			}
+			Ok(())
+		})
+	}
+
+	/// Detaches the tag from every given photo
+	pub fn detach(&self, db: &Database, photos: &[u32]) -> Result<(), OsplError>
+	{
+		db.savepoint(||
+		{
+			for photo in photos
+			{
+				db.connection.execute("DELETE FROM photos_tags_map WHERE containing_tag = ?1 AND contained_photo = ?2",
+				(&self.id, photo))?;
+			}
+			Ok(())
+		})
+	}
+
+	/// Moves every photo and every child tag of this tag to `target`, then deletes this tag.
+	/// A child tag named like a child of `target` is merged into it the same way.
+	pub fn merge_into(&self, db: &Database, target: &Tag) -> Result<(), OsplError>
+	{
+		db.savepoint(||
+		{
+			Tag::merge_content(db, self.id, target.id())?;
+			self.delete_with_descendants(db)
+		})
+	}
+
+	/// Copies the photos of the tag `source` to `target` and moves its children below `target`,
+	/// the children named like a child of `target` are merged into it and left in place
+	fn merge_content(db: &Database, source: u32, target: u32) -> Result<(), OsplError>
+	{
+		db.connection.execute("INSERT INTO photos_tags_map (containing_tag, contained_photo) \
+			SELECT DISTINCT ?2, contained_photo FROM photos_tags_map WHERE containing_tag = ?1 \
+			AND contained_photo NOT IN (SELECT contained_photo FROM photos_tags_map WHERE containing_tag = ?2)",
+			(source, target))?;
+		let mut stmt = db.connection.prepare_cached("SELECT id, name FROM tags WHERE parent = ?1")?;
+		let children: Vec<(u32, String)> = stmt.query_map([source], |row| Ok((row.get(0)?, row.get(1)?)))?
+			.collect::<Result<_, _>>()?;
+		for (child, name) in children
+		{
+			match Tag::find(db, &name, Some(target))?
+			{
+				Some(existing) => Tag::merge_content(db, child, existing)?,
+				None =>
+				{
+					db.connection.execute("UPDATE tags SET parent = ?2 WHERE id = ?1", (child, target))?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns the (tag, photo) rows of photos_tags_map pointing to a tag or a photo that does not exist
+	pub(crate) fn list_dangling_links(db: &Database) -> Result<Vec<(u32, u32)>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT containing_tag, contained_photo FROM photos_tags_map \
+			WHERE containing_tag NOT IN (SELECT id FROM tags) OR contained_photo NOT IN (SELECT id FROM photos) \
+			ORDER BY containing_tag, contained_photo")?;
+		let mut rows = stmt.query(())?;
+		let mut links = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			links.push((row.get(0)?, row.get(1)?));
+		}
+		Ok(links)
+	}
+
+	/// Returns true if the tag is attached to a photo, the photos in the trash included
+	pub(crate) fn is_attached(db: &Database, id: u32) -> Result<bool, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT 1 FROM photos_tags_map WHERE containing_tag = ?1")?;
+		Ok(stmt.exists([id])?)
+	}
+
+	/// Removes the rows of photos_tags_map linking the tag and the photo
+	pub(crate) fn delete_link(db: &Database, tag: u32, photo: u32) -> Result<(), OsplError>
+	{
+		db.connection.execute("DELETE FROM photos_tags_map WHERE containing_tag = ?1 AND contained_photo = ?2", (tag, photo))?;
+		Ok(())
+	}
+
+	/// Puts the tag under another tag, or at the root of the hierarchy with None
+	pub fn move_to(&self, db: &Database, parent: Option<u32>) -> Result<(), OsplError>
+	{
+		if let Some(parent) = parent
+		{
+			if Tag::descendants(db, self.id)?.contains(&parent)
+			{
+				return Err(OsplError::InternalError(Error::MovedInsideItself));
+			}
+		}
+		db.connection.execute("UPDATE tags SET parent = ?1 WHERE id = ?2", (parent, &self.id))?;
+		Ok(())
+	}
+}
+
+impl ElementListing<Tag> for Tag
+{
+	fn list_all(db: &Database, _fs: &Filesystem) -> Result<Vec<Tag>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT tags.*, tags_paths.path FROM tags \
+			INNER JOIN tags_paths ON tags_paths.id = tags.id ORDER BY tags_paths.path")?;
+		let mut rows = stmt.query(())?;
+		let mut tags = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			tags.push(Tag::from_row(row)?);
+		}
+		Ok(tags)
+	}
+}
+
+impl InsideElementListing<Photo> for Tag
+{
+	/// Lists the photos tagged with the tag or with any tag below it, the photos in the trash are left out
+	fn list_inside(db: &Database, id: u32) -> Result<Vec<Photo>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("WITH RECURSIVE descendants(id) AS \
+			(SELECT ?1 UNION SELECT tags.id FROM tags INNER JOIN descendants ON tags.parent = descendants.id) \
+			SELECT * FROM photos WHERE id IN (SELECT contained_photo FROM photos_tags_map \
+			WHERE containing_tag IN descendants) AND deleted_datetime IS NULL ORDER BY id")?;
+		let mut rows = stmt.query([&id])?;
+		let mut photos = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			photos.push(Photo::from_row(row)?);
+		}
+		Ok(photos)
+	}
+}
+
+impl InsideElementListing<Tag> for Photo
+{
+	fn list_inside(db: &Database, id: u32) -> Result<Vec<Tag>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT tags.*, tags_paths.path FROM tags \
+			INNER JOIN tags_paths ON tags_paths.id = tags.id \
+			INNER JOIN photos_tags_map ON photos_tags_map.containing_tag = tags.id \
+			WHERE photos_tags_map.contained_photo = ?1 ORDER BY tags_paths.path")?;
+		let mut rows = stmt.query([&id])?;
+		let mut tags = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			tags.push(Tag::from_row(row)?);
+		}
+		Ok(tags)
+	}
+}
+
+impl InsideElementListing<Tag> for Tag
+{
+	/// Lists the tags directly below the tag
+	fn list_inside(db: &Database, id: u32) -> Result<Vec<Tag>, OsplError>
+	{
+		let mut stmt = db.connection.prepare_cached("SELECT tags.*, tags_paths.path FROM tags \
+			INNER JOIN tags_paths ON tags_paths.id = tags.id WHERE tags.parent = ?1 ORDER BY tags.name")?;
+		let mut rows = stmt.query([&id])?;
+		let mut tags = Vec::new();
+		while let Some(row) = rows.next()?
+		{
+			tags.push(Tag::from_row(row)?);
+		}
+		Ok(tags)
+	}
+}
@@ -50,8 +50,6 @@ pub trait ElementFilesystem
 {
 	/// Inserts the element into the library filesystem
 	fn insert_into(&self, fs: &Filesystem) -> Result<(), OsplError>;
-	/// Removes the element from the library filesystem
-	fn remove_from(&self, fs: &Filesystem) -> Result<(), OsplError>;
 	/// Rename the element on the library filesystem
 	fn rename(&self, fs: &Filesystem, new_name: &str) -> Result<(), OsplError>;
 }
\ No newline at end of file
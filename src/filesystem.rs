@@ -19,7 +19,7 @@
 */
 
 use crate::{Error, OsplError};
-use crate::database::Database;
+use crate::api::thumbnail::{ThumbnailFormat, ThumbnailPreset};
 use crate::element::traits::ElementFilesystem;
 
 use super::DATABASE_FILENAME;
@@ -34,6 +34,8 @@ pub struct Filesystem
 	pictures_path: PathBuf,
 	thumbnails_path: PathBuf,
 	collections_path: PathBuf,
+	trash_path: PathBuf,
+	journal_path: PathBuf,
 	database_path:	PathBuf,
 }
 
@@ -48,6 +50,8 @@ impl Filesystem
 				thumbnails_path: path.as_ref().join("thumbnails"),
 				pictures_path: path.as_ref().join("pictures"),
 				collections_path: path.as_ref().join("collections"),
+				trash_path: path.as_ref().join("trash"),
+				journal_path: path.as_ref().join("journal"),
 				database_path: path.as_ref().join(DATABASE_FILENAME),
 			});
 	}
@@ -59,7 +63,30 @@ impl Filesystem
 		std::fs::create_dir(&fs.thumbnails_path)?;
 		std::fs::create_dir(&fs.pictures_path)?;
 		std::fs::create_dir(&fs.collections_path)?;
-		Database::create(&fs.database_path)?;
+		Ok(fs)
+	}
+
+	/// Creates the filesystem object of an existing library, after checking its structure
+	pub(crate) fn load<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
+	{
+		if !std::fs::metadata(&path)?.is_dir()
+		{
+			return Err(OsplError::InternalError(Error::NotALibrary));
+		}
+		let fs = Self::new(path)?;
+		if !fs.database_path.is_file()
+		{
+			return Err(OsplError::InternalError(Error::NotALibrary));
+		}
+		for directory in [&fs.thumbnails_path, &fs.pictures_path, &fs.collections_path]
+		{
+			if !directory.is_dir()
+			{
+				return Err(OsplError::InternalError(Error::CorruptedLibrary));
+			}
+		}
+		// Fails early with PermissionDenied, sqlite would only report it can't open the file
+		std::fs::File::open(&fs.database_path)?;
 		Ok(fs)
 	}
 }
@@ -90,6 +117,28 @@ impl Filesystem
 		self.collections_path.to_path_buf()
 	}
 
+	/// Returns the path on filesystem of the directory keeping the originals of the deleted photos.
+	///
+	/// It is created with the first deleted photo, libraries created before the trash do not have it.
+	pub fn trash_path(&self) -> PathBuf
+	{
+		self.trash_path.to_path_buf()
+	}
+
+	/// Returns the path on filesystem of the thumbnail of a photo for a preset,
+	/// `filename` being the name of the photo in the pictures directory
+	pub fn thumbnail_path(&self, filename: &str, preset: ThumbnailPreset, format: ThumbnailFormat) -> PathBuf
+	{
+		self.thumbnails_path.join(preset.name()).join(Path::new(filename).with_extension(format.extension()))
+	}
+
+	/// Returns the path on filesystem of the directory keeping the files removed
+	/// by the running operations until they are committed
+	pub fn journal_path(&self) -> PathBuf
+	{
+		self.journal_path.to_path_buf()
+	}
+
 	/// Returns the path on filesystem of the database file
 	pub fn database_path(&self) -> PathBuf
 	{
@@ -114,12 +163,4 @@ impl Filesystem
 	{
 		object.rename(self, new_name)
 	}
-
-	/// Removes the element from the library filesystem
-	///
-	/// If fs.remove(object) is called, it will call object.remove_from(Filesystem struct)
-	pub(crate) fn remove(&self, object: &dyn ElementFilesystem) -> Result<(), OsplError>
-	{
-		object.remove_from(self)
-	}
 }
@@ -0,0 +1,79 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform photo library management that can be used
+	to store and sort all your photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+use crate::OsplError;
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use xxhash_rust::xxh3::Xxh3;
+
+// Files are read by chunks so a big RAW or video file is never fully loaded in memory.
+// The streaming hasher gives the same value as xxh3_128 on the whole file,
+// so the hashes stored before are still valid.
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Computes the xxh3 128 bits hash of a file
+pub(crate) fn hash_file<P: AsRef<Path>>(path: P) -> Result<u128, OsplError>
+{
+	let mut file = File::open(path)?;
+	let mut hasher = Xxh3::new();
+	let mut buffer = vec![0; CHUNK_SIZE];
+	loop
+	{
+		let read = file.read(&mut buffer)?;
+		if read == 0
+		{
+			return Ok(hasher.digest128());
+		}
+		hasher.update(&buffer[..read]);
+	}
+}
+
+/// Copies a file and computes its xxh3 128 bits hash at the same time, so it is only read once.
+/// The destination must not exist, it is removed if the copy fails.
+pub(crate) fn copy_and_hash<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<u128, OsplError>
+{
+	let mut source = File::open(from)?;
+	let mut destination = File::options().write(true).create_new(true).open(&to)?;
+	let mut hasher = Xxh3::new();
+	let mut buffer = vec![0; CHUNK_SIZE];
+	let result = loop
+	{
+		let read = match source.read(&mut buffer)
+		{
+			Ok(0) => break destination.flush(),
+			Ok(read) => read,
+			Err(e) => break Err(e),
+		};
+		hasher.update(&buffer[..read]);
+		if let Err(e) = destination.write_all(&buffer[..read])
+		{
+			break Err(e);
+		}
+	};
+	if let Err(e) = result
+	{
+		drop(destination);
+		let _ = std::fs::remove_file(&to);
+		return Err(e.into());
+	}
+	Ok(hasher.digest128())
+}
@@ -0,0 +1,325 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform photo library management that can be used
+	to store and sort all your photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+use crate::OsplError;
+
+use std::io::BufReader;
+use std::path::Path;
+use chrono::naive::NaiveDateTime;
+use exif::{Exif, In, Reader, Tag, Value};
+use log::debug;
+
+// The EXIF reader understands JPEG, TIFF (and most TIFF based RAW files),
+// PNG eXIf chunks, HEIF/HEIC and WebP containers.
+// Every maker writes its MakerNote block with its own layout: only the lens of Canon and Nikon
+// MakerNotes is read, for the cameras that do not write the standard LensModel/LensSpecification tags.
+
+/// Image information and camera settings read from a photo file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Metadata
+{
+	pub(crate) height:				Option<u32>,
+	pub(crate) width:				Option<u32>,
+	pub(crate) creation_datetime:	Option<NaiveDateTime>,
+	pub(crate) format:				Option<String>,
+	pub(crate) orientation:			Option<u32>,
+	pub(crate) display_height:		Option<u32>,
+	pub(crate) display_width:		Option<u32>,
+	pub(crate) make:				Option<String>,
+	pub(crate) model:				Option<String>,
+	pub(crate) lens:				Option<String>,
+	pub(crate) aperture:			Option<f64>,
+	pub(crate) focal_length:		Option<f64>,
+	pub(crate) exposure_time:		Option<String>,
+	pub(crate) exposure_mode:		Option<u32>,
+	pub(crate) sensitivity:			Option<u32>,
+	pub(crate) flash:				Option<u32>,
+	pub(crate) metering_mode:		Option<u32>,
+	pub(crate) latitude:			Option<f64>,
+	pub(crate) longitude:			Option<f64>,
+}
+
+impl Metadata
+{
+	/// Reads every metadata the library stores from the file at `path`.
+	///
+	/// A file without EXIF data is not an error, only the fields that can be
+	/// found are filled.
+	pub(crate) fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, OsplError>
+	{
+		let mut metadata = Metadata
+		{
+			format: infer::get_from_path(&path)?.map(|t| t.extension().to_string()),
+			..Default::default()
+		};
+		let file = std::fs::File::open(&path)?;
+		match Reader::new().read_from_container(&mut BufReader::new(file))
+		{
+			Ok(exif) => metadata.fill_from_exif(&exif),
+			Err(e) => debug!("no exif data in {:?}: {}", path.as_ref(), e),
+		}
+		if let Ok((width, height)) = image::image_dimensions(&path)
+		{
+			metadata.width = Some(width);
+			metadata.height = Some(height);
+		}
+		(metadata.display_width, metadata.display_height) = match metadata.orientation
+		{
+			Some(5..=8) => (metadata.height, metadata.width),
+			_ => (metadata.width, metadata.height),
+		};
+		Ok(metadata)
+	}
+
+	fn fill_from_exif(&mut self, exif: &Exif)
+	{
+		self.width = get_uint(exif, Tag::PixelXDimension).or_else(|| get_uint(exif, Tag::ImageWidth));
+		self.height = get_uint(exif, Tag::PixelYDimension).or_else(|| get_uint(exif, Tag::ImageLength));
+		self.creation_datetime = get_datetime(exif, Tag::DateTimeOriginal)
+			.or_else(|| get_datetime(exif, Tag::DateTimeDigitized))
+			.or_else(|| get_datetime(exif, Tag::DateTime));
+		self.orientation = get_uint(exif, Tag::Orientation);
+		self.make = get_ascii(exif, Tag::Make);
+		self.model = get_ascii(exif, Tag::Model);
+		self.lens = get_ascii(exif, Tag::LensModel)
+			.or_else(|| get_lens_specification(exif))
+			.or_else(|| get_maker_note_lens(exif));
+		self.aperture = get_rational(exif, Tag::FNumber);
+		self.focal_length = get_rational(exif, Tag::FocalLength);
+		self.exposure_time = get_exposure_time(exif);
+		self.exposure_mode = get_uint(exif, Tag::ExposureMode);
+		self.sensitivity = get_uint(exif, Tag::PhotographicSensitivity);
+		self.flash = get_uint(exif, Tag::Flash);
+		self.metering_mode = get_uint(exif, Tag::MeteringMode);
+		self.latitude = get_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S');
+		self.longitude = get_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W');
+	}
+}
+
+fn get_ascii(exif: &Exif, tag: Tag) -> Option<String>
+{
+	match &exif.get_field(tag, In::PRIMARY)?.value
+	{
+		Value::Ascii(values) =>
+		{
+			let text = String::from_utf8_lossy(values.first()?);
+			let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+			(!text.is_empty()).then(|| text.to_string())
+		}
+		_ => None,
+	}
+}
+
+fn get_uint(exif: &Exif, tag: Tag) -> Option<u32>
+{
+	exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+fn get_rational(exif: &Exif, tag: Tag) -> Option<f64>
+{
+	match &exif.get_field(tag, In::PRIMARY)?.value
+	{
+		Value::Rational(values) => values.first().filter(|r| r.denom != 0).map(|r| r.to_f64()),
+		_ => None,
+	}
+}
+
+fn get_datetime(exif: &Exif, tag: Tag) -> Option<NaiveDateTime>
+{
+	let text = get_ascii(exif, tag)?;
+	NaiveDateTime::parse_from_str(&text, "%Y:%m:%d %H:%M:%S").ok()
+}
+
+/// Returns the exposure time the way photographers write it, like "1/250" or "2.5"
+fn get_exposure_time(exif: &Exif) -> Option<String>
+{
+	let rational = match &exif.get_field(Tag::ExposureTime, In::PRIMARY)?.value
+	{
+		Value::Rational(values) => *values.first()?,
+		_ => return None,
+	};
+	if rational.num == 0 || rational.denom == 0
+	{
+		return None;
+	}
+	if rational.num >= rational.denom
+	{
+		return Some(format!("{}", rational.to_f64()));
+	}
+	let divisor = gcd(rational.num, rational.denom);
+	Some(format!("{}/{}", rational.num / divisor, rational.denom / divisor))
+}
+
+/// Reads a GPS coordinate written as degrees, minutes and seconds, in decimal degrees.
+/// It is negative when its reference is `negative` (S or W).
+fn get_coordinate(exif: &Exif, tag: Tag, reference: Tag, negative: u8) -> Option<f64>
+{
+	let values = match &exif.get_field(tag, In::PRIMARY)?.value
+	{
+		Value::Rational(values) if values.len() == 3 && values.iter().all(|r| r.denom != 0) => values.clone(),
+		_ => return None,
+	};
+	let degrees = values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0;
+	match &exif.get_field(reference, In::PRIMARY)?.value
+	{
+		Value::Ascii(refs) if refs.first()?.first() == Some(&negative) => Some(-degrees),
+		Value::Ascii(_) => Some(degrees),
+		_ => None,
+	}
+}
+
+/// Builds a lens description like "24-70mm f/2.8" from the LensSpecification tag
+fn get_lens_specification(exif: &Exif) -> Option<String>
+{
+	let values = match &exif.get_field(Tag::LensSpecification, In::PRIMARY)?.value
+	{
+		Value::Rational(values) if values.len() == 4 => values.clone(),
+		_ => return None,
+	};
+	let value = |index: usize| Some(values[index]).filter(|r| r.denom != 0).map(|r| r.to_f64());
+	format_lens(value(0)?, value(1)?, value(2))
+}
+
+/// Builds a lens description from its focal range and its widest aperture
+fn format_lens(min_focal: f64, max_focal: f64, aperture: Option<f64>) -> Option<String>
+{
+	let focal = match (min_focal, max_focal)
+	{
+		(min, max) if min == max => format!("{}mm", min),
+		(min, max) => format!("{}-{}mm", min, max),
+	};
+	match aperture
+	{
+		Some(aperture) => Some(format!("{} f/{}", focal, aperture)),
+		None => Some(focal),
+	}
+}
+
+/// Reads the lens from the MakerNote block of Canon and Nikon cameras
+fn get_maker_note_lens(exif: &Exif) -> Option<String>
+{
+	let offset = match &exif.get_field(Tag::MakerNote, In::PRIMARY)?.value
+	{
+		Value::Undefined(_, offset) => *offset as usize,
+		_ => return None,
+	};
+	let make = get_ascii(exif, Tag::Make)?.to_uppercase();
+	if make.starts_with("CANON")
+	{
+		// A Canon MakerNote is an IFD, its offsets start at the TIFF header of the EXIF data
+		let ifd = Ifd { buf: exif.buf(), base: 0, little_endian: exif.little_endian() };
+		let (kind, value) = ifd.entry(offset, 0x0095)?;
+		// The name ends at the first NUL, the rest of the field is padding
+		let name = value.split(|byte| *byte == 0).next()?;
+		let text = String::from_utf8_lossy(name).trim().to_string();
+		return (kind == 2 && !text.is_empty()).then_some(text);
+	}
+	if make.starts_with("NIKON") && exif.buf().get(offset..offset + 6)? == b"Nikon\0"
+	{
+		// A Nikon MakerNote has its own TIFF header after a 10 bytes signature
+		let base = offset + 10;
+		let little_endian = match exif.buf().get(base..base + 2)?
+		{
+			b"II" => true,
+			b"MM" => false,
+			_ => return None,
+		};
+		let ifd = Ifd { buf: exif.buf(), base, little_endian };
+		let (kind, value) = ifd.entry(base + ifd.u32(base + 4)? as usize, 0x0084)?;
+		if kind != 5 || value.len() != 32
+		{
+			return None;
+		}
+		let rational = |index: usize|
+		{
+			let (num, denom) = (ifd.u32_in(value, index * 8)?, ifd.u32_in(value, index * 8 + 4)?);
+			(denom != 0 && num != 0).then(|| num as f64 / denom as f64)
+		};
+		return format_lens(rational(0)?, rational(1)?, rational(2));
+	}
+	None
+}
+
+/// An IFD read outside of the EXIF reader, in the MakerNote blocks
+struct Ifd<'a>
+{
+	buf: &'a [u8],
+	/// Position in `buf` the offsets of the IFD start from
+	base: usize,
+	little_endian: bool,
+}
+
+impl<'a> Ifd<'a>
+{
+	fn u16(&self, at: usize) -> Option<u16>
+	{
+		let bytes = self.buf.get(at..at + 2)?.try_into().ok()?;
+		Some(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+	}
+
+	fn u32(&self, at: usize) -> Option<u32>
+	{
+		self.u32_in(self.buf, at)
+	}
+
+	fn u32_in(&self, bytes: &[u8], at: usize) -> Option<u32>
+	{
+		let bytes = bytes.get(at..at + 4)?.try_into().ok()?;
+		Some(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+	}
+
+	/// Returns the type and the value bytes of the entry `tag` of the IFD at `at`
+	fn entry(&self, at: usize, tag: u16) -> Option<(u16, &'a [u8])>
+	{
+		let count = self.u16(at)? as usize;
+		for entry in (0..count).map(|index| at + 2 + index * 12)
+		{
+			if self.u16(entry)? != tag
+			{
+				continue;
+			}
+			let kind = self.u16(entry + 2)?;
+			let size = match kind
+			{
+				1 | 2 | 6 | 7 => 1,
+				3 | 8 => 2,
+				4 | 9 | 11 => 4,
+				5 | 10 | 12 => 8,
+				_ => return None,
+			} * self.u32(entry + 4)? as usize;
+			// Values of up to 4 bytes are in the entry, the others are pointed to
+			let start = match size
+			{
+				0..=4 => entry + 8,
+				_ => self.base + self.u32(entry + 8)? as usize,
+			};
+			return Some((kind, self.buf.get(start..start.checked_add(size)?)?));
+		}
+		None
+	}
+}
+
+fn gcd(a: u32, b: u32) -> u32
+{
+	if b == 0
+	{
+		return a;
+	}
+	gcd(b, a % b)
+}
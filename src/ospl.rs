@@ -32,13 +32,22 @@ pub static VERSION_REVISION: &str = env!("CARGO_PKG_VERSION_PATCH");
 
 mod database;
 mod filesystem;
+mod transaction;
 
 mod thumbnails;
+mod edits;
+mod metadata;
+mod hash;
+mod perceptual_hash;
 
 pub mod element;
 
 pub mod api;
 
+#[cfg(any(test, feature = "failpoints"))]
+#[doc(hidden)]
+pub use transaction::failpoint;
+
 use std::path::PathBuf;
 
 use element::album::Album;
@@ -46,6 +55,7 @@ use database::Database;
 use filesystem::Filesystem;
 use element::photo::Photo;
 use element::collection::Collection;
+use element::tag::Tag;
 
 #[derive(Debug, PartialEq)]
 pub enum OsplError
@@ -53,6 +63,8 @@ pub enum OsplError
 	DatabaseError(rusqlite::Error),
 	IoError(std::io::ErrorKind),
 	InternalError(Error),
+	/// The photo is already in the library, with this id
+	DuplicatePhoto(u32),
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -64,6 +76,7 @@ impl std::fmt::Display for OsplError
 			OsplError::DatabaseError(e) => write!(f, "Database error: {}", e),
 			OsplError::IoError(e) => write!(f, "IO error: {}", e),
 			OsplError::InternalError(e) => write!(f, "Internal error: {:?}", e),
+			OsplError::DuplicatePhoto(id) => write!(f, "Photo already in the library with id {}", id),
 		}
 	}
 }
@@ -119,12 +132,29 @@ pub enum Error
 	IsADirectory,
 	/// No name was specified
 	EmptyName,
+	/// The name contains a character that is not allowed
+	InvalidName,
+	/// The element cannot be moved inside itself or one of its children
+	MovedInsideItself,
+	/// The directory is not an ospl library
+	NotALibrary,
+	/// The library is missing some of its directories, or its database is damaged
+	CorruptedLibrary,
+	/// The database of the library is locked by another process
+	LibraryLocked,
+	/// The library was written by a newer version of ospl
+	LibraryTooNew,
+	/// The element still contains other elements
+	NotEmpty,
+	/// The settings are out of their range, or not supported by their format
+	InvalidSettings,
 }
 
 #[derive(Debug)]
 pub struct Library
 {
 	fs: Filesystem,
+	db: Database,
 }
 
 impl Library // Get functions
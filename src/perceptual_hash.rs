@@ -0,0 +1,88 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform photo library management that can be used
+	to store and sort all your photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+use image::DynamicImage;
+use image::imageops::FilterType;
+
+// The difference hash (dHash) shrinks the image to 9x8 grey pixels and keeps one bit
+// per pixel telling if it is brighter than its right neighbour.
+// It survives re-encoding, resizing and small color changes, so two files of the same
+// picture in different formats end up with hashes only a few bits apart.
+
+/// Computes the 64 bits difference hash of an image
+pub(crate) fn dhash(img: &DynamicImage) -> u64
+{
+	let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+	let mut hash: u64 = 0;
+	for y in 0..8
+	{
+		for x in 0..8
+		{
+			hash <<= 1;
+			if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0]
+			{
+				hash |= 1;
+			}
+		}
+	}
+	hash
+}
+
+/// Number of bits that differ between two hashes, 0 means visually identical
+pub(crate) fn distance(first: u64, second: u64) -> u32
+{
+	(first ^ second).count_ones()
+}
+
+/// Groups the ids whose hashes are at most `max_distance` apart, directly or through
+/// other similar photos. Only groups of at least two ids are returned.
+pub(crate) fn cluster(hashes: &[(u32, u64)], max_distance: u32) -> Vec<Vec<u32>>
+{
+	let mut roots: Vec<usize> = (0..hashes.len()).collect();
+	for first in 0..hashes.len()
+	{
+		for second in first + 1..hashes.len()
+		{
+			if distance(hashes[first].1, hashes[second].1) <= max_distance
+			{
+				let first_root = find_root(&mut roots, first);
+				let second_root = find_root(&mut roots, second);
+				roots[second_root.max(first_root)] = second_root.min(first_root);
+			}
+		}
+	}
+	let mut groups: Vec<Vec<u32>> = vec![Vec::new(); hashes.len()];
+	for (index, (id, _)) in hashes.iter().enumerate()
+	{
+		let root = find_root(&mut roots, index);
+		groups[root].push(*id);
+	}
+	groups.into_iter().filter(|group| group.len() > 1).collect()
+}
+
+fn find_root(roots: &mut [usize], index: usize) -> usize
+{
+	let mut root = index;
+	while roots[root] != root
+	{
+		root = roots[root];
+	}
+	roots[index] = root;
+	root
+}
@@ -17,10 +17,16 @@
 	with this program; if not, write to the Free Software Foundation, Inc.,
 	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 */
-use crate::OsplError;
+use crate::{Error, OsplError};
+use crate::api::edit;
+use crate::api::thumbnail::{ThumbnailFormat, ThumbnailSettings};
+use crate::element::photo::Photo;
 
-use std::path::Path;
+use image::{ColorType, DynamicImage, ImageEncoder};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
 use image::imageops::thumbnail;
+use xxhash_rust::xxh3::xxh3_64;
 
 // We should consider using a more efficient crate for creating thumbnails,
 // such as libvips or imagemagick.
@@ -28,16 +34,74 @@ use image::imageops::thumbnail;
 // However, currently, this is not a top priority as the primary goal is to
 // establish the basic functionality of the OSPL project.
 
-/// creates a thumbnail of the photo and scales it down with an height of 325px
-pub fn create_thumbnail_from_path<P, Q>(photo_path: P, save_to: Q) -> Result<(), OsplError>
-where
-	P: AsRef<Path>,
-	Q: AsRef<Path>,
+/// Rotates and flips a decoded photo the way its EXIF orientation says it should be shown
+pub(crate) fn orient(img: DynamicImage, orientation: Option<u32>) -> DynamicImage
 {
-	let img = image::open(photo_path)?;
-	let new_height: u32 = 325;
-	let new_width: u32 = (img.width() * new_height) / img.height();
-	let img = thumbnail(&img, new_width, new_height);
-	img.save(save_to)?;
-	Ok(())
+	match orientation
+	{
+		Some(2) => img.fliph(),
+		Some(3) => img.rotate180(),
+		Some(4) => img.flipv(),
+		Some(5) => img.rotate90().fliph(),
+		Some(6) => img.rotate90(),
+		Some(7) => img.rotate270().fliph(),
+		Some(8) => img.rotate270(),
+		_ => img,
+	}
+}
+
+/// Scales down an already decoded and oriented photo to fit in the size of the settings, without saving it.
+/// Photos smaller than the settings are kept at their size.
+pub(crate) fn render(img: &DynamicImage, settings: &ThumbnailSettings) -> Result<DynamicImage, OsplError>
+{
+	if img.width() == 0 || img.height() == 0
+	{
+		return Err(OsplError::InternalError(Error::NotAnImage));
+	}
+	let scale = f64::min(settings.max_width as f64 / img.width() as f64, settings.max_height as f64 / img.height() as f64);
+	if scale >= 1.0
+	{
+		return Ok(DynamicImage::ImageRgba8(img.to_rgba8()));
+	}
+	let width = ((img.width() as f64 * scale).round() as u32).max(1);
+	let height = ((img.height() as f64 * scale).round() as u32).max(1);
+	Ok(DynamicImage::ImageRgba8(thumbnail(img, width, height)))
+}
+
+/// Encodes a rendered thumbnail in the format of the settings
+pub(crate) fn encode(img: &DynamicImage, settings: &ThumbnailSettings) -> Result<Vec<u8>, OsplError>
+{
+	let mut buffer = Vec::new();
+	match settings.format
+	{
+		ThumbnailFormat::Jpeg =>
+		{
+			let rgb = img.to_rgb8();
+			JpegEncoder::new_with_quality(&mut buffer, settings.quality)
+				.write_image(&rgb, rgb.width(), rgb.height(), ColorType::Rgb8)?;
+		}
+		ThumbnailFormat::WebP =>
+		{
+			let rgba = img.to_rgba8();
+			WebPEncoder::new_lossless(&mut buffer)
+				.write_image(&rgba, rgba.width(), rgba.height(), ColorType::Rgba8)?;
+		}
+	}
+	Ok(buffer)
+}
+
+/// Identifies what the thumbnails of a photo are rendered from: the original, its orientation and its edits.
+/// The thumbnails are stale when it differs from the `thumbnail_hash` of the photo
+pub(crate) fn source_hash(photo: &Photo) -> String
+{
+	let mut source = format!("{:032x}", photo.hash());
+	if let Some(orientation @ 2..=8) = photo.orientation()
+	{
+		source += &format!("-{}", orientation);
+	}
+	if let Some(edits) = edit::stack_to_setting(&photo.edits())
+	{
+		source += &format!("-{:016x}", xxh3_64(edits.as_bytes()));
+	}
+	source
 }
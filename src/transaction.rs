@@ -0,0 +1,206 @@
+/*	libospl - Open Source Photo Library
+	an opensource and multiplateform photo library management that can be used
+	to store and sort all your photos.
+	Copyright (C) 2019-2022 Angelo Frangione
+
+	This program is free software; you can redistribute it and/or modify
+	it under the terms of the GNU General Public License as published by
+	the Free Software Foundation; either version 2 of the License, or
+	(at your option) any later version.
+
+	This program is distributed in the hope that it will be useful,
+	but WITHOUT ANY WARRANTY; without even the implied warranty of
+	MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+	GNU General Public License for more details.
+
+	You should have received a copy of the GNU General Public License along
+	with this program; if not, write to the Free Software Foundation, Inc.,
+	51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+*/
+use crate::Database;
+use crate::Filesystem;
+use crate::OsplError;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use log::warn;
+
+// A library operation changes the database and the files of the library.
+// The database part runs in a savepoint, and every change made to the files is written
+// in an undo journal: if the operation does not reach commit(), the savepoint is rolled back
+// and the journal is played backwards.
+// Files are never deleted before the commit, they are moved into the journal directory of the
+// transaction so they can be put back, and deleted once the database changes are committed.
+
+static TRANSACTION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A change made to the files of the library, and how to revert it
+#[derive(Debug)]
+enum Undo
+{
+	/// A file or a directory created by the operation, removed on rollback
+	Created(PathBuf),
+	/// A file or a directory moved by the operation, moved back on rollback
+	Moved { from: PathBuf, to: PathBuf },
+}
+
+/// Makes a library operation atomic across the database and the filesystem
+pub(crate) struct LibraryTransaction<'a>
+{
+	db:				&'a Database,
+	journal_path:	PathBuf,
+	undo:			Vec<Undo>,
+	/// Files moved in the journal directory, deleted on commit
+	removed:		usize,
+	committed:		bool,
+}
+
+impl<'a> LibraryTransaction<'a>
+{
+	pub(crate) fn begin(db: &'a Database, fs: &Filesystem) -> Result<Self, OsplError>
+	{
+		db.connection.execute_batch("SAVEPOINT library_transaction")?;
+		let id = TRANSACTION_COUNTER.fetch_add(1, Ordering::Relaxed);
+		Ok(LibraryTransaction
+		{
+			db,
+			journal_path: fs.journal_path().join(format!("{}-{}", std::process::id(), id)),
+			undo: Vec::new(),
+			removed: 0,
+			committed: false,
+		})
+	}
+
+	/// Fails if the failpoint `name` is armed, see [step()]
+	pub(crate) fn step(&self, name: &str) -> Result<(), OsplError>
+	{
+		step(name)
+	}
+
+	/// Records a file or a directory created by the operation
+	pub(crate) fn created<P: AsRef<Path>>(&mut self, path: P)
+	{
+		self.undo.push(Undo::Created(path.as_ref().to_path_buf()));
+	}
+
+	/// Records a file or a directory moved by the operation
+	pub(crate) fn moved<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q)
+	{
+		self.undo.push(Undo::Moved { from: from.as_ref().to_path_buf(), to: to.as_ref().to_path_buf() });
+	}
+
+	/// Removes a file or a directory when the transaction is committed.
+	/// Until then it is kept in the journal directory so it can be put back.
+	pub(crate) fn remove<P: AsRef<Path>>(&mut self, path: P) -> Result<(), OsplError>
+	{
+		std::fs::create_dir_all(&self.journal_path)?;
+		let staged = self.journal_path.join(self.removed.to_string());
+		std::fs::rename(&path, &staged)?;
+		self.removed += 1;
+		self.moved(path, staged);
+		Ok(())
+	}
+
+	/// Commits the database changes, then deletes the removed files
+	pub(crate) fn commit(mut self) -> Result<(), OsplError>
+	{
+		self.db.connection.execute_batch("RELEASE library_transaction")?;
+		self.committed = true;
+		if self.removed > 0
+		{
+			if let Err(e) = std::fs::remove_dir_all(&self.journal_path)
+			{
+				warn!("could not empty the journal {:?}: {}", self.journal_path, e);
+			}
+		}
+		Ok(())
+	}
+
+	fn rollback(&mut self)
+	{
+		if let Err(e) = self.db.connection.execute_batch("ROLLBACK TO library_transaction; RELEASE library_transaction")
+		{
+			warn!("could not roll back the database: {}", e);
+		}
+		while let Some(undo) = self.undo.pop()
+		{
+			let result = match &undo
+			{
+				Undo::Created(path) if path.is_dir() => std::fs::remove_dir_all(path),
+				Undo::Created(path) => std::fs::remove_file(path),
+				Undo::Moved { from, to } => std::fs::rename(to, from),
+			};
+			match result
+			{
+				Err(e) if e.kind() != std::io::ErrorKind::NotFound => warn!("could not undo {:?}: {}", undo, e),
+				_ => {},
+			}
+		}
+		if self.removed > 0
+		{
+			let _ = std::fs::remove_dir(&self.journal_path);
+		}
+	}
+}
+
+impl Drop for LibraryTransaction<'_>
+{
+	fn drop(&mut self)
+	{
+		if !self.committed
+		{
+			self.rollback();
+		}
+	}
+}
+
+/// Fails if the failpoint `name` is armed, see [failpoint]
+#[cfg(any(test, feature = "failpoints"))]
+pub(crate) fn step(name: &str) -> Result<(), OsplError>
+{
+	failpoint::check(name)
+}
+
+/// Does nothing, the failpoints are only built with the `failpoints` feature
+#[cfg(not(any(test, feature = "failpoints")))]
+pub(crate) fn step(_name: &str) -> Result<(), OsplError>
+{
+	Ok(())
+}
+
+/// Failures injected in the steps of the library operations, to test their rollback.
+///
+/// Failpoints are armed for the current thread only.
+/// They are only built with the `failpoints` feature, enabled for the tests.
+#[cfg(any(test, feature = "failpoints"))]
+#[doc(hidden)]
+pub mod failpoint
+{
+	use crate::{Error, OsplError};
+	use std::cell::RefCell;
+
+	thread_local!
+	{
+		static ARMED: RefCell<Option<String>> = const { RefCell::new(None) };
+	}
+
+	/// Makes the step `name` fail until [clear()] is called
+	pub fn set(name: &str)
+	{
+		ARMED.with(|armed| *armed.borrow_mut() = Some(name.to_string()));
+	}
+
+	pub fn clear()
+	{
+		ARMED.with(|armed| *armed.borrow_mut() = None);
+	}
+
+	pub(crate) fn check(name: &str) -> Result<(), OsplError>
+	{
+		match ARMED.with(|armed| armed.borrow().as_deref() == Some(name))
+		{
+			true => Err(OsplError::InternalError(Error::Other)),
+			false => Ok(()),
+		}
+	}
+}
@@ -0,0 +1,151 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::album::AlbumSort;
+	use ospl::api::query::PhotoQuery;
+
+	fn ids(library: &Library, album: u32) -> Vec<u32>
+	{
+		library.list_photos_in_album(album).unwrap().iter().map(|photo| photo.id()).collect()
+	}
+
+	#[test]
+	fn remove_photo_from_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		let smart = library.create_smart_album("stars", "", collection.id(), &PhotoQuery::new().starred(true)).unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		// Assigning it twice does not add it twice
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![photo]);
+		library.set_album_cover(album.id(), Some(photo)).unwrap();
+		let link = path.join("collections").join("2019").join("summer").join(library.get_photo_from_id(photo).unwrap().get_filename());
+		assert!(link.exists());
+
+		library.remove_photo_from_album(photo, album.id()).unwrap();
+		assert!(library.list_photos_in_album(album.id()).unwrap().is_empty());
+		assert!(!link.exists());
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().cover(), None);
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		assert_eq!(library.remove_photo_from_album(photo, album.id()), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		assert_eq!(library.remove_photo_from_album(photo, smart.id()), Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.remove_photo_from_album(42, album.id()), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn manual_order()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(first, album.id()).unwrap();
+		library.assign_photo_to_album(second, album.id()).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![first, second]);
+
+		library.move_photo_before(album.id(), second, first).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![second, first]);
+		library.move_photo_after(album.id(), second, first).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![first, second]);
+		library.move_photo_after(album.id(), first, second).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![second, first]);
+
+		assert_eq!(library.move_photo_before(album.id(), first, first), Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.move_photo_before(album.id(), first, 42), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		library.remove_photo_from_album(second, album.id()).unwrap();
+		assert_eq!(library.move_photo_after(album.id(), first, second), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		// A photo added again goes at the end
+		library.assign_photo_to_album(second, album.id()).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![first, second]);
+
+		// The order is kept when the library is loaded again
+		library.move_photo_before(album.id(), second, first).unwrap();
+		drop(library);
+		let library = Library::load(&path).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![second, first]);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn album_sort()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(second, album.id()).unwrap();
+		library.assign_photo_to_album(first, album.id()).unwrap();
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().sort(), AlbumSort::Manual);
+
+		library.set_album_sort(album.id(), AlbumSort::ImportDate).unwrap();
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().sort(), AlbumSort::ImportDate);
+		assert_eq!(ids(&library, album.id()), vec![first, second]);
+
+		library.set_album_sort(album.id(), AlbumSort::Filename).unwrap();
+		let mut expected = vec![first, second];
+		expected.sort_by_key(|id| library.get_photo_from_id(*id).unwrap().filename().to_lowercase());
+		assert_eq!(ids(&library, album.id()), expected);
+
+		library.set_album_sort(album.id(), AlbumSort::CaptureDate).unwrap();
+		assert_eq!(ids(&library, album.id()).len(), 2);
+
+		// Moving photos only changes the manual order
+		library.set_album_sort(album.id(), AlbumSort::Manual).unwrap();
+		assert_eq!(ids(&library, album.id()), vec![second, first]);
+		let smart = library.create_smart_album("all", "", collection.id(), &PhotoQuery::new()).unwrap();
+		library.set_album_sort(smart.id(), AlbumSort::Filename).unwrap();
+		assert_eq!(ids(&library, smart.id()), expected);
+		assert_eq!(library.move_photo_before(smart.id(), first, second), Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn album_cover()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		assert!(library.get_album_cover(album.id()).unwrap().is_none());
+		library.assign_photo_to_album(first, album.id()).unwrap();
+		library.assign_photo_to_album(second, album.id()).unwrap();
+		// Without a cover the first photo is shown
+		assert_eq!(library.get_album_cover(album.id()).unwrap().unwrap().id(), first);
+
+		library.set_album_cover(album.id(), Some(second)).unwrap();
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().cover(), Some(second));
+		assert_eq!(library.get_album_cover(album.id()).unwrap().unwrap().id(), second);
+		library.delete_photo_by_id(second).unwrap();
+		assert_eq!(library.get_album_cover(album.id()).unwrap().unwrap().id(), first);
+		library.restore_from_trash(second).unwrap();
+		assert_eq!(library.get_album_cover(album.id()).unwrap().unwrap().id(), second);
+		// The cover goes away with the photo
+		library.delete_photo_by_id(second).unwrap();
+		library.empty_trash().unwrap();
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().cover(), None);
+
+		let other = library.create_album("winter", "", collection.id()).unwrap();
+		assert_eq!(library.set_album_cover(other.id(), Some(first)), Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.set_album_cover(other.id(), Some(42)), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		library.set_album_cover(album.id(), None).unwrap();
+		assert_eq!(library.get_album_from_id(album.id()).unwrap().cover(), None);
+		super::remove_test_path(path);
+	}
+}
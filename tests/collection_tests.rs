@@ -6,6 +6,7 @@ use test_tools::remove_test_path;
 mod tests
 {
 	use ospl::Library;
+	use ospl::Error;
 	use ospl::OsplError;
 
 	#[test]
@@ -90,4 +91,202 @@ mod tests
 		assert_eq!(library.get_collection_from_id(1).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
 		super::remove_test_path(path);
 	}
+
+	#[test]
+	fn remove_collection_keeps_others()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.create_collection("first", "").unwrap();
+		library.create_collection("second", "").unwrap();
+		library.delete_collection_by_id(first.id()).unwrap();
+		assert!(!library.get_path().join("collections").join("first").exists());
+		assert!(library.get_path().join("collections").join("second").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn create_sub_collection()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "Trips to Japan", travels.id()).unwrap();
+		let tokyo = library.create_sub_collection("Tokyo", "", japan.id()).unwrap();
+		assert_eq!(Some(travels.id()), japan.parent_id());
+		assert_eq!(Some(japan.id()), library.get_collection_from_id(tokyo.id()).unwrap().parent_id());
+		assert_eq!(None, library.get_collection_from_id(travels.id()).unwrap().parent_id());
+		assert!(library.get_path().join("collections").join("Travels").join("Japan").join("Tokyo").exists());
+		let album = library.create_album("Shibuya", "", tokyo.id()).unwrap();
+		assert!(library.get_path().join("collections/Travels/Japan/Tokyo").join(album.name()).exists());
+		assert_eq!(library.create_sub_collection("Kyoto", "", 42).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn create_sub_collection_same_name()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let family = library.create_collection("Family", "").unwrap();
+		let first = library.create_sub_collection("2023", "", travels.id()).unwrap();
+		let second = library.create_sub_collection("2023", "", family.id()).unwrap();
+		assert_ne!(first.id(), second.id());
+		assert!(library.get_path().join("collections/Travels/2023").exists());
+		assert!(library.get_path().join("collections/Family/2023").exists());
+		// Two collections with the same parent, or at the root, cannot have the same name
+		let exists = OsplError::IoError(std::io::ErrorKind::AlreadyExists);
+		assert_eq!(library.create_sub_collection("2023", "", travels.id()).err().unwrap(), exists);
+		assert_eq!(library.create_collection("Travels", "").err().unwrap(), exists);
+		assert_eq!(4, library.list_all_collections().unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn create_collection_empty_name()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		assert_eq!(library.create_collection("", "").err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		assert_eq!(library.create_sub_collection("", "", travels.id()).err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		assert_eq!(1, library.list_all_collections().unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn collection_invalid_name()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let invalid = OsplError::InternalError(Error::InvalidName);
+		for name in ["..", ".", "../outside", "Japan/Tokyo", "Japan\\Tokyo"]
+		{
+			assert_eq!(library.create_collection(name, "").err().unwrap(), invalid);
+			assert_eq!(library.create_sub_collection(name, "", travels.id()).err().unwrap(), invalid);
+			assert_eq!(library.rename_collection_with_id(travels.id(), name).err().unwrap(), invalid);
+		}
+		assert_eq!(library.rename_collection_with_id(travels.id(), "").err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		assert_eq!(1, library.list_all_collections().unwrap().len());
+		assert!(library.get_path().join("collections").join("Travels").exists());
+		assert!(!library.get_path().join("outside").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn rename_and_move_collection_conflict()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let exists = OsplError::IoError(std::io::ErrorKind::AlreadyExists);
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_collection("Japan", "").unwrap();
+		let nested = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		let other = library.create_sub_collection("Italy", "", travels.id()).unwrap();
+		assert_eq!(library.rename_collection_with_id(japan.id(), "Travels").err().unwrap(), exists);
+		assert_eq!(library.rename_collection_with_id(other.id(), "Japan").err().unwrap(), exists);
+		assert_eq!(library.move_collection_by_id(japan.id(), Some(travels.id())).err().unwrap(), exists);
+		assert_eq!(library.move_collection_by_id(nested.id(), None).err().unwrap(), exists);
+		// Renaming a collection to its own name is not a conflict
+		library.rename_collection_with_id(japan.id(), "Japan").unwrap();
+		assert_eq!("Japan", library.get_collection_from_id(japan.id()).unwrap().name());
+		assert_eq!("Italy", library.get_collection_from_id(other.id()).unwrap().name());
+		assert_eq!(None, library.get_collection_from_id(japan.id()).unwrap().parent_id());
+		assert!(library.get_path().join("collections").join("Japan").exists());
+		assert!(library.get_path().join("collections").join("Travels").join("Italy").exists());
+		assert!(library.get_path().join("collections").join("Travels").join("Japan").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn rename_sub_collection()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		library.rename_collection_with_id(japan.id(), "Nippon").unwrap();
+		assert!(library.get_path().join("collections").join("Travels").join("Nippon").exists());
+		assert!(!library.get_path().join("collections").join("Travels").join("Japan").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn move_collection()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_collection("Japan", "").unwrap();
+		let album = library.create_album("Tokyo", "", japan.id()).unwrap();
+		library.move_collection_by_id(japan.id(), Some(travels.id())).unwrap();
+		assert_eq!(Some(travels.id()), library.get_collection_from_id(japan.id()).unwrap().parent_id());
+		assert!(library.get_path().join("collections").join("Travels").join("Japan").join("Tokyo").exists());
+		assert!(!library.get_path().join("collections").join("Japan").exists());
+		assert_eq!(album.id(), library.list_albums_in_collection(japan.id()).unwrap()[0].id());
+
+		library.move_collection_by_id(japan.id(), None).unwrap();
+		assert_eq!(None, library.get_collection_from_id(japan.id()).unwrap().parent_id());
+		assert!(library.get_path().join("collections").join("Japan").join("Tokyo").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn move_collection_inside_itself()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		let tokyo = library.create_sub_collection("Tokyo", "", japan.id()).unwrap();
+		assert_eq!(library.move_collection_by_id(travels.id(), Some(tokyo.id())).err().unwrap(), OsplError::InternalError(Error::MovedInsideItself));
+		assert_eq!(library.move_collection_by_id(travels.id(), Some(travels.id())).err().unwrap(), OsplError::InternalError(Error::MovedInsideItself));
+		assert!(library.get_path().join("collections").join("Travels").join("Japan").join("Tokyo").exists());
+		assert_eq!(None, library.get_collection_from_id(travels.id()).unwrap().parent_id());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn collection_tree()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		library.create_sub_collection("Italy", "", travels.id()).unwrap();
+		library.create_collection("Family", "").unwrap();
+		library.create_album("Tokyo", "", japan.id()).unwrap();
+		library.create_album("Kyoto", "", japan.id()).unwrap();
+
+		let tree = library.get_collection_tree(travels.id()).unwrap();
+		assert_eq!("Travels", tree.collection.name());
+		assert!(tree.albums.is_empty());
+		assert_eq!(2, tree.children.len());
+		assert_eq!("Japan", tree.children[0].collection.name());
+		assert_eq!(2, tree.children[0].albums.len());
+		assert_eq!("Tokyo", tree.children[0].albums[0].name());
+		assert!(tree.children[1].children.is_empty());
+
+		let trees = library.list_collection_trees().unwrap();
+		assert_eq!(2, trees.len());
+		assert_eq!("Travels", trees[0].collection.name());
+		assert_eq!("Family", trees[1].collection.name());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn remove_parent_collection()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		library.delete_collection_by_id(travels.id()).unwrap();
+		assert!(!library.get_path().join("collections").join("Travels").exists());
+		assert_eq!(library.get_collection_from_id(japan.id()).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert!(library.list_all_collections().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
 }
@@ -0,0 +1,157 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError, Error};
+	use ospl::api::library::DeletePolicy;
+	use ospl::api::query::PhotoQuery;
+	use rusqlite::Connection;
+	use std::path::Path;
+
+	/// Number of rows of a link table pointing to the photo
+	fn links(path: &Path, table: &str, photo: u32) -> u32
+	{
+		Connection::open(path.join("database.db")).unwrap()
+			.query_row(&format!("SELECT COUNT(*) FROM {} WHERE contained_photo = ?1", table), [photo], |row| row.get(0))
+			.unwrap()
+	}
+
+	#[test]
+	fn delete_photo_for_good()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		library.tag_photos(&[photo], tag.id()).unwrap();
+
+		library.delete_photo_by_id(photo).unwrap();
+		assert_eq!(links(&path, "photos_albums_map", photo), 1);
+		assert_eq!(links(&path, "photos_tags_map", photo), 1);
+		library.empty_trash().unwrap();
+		assert_eq!(links(&path, "photos_albums_map", photo), 0);
+		assert_eq!(links(&path, "photos_tags_map", photo), 0);
+		assert_eq!(0, std::fs::read_dir(path.join("collections").join("2019").join("summer")).unwrap().count());
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_album_policies()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let summer = library.create_album("summer", "", collection.id()).unwrap();
+		let holidays = library.create_album("holidays", "", collection.id()).unwrap();
+		let smart = library.create_smart_album("stars", "", collection.id(), &PhotoQuery::new().starred(true)).unwrap();
+		library.assign_photo_to_album(photo, summer.id()).unwrap();
+
+		assert_eq!(library.delete_album_with_policy(summer.id(), DeletePolicy::Refuse), Err(OsplError::InternalError(Error::NotEmpty)));
+		assert_eq!(library.delete_album_with_policy(summer.id(), DeletePolicy::Reassign(summer.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.delete_album_with_policy(summer.id(), DeletePolicy::Reassign(smart.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.delete_album_with_policy(summer.id(), DeletePolicy::Reassign(42)),
+			Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		assert_eq!(library.list_photos_in_album(summer.id()).unwrap().len(), 1);
+
+		library.delete_album_with_policy(summer.id(), DeletePolicy::Reassign(holidays.id())).unwrap();
+		assert!(library.get_album_from_id(summer.id()).is_err());
+		assert_eq!(library.list_photos_in_album(holidays.id()).unwrap()[0].id(), photo);
+		assert!(!path.join("collections").join("2019").join("summer").exists());
+		assert_eq!(1, std::fs::read_dir(path.join("collections").join("2019").join("holidays")).unwrap().count());
+
+		library.delete_album_with_policy(smart.id(), DeletePolicy::Refuse).unwrap();
+		library.delete_album_by_id(holidays.id()).unwrap();
+		assert_eq!(links(&path, "photos_albums_map", photo), 0);
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_collection_policies()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let travels = library.create_collection("Travels", "").unwrap();
+		let japan = library.create_sub_collection("Japan", "", travels.id()).unwrap();
+		let tokyo = library.create_album("Tokyo", "", japan.id()).unwrap();
+		let summer = library.create_album("summer", "", travels.id()).unwrap();
+		let archives = library.create_collection("Archives", "").unwrap();
+		library.assign_photo_to_album(photo, tokyo.id()).unwrap();
+		library.assign_photo_to_album(photo, summer.id()).unwrap();
+
+		assert_eq!(library.delete_collection_with_policy(travels.id(), DeletePolicy::Refuse),
+			Err(OsplError::InternalError(Error::NotEmpty)));
+		assert_eq!(library.delete_collection_with_policy(travels.id(), DeletePolicy::Reassign(japan.id())),
+			Err(OsplError::InternalError(Error::MovedInsideItself)));
+		assert_eq!(library.delete_collection_with_policy(travels.id(), DeletePolicy::Reassign(travels.id())),
+			Err(OsplError::InternalError(Error::MovedInsideItself)));
+		let clash = library.create_sub_collection("Japan", "", archives.id()).unwrap();
+		assert_eq!(library.delete_collection_with_policy(travels.id(), DeletePolicy::Reassign(archives.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists)));
+		assert!(path.join("collections").join("Travels").join("Japan").join("Tokyo").is_dir());
+		library.delete_collection_by_id(clash.id()).unwrap();
+
+		library.delete_collection_with_policy(travels.id(), DeletePolicy::Reassign(archives.id())).unwrap();
+		assert!(library.get_collection_from_id(travels.id()).is_err());
+		assert_eq!(library.get_collection_from_id(japan.id()).unwrap().parent_id(), Some(archives.id()));
+		assert_eq!(library.get_album_from_id(summer.id()).unwrap().collection_id(), archives.id());
+		assert!(path.join("collections").join("Archives").join("Japan").join("Tokyo").is_dir());
+		assert!(path.join("collections").join("Archives").join("summer").is_dir());
+		assert!(!path.join("collections").join("Travels").exists());
+		assert_eq!(library.list_photos_in_album(tokyo.id()).unwrap().len(), 1);
+		assert!(library.check_integrity().unwrap().is_empty());
+
+		library.delete_collection_by_id(archives.id()).unwrap();
+		assert!(library.list_all_collections().unwrap().is_empty());
+		assert!(library.get_album_from_id(tokyo.id()).is_err());
+		assert!(library.get_album_from_id(summer.id()).is_err());
+		assert_eq!(links(&path, "photos_albums_map", photo), 0);
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		assert!(library.check_integrity().unwrap().is_empty());
+
+		let empty = library.create_collection("Empty", "").unwrap();
+		library.delete_collection_with_policy(empty.id(), DeletePolicy::Refuse).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_tag_policies()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let places = library.create_tag("Places").unwrap();
+		let paris = library.create_tag("Places/Paris").unwrap();
+		let cities = library.create_tag("Cities").unwrap();
+		library.tag_photos(&[photo], places.id()).unwrap();
+
+		assert_eq!(library.delete_tag_with_policy(places.id(), DeletePolicy::Refuse), Err(OsplError::InternalError(Error::NotEmpty)));
+		assert_eq!(library.delete_tag_with_policy(places.id(), DeletePolicy::Reassign(places.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.delete_tag_with_policy(places.id(), DeletePolicy::Reassign(paris.id())),
+			Err(OsplError::InternalError(Error::MovedInsideItself)));
+
+		library.delete_tag_with_policy(places.id(), DeletePolicy::Reassign(cities.id())).unwrap();
+		assert!(library.get_tag_from_id(places.id()).is_err());
+		assert_eq!(library.get_tag_from_id(paris.id()).unwrap().path(), "Cities/Paris");
+		assert_eq!(library.list_tags_of_photo(photo).unwrap()[0].id(), cities.id());
+
+		library.delete_tag_with_policy(paris.id(), DeletePolicy::Refuse).unwrap();
+		library.delete_tag_by_id(cities.id()).unwrap();
+		assert!(library.list_all_tags().unwrap().is_empty());
+		assert_eq!(links(&path, "photos_tags_map", photo), 0);
+		super::remove_test_path(path);
+	}
+}
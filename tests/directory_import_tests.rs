@@ -0,0 +1,169 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::Error;
+	use ospl::OsplError;
+	use ospl::api::import::{DuplicatePolicy, ImportDestination, ImportOptions, ImportProgress};
+	use std::path::{Path, PathBuf};
+
+	/// Creates a folder like a memory card dump:
+	/// a photo, a sub folder with a png, a text file and a broken png
+	fn create_source_folder(path: &Path) -> PathBuf
+	{
+		let source = path.with_extension("source");
+		std::fs::create_dir_all(source.join("sub")).unwrap();
+		std::fs::copy("tests/files/test_photo_light.jpg", source.join("a_photo.jpg")).unwrap();
+		image::RgbImage::new(40, 30).save(source.join("sub").join("b_image.png")).unwrap();
+		std::fs::write(source.join("c_notes.txt"), "not a photo").unwrap();
+		let mut broken = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+		broken.extend_from_slice(&[0; 64]);
+		std::fs::write(source.join("d_broken.png"), broken).unwrap();
+		source
+	}
+
+	#[test]
+	fn import_directory()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = create_source_folder(&path);
+
+		let mut updates: Vec<ImportProgress> = Vec::new();
+		let report = library.import_directory(&source, &ImportOptions::default(), |progress| updates.push(*progress)).unwrap();
+		assert_eq!(2, report.imported.len());
+		assert_eq!(vec![source.join("c_notes.txt")], report.skipped);
+		assert_eq!(1, report.failed.len());
+		assert_eq!(source.join("d_broken.png"), report.failed[0].0);
+		assert_eq!(OsplError::InternalError(Error::NotAnImage), report.failed[0].1);
+		assert_eq!(2, library.list_all_photos().unwrap().len());
+		assert_eq!(2, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		assert_eq!("a_photo.jpg", library.get_photo_from_id(report.imported[0]).unwrap().filename());
+		assert_eq!("b_image.png", library.get_photo_from_id(report.imported[1]).unwrap().filename());
+
+		assert_eq!(4, updates.len());
+		let last = updates.last().unwrap();
+		assert_eq!(ImportProgress { total: 4, seen: 4, imported: 2, skipped: 1, failed: 1 }, *last);
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_not_recursive()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = create_source_folder(&path);
+		let options = ImportOptions { recursive: false, ..Default::default() };
+		let report = library.import_directory(&source, &options, |_| {}).unwrap();
+		assert_eq!(1, report.imported.len());
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_twice()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = create_source_folder(&path);
+		let options = ImportOptions { batch_size: 1, ..Default::default() };
+		library.import_directory(&source, &options, |_| {}).unwrap();
+		let report = library.import_directory(&source, &options, |_| {}).unwrap();
+		assert!(report.imported.is_empty());
+		assert_eq!(3, report.skipped.len());
+		assert_eq!(2, library.list_all_photos().unwrap().len());
+
+		let options = ImportOptions { duplicates: DuplicatePolicy::Refuse, ..Default::default() };
+		let report = library.import_directory(&source, &options, |_| {}).unwrap();
+		assert_eq!(3, report.failed.len());
+		assert_eq!(2, report.failed.iter().filter(|(_, e)| matches!(e, OsplError::DuplicatePhoto(_))).count());
+		assert_eq!(2, library.list_all_photos().unwrap().len());
+
+		// The copies of the duplicates do not stay in the library
+		let copies = path.with_extension("copies");
+		std::fs::create_dir_all(&copies).unwrap();
+		std::fs::copy("tests/files/test_photo_light.jpg", copies.join("again.jpg")).unwrap();
+		let report = library.import_directory(&copies, &ImportOptions::default(), |_| {}).unwrap();
+		assert_eq!(1, report.skipped.len());
+		assert_eq!(2, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		std::fs::remove_dir_all(copies).unwrap();
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_not_found()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let report = library.import_directory(path.join("nothing"), &ImportOptions::default(), |_| {});
+		assert_eq!(report.err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_into_new_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = create_source_folder(&path);
+		let collection = library.create_collection("cards", "").unwrap();
+		let options = ImportOptions { destination: ImportDestination::NewAlbum(collection.id()), ..Default::default() };
+		let report = library.import_directory(&source, &options, |_| {}).unwrap();
+		let albums = library.list_albums_in_collection(collection.id()).unwrap();
+		assert_eq!(1, albums.len());
+		assert_eq!(source.file_name().unwrap().to_str().unwrap(), albums[0].name());
+		let in_album: Vec<u32> = library.list_photos_in_album(albums[0].id()).unwrap().iter().map(|p| p.id()).collect();
+		assert_eq!(report.imported, in_album);
+		let album_path = path.join("collections").join("cards").join(albums[0].name());
+		assert_eq!(2, std::fs::read_dir(&album_path).unwrap().count());
+
+		let report = library.import_directory(&source, &options, |_| {}).unwrap();
+		assert!(report.imported.is_empty());
+		assert_eq!(1, library.list_albums_in_collection(collection.id()).unwrap().len());
+		assert_eq!(2, library.list_photos_in_album(albums[0].id()).unwrap().len());
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	/// Imports the same folder of sample photos with one worker, then with several workers:
+	/// the photos, their ids and their order must be the same.
+	#[test]
+	fn import_directory_workers_same_results()
+	{
+		let path = super::generate_test_path();
+		let source = path.with_extension("source");
+		std::fs::create_dir_all(&source).unwrap();
+		for i in 0..8
+		{
+			std::fs::copy("tests/files/test_photo_light.jpg", source.join(format!("photo_{}.jpg", i))).unwrap();
+		}
+
+		let mut results = Vec::new();
+		for (i, workers) in [1, 4].iter().enumerate()
+		{
+			let library_path = path.with_extension(format!("library_{}", i));
+			let library = Library::create(&library_path).unwrap();
+			let options = ImportOptions { duplicates: DuplicatePolicy::ImportAnyway, workers: *workers, ..Default::default() };
+			let report = library.import_directory(&source, &options, |_| {}).unwrap();
+			assert_eq!(8, report.imported.len());
+			let photos: Vec<(u32, String, u128, Option<u64>)> = library.list_all_photos().unwrap().iter()
+				.map(|photo| (photo.id(), photo.filename(), photo.hash(), photo.perceptual_hash()))
+				.collect();
+			assert_eq!(report.imported, photos.iter().map(|photo| photo.0).collect::<Vec<u32>>());
+			assert_eq!(8, std::fs::read_dir(library_path.join("thumbnails").join("grid")).unwrap().count());
+			results.push(photos);
+		}
+		assert_eq!(results[0], results[1]);
+		assert_eq!("photo_0.jpg", results[1][0].1);
+		assert_eq!("photo_7.jpg", results[1][7].1);
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path.with_extension("library_0"));
+		super::remove_test_path(path.with_extension("library_1"));
+	}
+}
@@ -0,0 +1,125 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::edit::Edit;
+	use ospl::api::thumbnail::ThumbnailPreset;
+
+	static PHOTO: &str = "tests/files/test_photo_light.jpg";
+
+	#[test]
+	fn edit_stack()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		assert!(library.list_edits_of_photo(id).unwrap().is_empty());
+
+		library.add_edit(id, Edit::Rotate(1)).unwrap();
+		library.add_edit(id, Edit::WhiteBalance { temperature: 0.25, tint: -0.1 }).unwrap();
+		library.add_edit(id, Edit::Exposure(0.5)).unwrap();
+		let stack = vec![Edit::Rotate(1), Edit::WhiteBalance { temperature: 0.25, tint: -0.1 }, Edit::Exposure(0.5)];
+		assert_eq!(stack, library.list_edits_of_photo(id).unwrap());
+
+		// Edits are saved in the library
+		drop(library);
+		let library = Library::load(&path).unwrap();
+		assert_eq!(stack, library.get_photo_from_id(id).unwrap().edits());
+
+		library.undo_edit(id).unwrap();
+		assert_eq!(stack[..2], library.list_edits_of_photo(id).unwrap());
+		library.revert_to_original(id).unwrap();
+		assert!(library.list_edits_of_photo(id).unwrap().is_empty());
+		library.undo_edit(id).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn render_photo()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let original = std::fs::read(path.join("pictures").read_dir().unwrap().next().unwrap().unwrap().path()).unwrap();
+		let (width, height) = image::image_dimensions(PHOTO).unwrap();
+
+		let img = library.render_photo(id, u32::MAX).unwrap();
+		assert_eq!((width, height), (img.width(), img.height()));
+
+		library.set_edits(id, &[Edit::Crop { x: 10, y: 20, width: 300, height: 200 }, Edit::Rotate(3), Edit::Saturation(-1.0)]).unwrap();
+		let img = library.render_photo(id, u32::MAX).unwrap().to_rgb8();
+		assert_eq!((200, 300), img.dimensions());
+		assert!(img.pixels().all(|pixel| pixel[0].abs_diff(pixel[1]) <= 1 && pixel[1].abs_diff(pixel[2]) <= 1));
+		let img = library.render_photo(id, 150).unwrap();
+		assert_eq!((100, 150), (img.width(), img.height()));
+
+		library.set_edits(id, &[Edit::Brightness(1.0)]).unwrap();
+		let img = library.render_photo(id, 64).unwrap().to_rgb8();
+		assert!(img.pixels().all(|pixel| pixel.0 == [255, 255, 255]));
+
+		// The original is never modified
+		let copy = path.join("pictures").read_dir().unwrap().next().unwrap().unwrap().path();
+		assert_eq!(original, std::fs::read(copy).unwrap());
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)), library.render_photo(id, 0).map(|_| ()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn thumbnails_of_edited_photo()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let grid = library.get_thumbnail(id, ThumbnailPreset::Grid).unwrap();
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		let (width, height) = image::image_dimensions(&grid).unwrap();
+
+		library.add_edit(id, Edit::Crop { x: 0, y: 0, width: 325, height: 650 }).unwrap();
+		assert_eq!((163, 325), image::image_dimensions(&grid).unwrap());
+		assert!(!preview.exists());
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		assert_eq!((325, 650), image::image_dimensions(&preview).unwrap());
+		assert!(library.check_integrity().unwrap().is_empty());
+
+		library.revert_to_original(id).unwrap();
+		assert_eq!((width, height), image::image_dimensions(&grid).unwrap());
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn invalid_edits()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		let (width, height) = (photo.display_width().unwrap(), photo.display_height().unwrap());
+
+		let invalid = Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		assert_eq!(invalid, library.add_edit(id, Edit::Rotate(4)));
+		assert_eq!(invalid, library.add_edit(id, Edit::Contrast(1.5)));
+		assert_eq!(invalid, library.add_edit(id, Edit::Crop { x: 0, y: 0, width: 0, height: 10 }));
+		assert_eq!(invalid, library.add_edit(id, Edit::Crop { x: 1, y: 0, width, height }));
+		// The crop fits once the photo is turned
+		assert_eq!(invalid, library.set_edits(id, &[Edit::Crop { x: 0, y: 0, width: height, height: width }]));
+		library.set_edits(id, &[Edit::Rotate(1), Edit::Crop { x: 0, y: 0, width: height, height: width }]).unwrap();
+		assert_eq!(2, library.list_edits_of_photo(id).unwrap().len());
+		// The photo is shown turned
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!((Some(height), Some(width)), (photo.display_width(), photo.display_height()));
+		library.set_edits(id, &[Edit::Crop { x: 1, y: 2, width: 10, height: 20 }]).unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!((Some(10), Some(20)), (photo.display_width(), photo.display_height()));
+		library.revert_to_original(id).unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!((Some(width), Some(height)), (photo.display_width(), photo.display_height()));
+
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::NotFound)), library.add_edit(id + 1, Edit::FlipVertical));
+		super::remove_test_path(path);
+	}
+}
@@ -8,6 +8,7 @@ mod tests
 	use ospl::Library;
 	use ospl::OsplError;
 	use ospl::Error;
+	use ospl::api::import::{DuplicatePolicy, ImportDestination};
 
 	#[cfg(target_os = "linux")]
 	use std::fs;
@@ -28,7 +29,7 @@ mod tests
 		let filename = p.get_filename();
 		assert_eq!("test_photo_light.jpg", p.filename());
 		let photo_path = path.join("pictures").join(&filename);
-		let thumb_path = path.join("thumbnails").join(&filename);
+		let thumb_path = path.join("thumbnails").join("grid").join(&filename);
 		println!("FULL_PATH: {:?}", photo_path);
 		assert!(std::path::Path::new(&photo_path).exists());
 		assert!(std::path::Path::new(&thumb_path).exists());
@@ -91,4 +92,155 @@ mod tests
 		}
 		super::remove_test_path(path);
 	}
+
+	#[test]
+	fn import_duplicate_skip()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let again = library.import_photo_with_policy("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip).unwrap();
+		assert_eq!(id, again);
+		assert_eq!(1, library.list_all_photos().unwrap().len());
+		// The copy of the duplicate does not stay in the library
+		assert_eq!(1, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_duplicate_refuse()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		assert_eq!(library.import_photo_with_policy("tests/files/test_photo_light.jpg", DuplicatePolicy::Refuse).err().unwrap(), OsplError::DuplicatePhoto(id));
+		assert_eq!(1, library.list_all_photos().unwrap().len());
+		assert_eq!(1, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_duplicate_anyway()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo_with_policy("tests/files/test_photo_light.jpg", DuplicatePolicy::ImportAnyway).unwrap();
+		assert_ne!(id_0, id_1);
+		assert_eq!(2, library.list_all_photos().unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn find_exact_duplicates()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let png_path = path.with_extension("png");
+		image::RgbImage::new(40, 30).save(&png_path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo(&png_path).unwrap();
+		let id_2 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		assert_eq!(1, library.find_exact_duplicates().unwrap().len());
+		let id_3 = library.import_photo(&png_path).unwrap();
+
+		let groups = library.find_exact_duplicates().unwrap();
+		assert_eq!(2, groups.len());
+		assert_eq!(vec![id_0, id_2], groups[0].iter().map(|p| p.id()).collect::<Vec<u32>>());
+		assert_eq!(vec![id_1, id_3], groups[1].iter().map(|p| p.id()).collect::<Vec<u32>>());
+		std::fs::remove_file(png_path).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_photo_into_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		let id = library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::Album(album.id())).unwrap();
+		let photos = library.list_photos_in_album(album.id()).unwrap();
+		assert_eq!(vec![id], photos.iter().map(|p| p.id()).collect::<Vec<u32>>());
+		let link = path.join("collections").join("2019").join("summer").join(photos[0].get_filename());
+		assert!(link.exists());
+
+		let again = library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::Album(album.id())).unwrap();
+		assert_eq!(id, again);
+		assert_eq!(1, library.list_photos_in_album(album.id()).unwrap().len());
+		assert_eq!(1, std::fs::read_dir(path.join("collections").join("2019").join("summer")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_photo_into_new_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let id = library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::NewAlbum(collection.id())).unwrap();
+		let albums = library.list_albums_in_collection(collection.id()).unwrap();
+		assert_eq!(1, albums.len());
+		assert_eq!("files", albums[0].name());
+		assert_eq!(id, library.list_photos_in_album(albums[0].id()).unwrap()[0].id());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_photo_into_new_album_name_taken()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.create_collection("2019", "").unwrap();
+		let second = library.create_collection("2020", "").unwrap();
+		let taken = library.create_album("files", "", first.id()).unwrap();
+		assert_eq!(library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::NewAlbum(second.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::AlreadyExists)));
+		assert!(library.list_all_photos().unwrap().is_empty());
+		assert!(library.list_albums_in_collection(second.id()).unwrap().is_empty());
+		// The album of the collection is used
+		let id = library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::NewAlbum(first.id())).unwrap();
+		assert_eq!(id, library.list_photos_in_album(taken.id()).unwrap()[0].id());
+		assert_eq!(1, library.list_albums_in_collection(first.id()).unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_photo_into_smart_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let smart = library.create_smart_album("files", "", collection.id(), &ospl::api::query::PhotoQuery::new()).unwrap();
+		assert_eq!(library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::Album(smart.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		// The album named after the folder is the smart album
+		assert_eq!(library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::NewAlbum(collection.id())),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert!(library.list_all_photos().unwrap().is_empty());
+		assert_eq!(0, fs::read_dir(path.join("collections").join("2019").join("files")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_photo_into_album_failure()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		let broken = path.with_extension("png");
+		let mut content = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+		content.extend_from_slice(&[0; 64]);
+		std::fs::write(&broken, content).unwrap();
+		assert!(library.import_photo_into(&broken, DuplicatePolicy::Skip, ImportDestination::Album(album.id())).is_err());
+		assert_eq!(OsplError::IoError(std::io::ErrorKind::NotFound), library.import_photo_into("tests/files/test_photo_light.jpg",
+			DuplicatePolicy::Skip, ImportDestination::Album(album.id() + 1)).err().unwrap());
+		assert!(library.list_all_photos().unwrap().is_empty());
+		assert!(library.list_photos_in_album(album.id()).unwrap().is_empty());
+		assert_eq!(0, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		assert_eq!(0, std::fs::read_dir(path.join("collections").join("2019").join("summer")).unwrap().count());
+		std::fs::remove_file(broken).unwrap();
+		super::remove_test_path(path);
+	}
 }
@@ -0,0 +1,162 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::api::integrity::IntegrityIssue;
+	use ospl::api::query::PhotoQuery;
+	use std::path::{Path, PathBuf};
+
+	/// Writes the same content to the file again, so it is modified after the files written before
+	fn touch(file: &Path)
+	{
+		std::thread::sleep(std::time::Duration::from_millis(1100));
+		std::fs::write(file, std::fs::read(file).unwrap()).unwrap();
+	}
+
+	#[test]
+	fn check_integrity_clean_library()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		library.tag_photos(&[photo], tag.id()).unwrap();
+		assert_eq!(Vec::<IntegrityIssue>::new(), library.check_integrity().unwrap());
+		assert_eq!(Vec::<IntegrityIssue>::new(), library.repair().unwrap());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn check_integrity_and_repair()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let summer = library.create_album("summer", "", collection.id()).unwrap();
+		let winter = library.create_album("winter", "", collection.id()).unwrap();
+		let first = library.get_photo_from_id(library.import_photo("tests/files/test_photo_light.jpg").unwrap()).unwrap();
+		let second = library.get_photo_from_id(library.import_photo("tests/files/test_photo.jpg").unwrap()).unwrap();
+		library.assign_photo_to_album(first.id(), summer.id()).unwrap();
+		library.assign_photo_to_album(second.id(), summer.id()).unwrap();
+		library.assign_photo_to_album(first.id(), winter.id()).unwrap();
+
+		// Thumbnail of the first photo removed, original of the second one written again after its thumbnail
+		std::fs::remove_file(path.join("thumbnails").join("grid").join(first.get_filename())).unwrap();
+		touch(&path.join("pictures").join(second.get_filename()));
+		// Hard link of the first photo removed from summer, winter directory removed
+		let summer_path = path.join("collections").join("2019").join("summer");
+		std::fs::remove_file(summer_path.join(first.get_filename())).unwrap();
+		std::fs::remove_dir_all(path.join("collections").join("2019").join("winter")).unwrap();
+		// Files and directories the database does not know
+		std::fs::write(path.join("pictures").join("orphan.jpg"), "orphan").unwrap();
+		std::fs::write(path.join("thumbnails").join("orphan.jpg"), "orphan").unwrap();
+		std::fs::create_dir(path.join("collections").join("unknown")).unwrap();
+		// Links to photos that do not exist
+		let connection = rusqlite::Connection::open(path.join("database.db")).unwrap();
+		connection.execute_batch("PRAGMA foreign_keys = OFF; \
+			INSERT INTO photos_albums_map (containing_album, contained_photo) VALUES (1, 99); \
+			INSERT INTO photos_tags_map (containing_tag, contained_photo) VALUES (7, 1);").unwrap();
+		drop(connection);
+
+		let issues = library.check_integrity().unwrap();
+		let expected = vec![
+			IntegrityIssue::MissingThumbnail(first.id()),
+			IntegrityIssue::StaleThumbnail(second.id()),
+			IntegrityIssue::OrphanFile(PathBuf::from("pictures/orphan.jpg")),
+			IntegrityIssue::OrphanFile(PathBuf::from("thumbnails/orphan.jpg")),
+			IntegrityIssue::DanglingAlbumLink { album: 1, photo: 99 },
+			IntegrityIssue::DanglingTagLink { tag: 7, photo: 1 },
+			IntegrityIssue::MissingAlbumLink { album: summer.id(), photo: first.id() },
+			IntegrityIssue::MissingAlbumDirectory(winter.id()),
+			IntegrityIssue::UnknownDirectory(PathBuf::from("collections/unknown")),
+		];
+		for issue in &expected
+		{
+			assert!(issues.contains(issue), "{:?} not found in {:?}", issue, issues);
+		}
+		assert_eq!(expected.len(), issues.len());
+
+		let remaining = library.repair().unwrap();
+		assert_eq!(vec![
+			IntegrityIssue::OrphanFile(PathBuf::from("pictures/orphan.jpg")),
+			IntegrityIssue::UnknownDirectory(PathBuf::from("collections/unknown")),
+		], remaining);
+		assert!(path.join("thumbnails").join("grid").join(first.get_filename()).exists());
+		assert!(summer_path.join(first.get_filename()).exists());
+		assert!(path.join("collections").join("2019").join("winter").join(first.get_filename()).exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn check_integrity_smart_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let query = PhotoQuery::parse("").unwrap();
+		let album = library.create_smart_album("everything", "", collection.id(), &query).unwrap();
+		library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		assert_eq!(1, library.list_photos_in_album(album.id()).unwrap().len());
+		// The photos of a smart album have no hard link
+		assert_eq!(Vec::<IntegrityIssue>::new(), library.check_integrity().unwrap());
+
+		let album_path = path.join("collections").join("2019").join("everything");
+		std::fs::remove_dir(&album_path).unwrap();
+		assert_eq!(vec![IntegrityIssue::MissingAlbumDirectory(album.id())], library.check_integrity().unwrap());
+		assert_eq!(Vec::<IntegrityIssue>::new(), library.repair().unwrap());
+		assert_eq!(0, std::fs::read_dir(&album_path).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn check_integrity_originals()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.get_photo_from_id(library.import_photo("tests/files/test_photo_light.jpg").unwrap()).unwrap();
+		let second = library.get_photo_from_id(library.import_photo("tests/files/test_photo.jpg").unwrap()).unwrap();
+		std::fs::remove_file(path.join("pictures").join(first.get_filename())).unwrap();
+		let original = path.join("pictures").join(second.get_filename());
+		let mut content = std::fs::read(&original).unwrap();
+		content[1000] ^= 0xFF;
+		std::fs::write(&original, &content).unwrap();
+		// Only the original is changed, its thumbnail is not stale
+		touch(&path.join("thumbnails").join("grid").join(second.get_filename()));
+
+		let expected = vec![
+			IntegrityIssue::MissingOriginal(first.id()),
+			IntegrityIssue::CorruptedOriginal { id: second.id(), expected: second.hash(), found: xxhash_rust::xxh3::xxh3_128(&content) },
+		];
+		assert_eq!(expected, library.check_integrity().unwrap());
+		assert_eq!(expected, library.repair().unwrap());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn check_integrity_journal_leftovers()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.delete_photo_by_id(photo).unwrap();
+		// A finished operation leaves nothing in the journal
+		assert_eq!(Vec::<IntegrityIssue>::new(), library.check_integrity().unwrap());
+
+		// Files of an operation interrupted before its end
+		std::fs::create_dir_all(path.join("journal").join("1234-0")).unwrap();
+		std::fs::write(path.join("journal").join("1234-0").join("0"), "removed").unwrap();
+		let expected = vec![IntegrityIssue::LeftoverJournal(PathBuf::from("journal/1234-0"))];
+		assert_eq!(expected, library.check_integrity().unwrap());
+		// They are left for the user to look at
+		assert_eq!(expected, library.repair().unwrap());
+		assert!(path.join("journal").join("1234-0").join("0").exists());
+		super::remove_test_path(path);
+	}
+}
@@ -105,4 +105,75 @@ mod tests
 		loaded_library.get_photo_from_id(1).unwrap();
 		super::remove_test_path(path);
 	}
+
+	#[test]
+	fn library_load_not_found()
+	{
+		let path = super::generate_test_path();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+	}
+
+	#[test]
+	fn library_load_not_a_library()
+	{
+		let path = super::generate_test_path();
+		std::fs::create_dir(&path).unwrap();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::InternalError(Error::NotALibrary));
+		assert!(!path.join("database.db").exists());
+		assert_eq!(Library::load("tests/files/test_photo.jpg").err().unwrap(), OsplError::InternalError(Error::NotALibrary));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn library_load_missing_directory()
+	{
+		let path = super::generate_test_path();
+		Library::create(&path).unwrap();
+		std::fs::remove_dir(path.join("thumbnails")).unwrap();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::InternalError(Error::CorruptedLibrary));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn library_load_corrupted_database()
+	{
+		let path = super::generate_test_path();
+		Library::create(&path).unwrap();
+		std::fs::copy("tests/files/not_an_image.odt", path.join("database.db")).unwrap();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::InternalError(Error::CorruptedLibrary));
+
+		// A valid sqlite database without the tables of a library
+		std::fs::remove_file(path.join("database.db")).unwrap();
+		Connection::open(path.join("database.db")).unwrap().execute_batch("CREATE TABLE other (id INTEGER)").unwrap();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::InternalError(Error::CorruptedLibrary));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn library_load_locked()
+	{
+		let path = super::generate_test_path();
+		Library::create(&path).unwrap();
+		let connection = Connection::open(path.join("database.db")).unwrap();
+		connection.execute_batch("BEGIN EXCLUSIVE").unwrap();
+		assert_eq!(Library::load(&path).err().unwrap(), OsplError::InternalError(Error::LibraryLocked));
+		connection.execute_batch("ROLLBACK").unwrap();
+		Library::load(&path).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn library_database_wal()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let conn = Connection::open(path.join("database.db")).unwrap();
+		let mode: String = conn.pragma_query_value(None, "journal_mode", |row| row.get(0)).unwrap();
+		assert_eq!(mode, "wal");
+		// Another connection can read while the library is open
+		library.create_collection("2019", "").unwrap();
+		let count: u32 = conn.query_row("SELECT COUNT(*) FROM collections", (), |row| row.get(0)).unwrap();
+		assert_eq!(count, 1);
+		super::remove_test_path(path);
+	}
 }
@@ -101,4 +101,51 @@ mod tests
 		assert_eq!(photos.get(1).unwrap().filename(), "test_photo_light.jpg");
 		super::remove_test_path(path);
 	}
+
+	/// Times the listings on a library of 100k photos, run it with `cargo test --release -- --ignored`
+	#[test]
+	#[ignore]
+	fn listing_benchmark()
+	{
+		const PHOTOS: u32 = 100_000;
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let mut albums = Vec::new();
+		for i in 0..10
+		{
+			albums.push(library.create_album(&format!("album_{}", i), "", collection.id()).unwrap().id());
+		}
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let france = library.get_tag_from_path("Places/France").unwrap();
+
+		// Importing real files would take hours, the rows are written directly
+		let connection = rusqlite::Connection::open(path.join("database.db")).unwrap();
+		connection.execute_batch(&format!("BEGIN; \
+			INSERT INTO photos (filename, hash, import_datetime, height, width, format) \
+			WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {photos}) \
+			SELECT 'photo_' || i || '.jpg', randomblob(16), '2019-08-01T10:00:00', 3000, 4000, 'jpg' FROM n; \
+			INSERT INTO photos_albums_map (containing_album, contained_photo) \
+			SELECT {first_album} + id % 10, id FROM photos; \
+			INSERT INTO photos_tags_map (containing_tag, contained_photo) \
+			SELECT {paris}, id FROM photos WHERE id % 4 = 0; \
+			COMMIT;", photos = PHOTOS, first_album = albums[0], paris = paris.id())).unwrap();
+		drop(connection);
+
+		let time = |name: &str, f: &dyn Fn() -> usize|
+		{
+			let start = std::time::Instant::now();
+			let count = f();
+			println!("{}: {} elements in {:?}", name, count, start.elapsed());
+			count
+		};
+		assert_eq!(PHOTOS as usize, time("list_all_photos", &|| library.list_all_photos().unwrap().len()));
+		assert_eq!(PHOTOS as usize, time("list_all_thumbnails", &|| library.list_all_thumbnails().unwrap().len()));
+		assert_eq!(PHOTOS as usize / 10, time("list_photos_in_album", &|| library.list_photos_in_album(albums[3]).unwrap().len()));
+		assert_eq!(PHOTOS as usize / 4, time("list_photos_with_tag", &|| library.list_photos_with_tag(france.id()).unwrap().len()));
+		assert_eq!(10, time("list_albums_in_collection", &|| library.list_albums_in_collection(collection.id()).unwrap().len()));
+		assert_eq!(3, time("list_all_tags", &|| library.list_all_tags().unwrap().len()));
+		assert_eq!(10_000, time("get_photo_from_id x 10000", &|| (1..=10_000).filter(|id| library.get_photo_from_id(*id).is_ok()).count()));
+		super::remove_test_path(path);
+	}
 }
@@ -0,0 +1,99 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use chrono::NaiveDate;
+
+	#[test]
+	fn import_reads_exif()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		let taken = NaiveDate::from_ymd_opt(2021, 6, 18).unwrap().and_hms_opt(14, 18, 14).unwrap();
+		assert_eq!(photo.creation_datetime(), Some(taken));
+		assert_eq!(photo.make(), Some("Canon".to_string()));
+		assert_eq!(photo.model(), Some("Canon EOS 70D".to_string()));
+		assert_eq!(photo.lens(), Some("EF50mm f/1.4 USM".to_string()));
+		assert_eq!(photo.aperture(), Some(2.2));
+		assert_eq!(photo.focal_length(), Some(50.0));
+		assert_eq!(photo.exposure_time(), Some("1/8000".to_string()));
+		assert_eq!(photo.exposure_mode(), Some(1));
+		assert_eq!(photo.sensitivity(), Some(100));
+		assert_eq!(photo.flash(), Some(16));
+		assert_eq!(photo.metering_mode(), Some(2));
+		assert_eq!(photo.orientation(), Some(1));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_reads_dimensions_and_format()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!(photo.width(), Some(1368));
+		assert_eq!(photo.height(), Some(912));
+		assert_eq!(photo.format(), Some("jpg".to_string()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_reads_lens_from_maker_note()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		// The LensModel (0xA434) and LensSpecification (0xA432) entries get unknown tags,
+		// like a camera that only writes its lens in the Canon MakerNote
+		let mut bytes = std::fs::read("tests/files/test_photo_light.jpg").unwrap();
+		for (entry, hidden) in [([0x34, 0xA4, 0x02, 0x00], 0xF0), ([0x32, 0xA4, 0x05, 0x00], 0xF1)]
+		{
+			let position = bytes.windows(4).position(|window| window == entry).unwrap();
+			bytes[position] = hidden;
+		}
+		let jpg_path = path.with_extension("jpg");
+		std::fs::write(&jpg_path, bytes).unwrap();
+		let id = library.import_photo(&jpg_path).unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!(photo.lens(), Some("EF50mm f/1.4 USM".to_string()));
+		assert_eq!(photo.model(), Some("Canon EOS 70D".to_string()));
+		std::fs::remove_file(jpg_path).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_without_exif()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let png_path = path.with_extension("png");
+		image::RgbImage::new(40, 30).save(&png_path).unwrap();
+		let id = library.import_photo(&png_path).unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!(photo.width(), Some(40));
+		assert_eq!(photo.height(), Some(30));
+		assert_eq!(photo.format(), Some("png".to_string()));
+		assert_eq!(photo.make(), None);
+		assert_eq!(photo.creation_datetime(), None);
+		std::fs::remove_file(png_path).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn list_all_photos_with_metadata()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let photos = library.list_all_photos().unwrap();
+		assert_eq!(photos[0].model(), Some("Canon EOS 70D".to_string()));
+		assert_eq!(photos[0].width(), Some(1368));
+		super::remove_test_path(path);
+	}
+}
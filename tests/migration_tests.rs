@@ -0,0 +1,158 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError, Error};
+	use ospl::api::integrity::IntegrityIssue;
+	use rusqlite::Connection;
+	use std::path::{Path, PathBuf};
+
+	static LIBRARY_V0: &str = include_str!("files/library_v0.sql");
+	/// Name of the photo of the fixture in pictures/
+	static PHOTO_V0: &str = "2022-06-01_10-00-00-000000000_test_photo_light.jpg";
+
+	/// Builds a library directory around a database created from `sql`
+	fn library_from_fixture(sql: &str) -> PathBuf
+	{
+		let path = super::generate_test_path();
+		for directory in ["pictures", "thumbnails", "collections/2019/summer"]
+		{
+			std::fs::create_dir_all(path.join(directory)).unwrap();
+		}
+		// Before the presets, the thumbnail of a photo had its name at the root of thumbnails/
+		std::fs::copy("tests/files/test_photo_light.jpg", path.join("pictures").join(PHOTO_V0)).unwrap();
+		std::fs::copy("tests/files/test_photo_light.jpg", path.join("thumbnails").join(PHOTO_V0)).unwrap();
+		Connection::open(path.join("database.db")).unwrap().execute_batch(sql).unwrap();
+		path
+	}
+
+	fn user_version(path: &Path) -> u32
+	{
+		let conn = Connection::open(path.join("database.db")).unwrap();
+		conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+	}
+
+	#[test]
+	fn create_library_latest_version()
+	{
+		let path = super::generate_test_path();
+		Library::create(&path).unwrap();
+		assert_eq!(user_version(&path), 10);
+		Library::load(&path).unwrap();
+		assert_eq!(user_version(&path), 10);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn load_library_v0()
+	{
+		let path = library_from_fixture(LIBRARY_V0);
+		assert_eq!(user_version(&path), 0);
+		let library = Library::load(&path).unwrap();
+		assert_eq!(user_version(&path), 10);
+
+		let photo = library.get_photo_from_id(1).unwrap();
+		assert_eq!(photo.filename(), "test_photo_light.jpg");
+		assert_eq!(photo.perceptual_hash(), None);
+		assert_eq!(photo.display_width(), Some(1920));
+		assert_eq!(photo.display_height(), Some(1080));
+		let collections = library.list_all_collections().unwrap();
+		assert_eq!(collections.len(), 1);
+		assert_eq!(collections[0].name(), "2019");
+		let albums = library.list_albums_in_collection(collections[0].id()).unwrap();
+		assert_eq!(albums.len(), 1);
+		assert_eq!(library.list_photos_in_album(albums[0].id()).unwrap().len(), 1);
+		// Photos imported before the trash are not in it
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		assert!(library.list_trash().unwrap().is_empty());
+
+		let tags: Vec<String> = library.list_all_tags().unwrap().iter().map(|tag| tag.path()).collect();
+		assert_eq!(tags, vec!["beach", "sunset"]);
+		assert_eq!(library.list_tags_of_photo(1).unwrap()[0].name(), "beach");
+		// Elements created before the search index are found
+		assert_eq!(library.search_text("beach").unwrap().len(), 1);
+		assert_eq!(library.search_text("test_photo_light").unwrap()[0].id, 1);
+
+		// The old thumbnail is replaced by a grid thumbnail
+		assert!(!path.join("thumbnails").join(PHOTO_V0).exists());
+		let thumbnail = library.list_all_thumbnails().unwrap()[0].1.clone();
+		assert_eq!(thumbnail, path.join("thumbnails").join("grid").join(PHOTO_V0));
+		assert!(thumbnail.exists());
+		assert!(!library.check_integrity().unwrap().iter().any(|issue| matches!(issue,
+			IntegrityIssue::OrphanFile(_) | IntegrityIssue::MissingThumbnail(_) | IntegrityIssue::StaleThumbnail(_))));
+
+		// Features of the new schema work on the migrated library
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		assert_eq!(paris.path(), "Places/France/Paris");
+		library.create_tag("Places/beach").unwrap();
+		let child = library.create_sub_collection("august", "", collections[0].id()).unwrap();
+		assert_eq!(library.list_collections_in_collection(collections[0].id()).unwrap()[0].id(), child.id());
+		library.create_sub_collection("2019", "", collections[0].id()).unwrap();
+		assert_eq!(library.search_text("august").unwrap()[0].id, child.id());
+
+		// Loading it again does not run the migrations twice
+		Library::load(&path).unwrap();
+		assert_eq!(user_version(&path), 10);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn load_library_v0_display_dimensions()
+	{
+		let rotated = "INSERT INTO photos (id, filename, hash, import_datetime, height, width, orientation) \
+			VALUES (2, 'portrait.jpg', X'00000000000000000000000000000002', '2022-06-01 10:00:00', 3000, 4000, '6');";
+		let path = library_from_fixture(&format!("{}{}", LIBRARY_V0, rotated));
+		let library = Library::load(&path).unwrap();
+		let photo = library.get_photo_from_id(2).unwrap();
+		assert_eq!((Some(4000), Some(3000)), (photo.width(), photo.height()));
+		assert_eq!((Some(3000), Some(4000)), (photo.display_width(), photo.display_height()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn load_library_v0_dangling_links()
+	{
+		// Written by a version of ospl that did not enforce the foreign keys
+		let dangling = "PRAGMA foreign_keys = OFF; INSERT INTO photos_albums_map (containing_album, contained_photo) VALUES (1, 99); \
+			INSERT INTO photos_tags_map (containing_tag, contained_photo) VALUES (99, 1);";
+		let path = library_from_fixture(&format!("{}{}", LIBRARY_V0, dangling));
+		let library = Library::load(&path).unwrap();
+		let issues = library.check_integrity().unwrap();
+		assert!(!issues.iter().any(|issue| matches!(issue, IntegrityIssue::DanglingAlbumLink { .. } | IntegrityIssue::DanglingTagLink { .. })));
+		assert_eq!(library.list_photos_in_album(1).unwrap().len(), 1);
+		// The links now go away with the album
+		library.delete_album_by_id(1).unwrap();
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn load_library_v0_duplicate_links()
+	{
+		let duplicate = "INSERT INTO photos_albums_map (containing_album, contained_photo) VALUES (1, 1);";
+		let path = library_from_fixture(&format!("{}{}", LIBRARY_V0, duplicate));
+		let library = Library::load(&path).unwrap();
+		assert_eq!(library.list_photos_in_album(1).unwrap().len(), 1);
+		library.remove_photo_from_album(1, 1).unwrap();
+		assert!(library.list_photos_in_album(1).unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn load_library_too_new()
+	{
+		let path = super::generate_test_path();
+		Library::create(&path).unwrap();
+		Connection::open(path.join("database.db")).unwrap().pragma_update(None, "user_version", 999).unwrap();
+		match Library::load(&path)
+		{
+			Err(OsplError::InternalError(Error::LibraryTooNew)) => {},
+			other => panic!("expected LibraryTooNew, got {:?}", other.map(|library| library.get_path())),
+		}
+		assert_eq!(user_version(&path), 999);
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,152 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::query::{PhotoQuery, PhotoSort, SortOrder};
+	use ospl::api::rating::{ColorLabel, PickFlag};
+	use chrono::NaiveDate;
+	use std::path::{Path, PathBuf};
+
+	/// Library with a photo taken by a Canon EOS 70D on 2021-06-18,
+	/// and two PNG files without EXIF data named "sunset.png" and "Another sunset.png"
+	fn library_with_photos(path: &Path) -> (Library, u32, u32, u32)
+	{
+		let library = Library::create(path).unwrap();
+		let canon = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let files = path.with_extension("files");
+		std::fs::create_dir_all(&files).unwrap();
+		let mut ids = Vec::new();
+		for (name, color) in [("sunset.png", 0u8), ("Another sunset.png", 255u8)]
+		{
+			let file: PathBuf = files.join(name);
+			image::RgbImage::from_pixel(40, 30, image::Rgb([color, 100, 50])).save(&file).unwrap();
+			ids.push(library.import_photo(&file).unwrap());
+		}
+		std::fs::remove_dir_all(files).unwrap();
+		(library, canon, ids[0], ids[1])
+	}
+
+	fn search(library: &Library, query: &PhotoQuery) -> Vec<u32>
+	{
+		library.search_photos(query).unwrap().iter().map(|photo| photo.id()).collect()
+	}
+
+	fn search_text(library: &Library, text: &str) -> Vec<u32>
+	{
+		search(library, &PhotoQuery::parse(text).unwrap())
+	}
+
+	#[test]
+	fn query_builder()
+	{
+		let path = super::generate_test_path();
+		let (library, canon, sunset, another) = library_with_photos(&path);
+		library.set_rating(canon, 5).unwrap();
+		library.set_rating(another, 3).unwrap();
+		library.set_starred(sunset, true).unwrap();
+		library.set_color_label(another, Some(ColorLabel::Red)).unwrap();
+		library.set_flag(canon, PickFlag::Picked).unwrap();
+
+		assert_eq!(vec![canon, sunset, another], search(&library, &PhotoQuery::new()));
+		assert_eq!(vec![canon, another], search(&library, &PhotoQuery::new().min_rating(3)));
+		assert_eq!(vec![another], search(&library, &PhotoQuery::new().min_rating(1).max_rating(4)));
+		assert_eq!(vec![sunset], search(&library, &PhotoQuery::new().starred(true)));
+		assert_eq!(vec![another], search(&library, &PhotoQuery::new().color_label(ColorLabel::Red)));
+		assert_eq!(vec![sunset, another], search(&library, &PhotoQuery::new().flag(PickFlag::Unflagged)));
+		assert_eq!(vec![canon], search(&library, &PhotoQuery::new().camera("eos 70d").lens("50mm")));
+		assert_eq!(vec![sunset, another], search(&library, &PhotoQuery::new().format("PNG")));
+		assert_eq!(vec![sunset, another], search(&library, &PhotoQuery::new().text("SUNSET")));
+		let june = NaiveDate::from_ymd_opt(2021, 6, 18);
+		assert_eq!(vec![canon], search(&library, &PhotoQuery::new().taken_between(june, june)));
+		assert!(search(&library, &PhotoQuery::new().taken_between(None, NaiveDate::from_ymd_opt(2021, 6, 17))).is_empty());
+		assert!(search(&library, &PhotoQuery::new().starred(true).min_rating(1)).is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn tags_and_albums()
+	{
+		let path = super::generate_test_path();
+		let (library, canon, sunset, another) = library_with_photos(&path);
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let france = library.get_tag_from_path("Places/France").unwrap();
+		let beach = library.create_tag("beach").unwrap();
+		library.tag_photos(&[canon], paris.id()).unwrap();
+		library.tag_photos(&[sunset], france.id()).unwrap();
+		library.tag_photos(&[sunset, another], beach.id()).unwrap();
+		library.create_tag("Places/Franceville").unwrap();
+		let collection = library.create_collection("2021", "").unwrap();
+		let album = library.create_album("Pizza Party", "", collection.id()).unwrap();
+		library.assign_photo_to_album(another, album.id()).unwrap();
+
+		assert_eq!(vec![canon, sunset], search(&library, &PhotoQuery::new().tag("Places/France")));
+		assert_eq!(vec![canon], search(&library, &PhotoQuery::new().tag("Places/France/Paris")));
+		assert!(search(&library, &PhotoQuery::new().tag("Places/Fra")).is_empty());
+		assert_eq!(vec![sunset], search(&library, &PhotoQuery::new().tag("Places").tag("beach")));
+		assert_eq!(vec![another], search(&library, &PhotoQuery::new().album(album.id())));
+		assert_eq!(vec![another], search_text(&library, "album:\"Pizza Party\""));
+		assert_eq!(vec![sunset, another], search_text(&library, "tag:beach"));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn text_query()
+	{
+		let path = super::generate_test_path();
+		let (library, canon, sunset, another) = library_with_photos(&path);
+		library.set_rating(canon, 5).unwrap();
+		library.set_rating(another, 3).unwrap();
+		library.set_starred(sunset, true).unwrap();
+		library.set_color_label(another, Some(ColorLabel::Green)).unwrap();
+		library.set_flag(sunset, PickFlag::Rejected).unwrap();
+
+		assert_eq!(vec![canon, another], search_text(&library, "rating>=3"));
+		assert_eq!(vec![canon], search_text(&library, "rating>3"));
+		assert_eq!(vec![sunset], search_text(&library, "rating<3"));
+		assert_eq!(vec![another], search_text(&library, "rating:3"));
+		assert_eq!(vec![sunset], search_text(&library, "starred:yes flag:rejected"));
+		assert_eq!(vec![another], search_text(&library, "label:green"));
+		assert_eq!(vec![canon], search_text(&library, "camera:\"Canon EOS\" format:jpg"));
+		assert_eq!(vec![canon], search_text(&library, "date:2021-05..2021-06"));
+		assert_eq!(vec![canon], search_text(&library, "date:2021"));
+		assert!(search_text(&library, "date:2021-06-19..").is_empty());
+		assert!(search_text(&library, "date:..2021-05").is_empty());
+		assert_eq!(vec![another], search_text(&library, "another sunset"));
+		assert_eq!(vec![sunset, another], search_text(&library, "sunset sort:-name"));
+
+		let invalid = Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		for text in ["color:red", "rating>=x", "date:2021-13", "flag:maybe", "sort:size", "camera:\"X-T4", "tag:"]
+		{
+			assert_eq!(invalid, PhotoQuery::parse(text), "{}", text);
+		}
+		assert_eq!(PhotoQuery::new(), PhotoQuery::parse("  ").unwrap());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn sorting_and_pagination()
+	{
+		let path = super::generate_test_path();
+		let (library, canon, sunset, another) = library_with_photos(&path);
+		library.set_rating(sunset, 4).unwrap();
+		library.set_rating(another, 2).unwrap();
+
+		let by_rating = PhotoQuery::new().sort_by(PhotoSort::Rating, SortOrder::Descending);
+		assert_eq!(vec![sunset, another, canon], search(&library, &by_rating));
+		assert_eq!(vec![another], search(&library, &by_rating.clone().offset(1).limit(1)));
+		assert_eq!(vec![another, canon], search(&library, &by_rating.clone().offset(1)));
+		assert_eq!(3, library.count_photos(&by_rating.clone().limit(1)).unwrap());
+
+		// Photos without a date are last, in both orders
+		let by_date = PhotoQuery::new().sort_by(PhotoSort::CreationDate, SortOrder::Descending);
+		assert_eq!(vec![canon, another, sunset], search(&library, &by_date));
+		assert_eq!(vec![another, sunset, canon], search_text(&library, "sort:name"));
+		assert_eq!(vec![another, sunset, canon], search_text(&library, "sort:-id"));
+		assert_eq!(2, library.count_photos(&PhotoQuery::parse("format:png").unwrap()).unwrap());
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,97 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::rating::{ColorLabel, PickFlag};
+
+	fn ids(photos: Vec<ospl::element::photo::Photo>) -> Vec<u32>
+	{
+		photos.iter().map(|photo| photo.id()).collect()
+	}
+
+	#[test]
+	fn rating_and_starred()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let photo = library.get_photo_from_id(first).unwrap();
+		assert_eq!((0, false), (photo.rating(), photo.starred()));
+
+		library.set_rating(first, 3).unwrap();
+		library.set_rating(second, 5).unwrap();
+		library.set_starred(second, true).unwrap();
+		assert_eq!(3, library.get_photo_from_id(first).unwrap().rating());
+		assert!(library.get_photo_from_id(second).unwrap().starred());
+		assert_eq!(vec![second, first], ids(library.list_photos_with_rating(1).unwrap()));
+		assert_eq!(vec![second], ids(library.list_photos_with_rating(4).unwrap()));
+		assert_eq!(vec![second], ids(library.list_starred_photos().unwrap()));
+
+		library.set_rating(first, 0).unwrap();
+		library.set_starred(second, false).unwrap();
+		assert_eq!(vec![second], ids(library.list_photos_with_rating(1).unwrap()));
+		assert!(library.list_starred_photos().unwrap().is_empty());
+
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)), library.set_rating(first, 6));
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::NotFound)), library.set_starred(second + 1, true));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn color_labels_and_flags()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let photo = library.get_photo_from_id(first).unwrap();
+		assert_eq!((None, PickFlag::Unflagged), (photo.color_label(), photo.flag()));
+
+		library.set_color_label(first, Some(ColorLabel::Purple)).unwrap();
+		library.set_flag(second, PickFlag::Rejected).unwrap();
+		assert_eq!(Some(ColorLabel::Purple), library.get_photo_from_id(first).unwrap().color_label());
+		assert_eq!(PickFlag::Rejected, library.get_photo_from_id(second).unwrap().flag());
+		assert_eq!(vec![first], ids(library.list_photos_with_color_label(ColorLabel::Purple).unwrap()));
+		assert!(library.list_photos_with_color_label(ColorLabel::Red).unwrap().is_empty());
+		assert_eq!(vec![second], ids(library.list_photos_with_flag(PickFlag::Rejected).unwrap()));
+		assert_eq!(vec![first], ids(library.list_photos_with_flag(PickFlag::Unflagged).unwrap()));
+
+		library.set_color_label(first, None).unwrap();
+		library.set_flag(second, PickFlag::Picked).unwrap();
+		assert_eq!(None, library.get_photo_from_id(first).unwrap().color_label());
+		assert_eq!(vec![second], ids(library.list_photos_with_flag(PickFlag::Picked).unwrap()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn bulk_setters()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+
+		library.set_rating_of_photos(&[first, second], 4).unwrap();
+		library.set_starred_of_photos(&[first, second], true).unwrap();
+		library.set_color_label_of_photos(&[first, second], Some(ColorLabel::Blue)).unwrap();
+		library.set_flag_of_photos(&[first, second], PickFlag::Picked).unwrap();
+		for photo in library.list_all_photos().unwrap()
+		{
+			assert_eq!(4, photo.rating());
+			assert!(photo.starred());
+			assert_eq!(Some(ColorLabel::Blue), photo.color_label());
+			assert_eq!(PickFlag::Picked, photo.flag());
+		}
+
+		// Nothing changes when one of the photos does not exist
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::NotFound)), library.set_rating_of_photos(&[first, second + 1], 1));
+		assert_eq!(4, library.get_photo_from_id(first).unwrap().rating());
+		library.set_flag_of_photos(&[], PickFlag::Rejected).unwrap();
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,91 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::api::search::{SearchHit, SearchHitKind};
+
+	fn found(hits: &[SearchHit]) -> Vec<(SearchHitKind, u32)>
+	{
+		hits.iter().map(|hit| (hit.kind, hit.id)).collect()
+	}
+
+	#[test]
+	fn search_every_element()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.set_photo_title(photo, "Été à la plage").unwrap();
+		library.set_photo_comment(photo, "The pizza was cold").unwrap();
+		let collection = library.create_collection("Holidays", "Pizza and beaches").unwrap();
+		let album = library.create_album("Pizza Party", "", collection.id()).unwrap();
+		let tag = library.create_tag("Food/pizza").unwrap();
+
+		let hits = library.search_text("pizza").unwrap();
+		assert_eq!(4, hits.len());
+		// Names count more than titles and comments, short names more than long ones
+		assert_eq!(vec![(SearchHitKind::Tag, tag.id()), (SearchHitKind::Album, album.id())], found(&hits[..2]));
+		assert!(found(&hits).contains(&(SearchHitKind::Collection, collection.id())));
+		assert!(found(&hits).contains(&(SearchHitKind::Photo, photo)));
+		assert_eq!("<mark>Pizza</mark> Party", hits[1].snippet);
+
+		// Prefixes, without case nor accents
+		let hits = library.search_text("ete PLAG").unwrap();
+		assert_eq!(vec![(SearchHitKind::Photo, photo)], found(&hits));
+		assert_eq!("<mark>Été</mark> à la <mark>plage</mark>", hits[0].snippet);
+		assert_eq!(vec![(SearchHitKind::Photo, photo)], found(&library.search_text("test_photo").unwrap()));
+		assert!(library.search_text("pizza plage party").unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn index_follows_changes()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("Holidays", "").unwrap();
+		let album = library.create_album("Pizza Party", "", collection.id()).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+
+		library.rename_album_with_id(album.id(), "Barbecue").unwrap();
+		library.rename_tag_with_id(tag.id(), "seaside").unwrap();
+		library.set_photo_title(photo, "Barbecue at the seaside").unwrap();
+		assert!(library.search_text("pizza").unwrap().is_empty());
+		assert!(library.search_text("beach").unwrap().is_empty());
+		assert_eq!(vec![(SearchHitKind::Album, album.id()), (SearchHitKind::Photo, photo)],
+			found(&library.search_text("barbecue").unwrap()));
+		assert_eq!(vec![(SearchHitKind::Tag, tag.id()), (SearchHitKind::Photo, photo)],
+			found(&library.search_text("seaside").unwrap()));
+
+		library.set_photo_title(photo, "").unwrap();
+		library.delete_album_by_id(album.id()).unwrap();
+		library.delete_tag_by_id(tag.id()).unwrap();
+		library.delete_collection_by_id(collection.id()).unwrap();
+		assert!(library.search_text("barbecue").unwrap().is_empty());
+		assert!(library.search_text("seaside").unwrap().is_empty());
+		assert!(library.search_text("holidays").unwrap().is_empty());
+		library.delete_photo_by_id(photo).unwrap();
+		assert!(library.search_text("test_photo").unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn query_is_not_fts_syntax()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		library.create_collection("Summer \"2019\"", "NOT AND OR").unwrap();
+		for query in ["\"", "summer OR", "NOT", "-", "(2019", "*", "title:summer", "   "]
+		{
+			library.search_text(query).unwrap();
+		}
+		assert_eq!(1, library.search_text("summer \"2019").unwrap().len());
+		assert_eq!(1, library.search_text("not or").unwrap().len());
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,77 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use std::path::{Path, PathBuf};
+
+	/// Saves the light test photo again as a smaller PNG, like an exported copy
+	fn save_png_copy(path: &Path) -> PathBuf
+	{
+		let png_path = path.with_extension("copy.png");
+		let img = image::open("tests/files/test_photo_light.jpg").unwrap();
+		img.thumbnail(684, 456).save(&png_path).unwrap();
+		png_path
+	}
+
+	/// Saves a picture that does not look like the test photos
+	fn save_other_picture(path: &Path) -> PathBuf
+	{
+		let png_path = path.with_extension("other.png");
+		image::RgbImage::from_fn(90, 80, |x, y| image::Rgb([((x * y) % 256) as u8, (x % 7 * 30) as u8, 0]))
+			.save(&png_path).unwrap();
+		png_path
+	}
+
+	#[test]
+	fn perceptual_hash_stored_at_import()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert!(photo.perceptual_hash().is_some());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn find_similar_photos()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let copy = save_png_copy(&path);
+		let other = save_other_picture(&path);
+		let original_id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let copy_id = library.import_photo(&copy).unwrap();
+		library.import_photo(&other).unwrap();
+
+		let similar = library.find_similar_photos(original_id, 6).unwrap();
+		assert_eq!(vec![copy_id], similar.iter().map(|p| p.id()).collect::<Vec<u32>>());
+		assert_eq!(2, library.find_similar_photos(original_id, 64).unwrap().len());
+		std::fs::remove_file(copy).unwrap();
+		std::fs::remove_file(other).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn find_similar_photo_groups()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let copy = save_png_copy(&path);
+		let other = save_other_picture(&path);
+		let original_id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.import_photo(&other).unwrap();
+		let copy_id = library.import_photo(&copy).unwrap();
+
+		let groups = library.find_similar_photo_groups(6).unwrap();
+		assert_eq!(1, groups.len());
+		assert_eq!(vec![original_id, copy_id], groups[0].iter().map(|p| p.id()).collect::<Vec<u32>>());
+		std::fs::remove_file(copy).unwrap();
+		std::fs::remove_file(other).unwrap();
+		super::remove_test_path(path);
+	}
+}
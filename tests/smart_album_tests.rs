@@ -0,0 +1,233 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::query::{PhotoQuery, PhotoSort, SortOrder};
+	use chrono::NaiveDate;
+	use std::path::Path;
+
+	fn ids(photos: Vec<ospl::element::photo::Photo>) -> Vec<u32>
+	{
+		photos.iter().map(|photo| photo.id()).collect()
+	}
+
+	/// Writes a small JPEG taken at `latitude`, `longitude`
+	fn write_photo_with_gps(path: &Path, latitude: f64, longitude: f64, color: u8)
+	{
+		let img = image::RgbImage::from_pixel(16, 16, image::Rgb([color, 0, 0]));
+		let mut jpeg = Vec::new();
+		image::codecs::jpeg::JpegEncoder::new(&mut jpeg).encode_image(&img).unwrap();
+
+		let degrees = |value: f64| exif::Value::Rational(vec![
+			exif::Rational::from((value.abs() as u32, 1)),
+			exif::Rational::from(((value.abs().fract() * 60.0) as u32, 1)),
+			exif::Rational::from((((value.abs() * 3600.0) % 60.0 * 100.0).round() as u32, 100)),
+		]);
+		let reference = |text: &str| exif::Value::Ascii(vec![text.as_bytes().to_vec()]);
+		let fields = [
+			(exif::Tag::GPSLatitudeRef, reference(if latitude < 0.0 { "S" } else { "N" })),
+			(exif::Tag::GPSLatitude, degrees(latitude)),
+			(exif::Tag::GPSLongitudeRef, reference(if longitude < 0.0 { "W" } else { "E" })),
+			(exif::Tag::GPSLongitude, degrees(longitude)),
+		].map(|(tag, value)| exif::Field { tag, ifd_num: exif::In::PRIMARY, value });
+		let mut writer = exif::experimental::Writer::new();
+		for field in &fields
+		{
+			writer.push_field(field);
+		}
+		let mut tiff = std::io::Cursor::new(Vec::new());
+		writer.write(&mut tiff, false).unwrap();
+		let tiff = tiff.into_inner();
+
+		let mut file = jpeg[..2].to_vec();
+		file.extend([0xFF, 0xE1]);
+		file.extend(((tiff.len() + 8) as u16).to_be_bytes());
+		file.extend(b"Exif\0\0");
+		file.extend(tiff);
+		file.extend(&jpeg[2..]);
+		std::fs::write(path, file).unwrap();
+	}
+
+	#[test]
+	fn smart_album()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let canon = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let other = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2021", "").unwrap();
+		let query = PhotoQuery::new().min_rating(4).sort_by(PhotoSort::Rating, SortOrder::Descending);
+		let smart = library.create_smart_album("Best", "", collection.id(), &query).unwrap();
+		let regular = library.create_album("Chosen", "", collection.id()).unwrap();
+
+		assert!(smart.is_smart());
+		assert_eq!(Some(query), library.get_album_from_id(smart.id()).unwrap().query());
+		assert!(!regular.is_smart());
+		assert_eq!(None, regular.query());
+		assert!(library.list_photos_in_album(smart.id()).unwrap().is_empty());
+
+		// Evaluated every time the photos are listed
+		library.set_rating(canon, 4).unwrap();
+		library.set_rating(other, 5).unwrap();
+		assert_eq!(vec![other, canon], ids(library.list_photos_in_album(smart.id()).unwrap()));
+		library.set_rating(other, 1).unwrap();
+		assert_eq!(vec![canon], ids(library.list_photos_in_album(smart.id()).unwrap()));
+
+		library.set_smart_album_query(smart.id(), &PhotoQuery::parse("rating<=1").unwrap()).unwrap();
+		assert_eq!(vec![other], ids(library.list_photos_in_album(smart.id()).unwrap()));
+
+		// Listed with the other albums of the collection
+		let albums = library.list_albums_in_collection(collection.id()).unwrap();
+		let smart_albums: Vec<bool> = albums.iter().map(|album| album.is_smart()).collect();
+		assert_eq!(vec![true, false], smart_albums);
+		assert_eq!(Some("rating<=1".to_string()), albums[0].query().map(|query| query.to_text()));
+
+		let invalid = Err(OsplError::IoError(std::io::ErrorKind::InvalidInput));
+		assert_eq!(invalid, library.assign_photo_to_album(canon, smart.id()));
+		assert_eq!(invalid, library.set_smart_album_query(regular.id(), &PhotoQuery::new()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn convert_to_static()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let canon = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let other = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2021", "").unwrap();
+		let smart = library.create_smart_album("Starred", "", collection.id(), &PhotoQuery::new().starred(true)).unwrap();
+		library.set_starred(canon, true).unwrap();
+
+		library.convert_smart_album_to_static(smart.id()).unwrap();
+		let album = library.get_album_from_id(smart.id()).unwrap();
+		assert!(!album.is_smart());
+		let photo = library.get_photo_from_id(canon).unwrap();
+		assert!(path.join("collections").join("2021").join("Starred").join(photo.get_filename()).exists());
+
+		// The snapshot does not follow the query anymore
+		library.set_starred(other, true).unwrap();
+		library.set_starred(canon, false).unwrap();
+		assert_eq!(vec![canon], ids(library.list_photos_in_album(smart.id()).unwrap()));
+		library.assign_photo_to_album(other, smart.id()).unwrap();
+		library.convert_smart_album_to_static(smart.id()).unwrap();
+		assert_eq!(vec![canon, other], ids(library.list_photos_in_album(smart.id()).unwrap()));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn query_text_round_trip()
+	{
+		let query = PhotoQuery::new()
+			.tag("Places/Côte d'Azur")
+			.album(3)
+			.album_named("Pizza Party")
+			.min_rating(2)
+			.max_rating(4)
+			.starred(false)
+			.color_label(ospl::api::rating::ColorLabel::Yellow)
+			.flag(ospl::api::rating::PickFlag::Unflagged)
+			.camera("X-T4")
+			.lens("XF 23mm")
+			.format("heic")
+			.taken_between(NaiveDate::from_ymd_opt(2023, 6, 1), NaiveDate::from_ymd_opt(2023, 8, 31))
+			.within_area(43.5, 6.9, 43.8, 7.5)
+			.text("sunset")
+			.sort_by(PhotoSort::ImportDate, SortOrder::Descending);
+		assert_eq!(query, PhotoQuery::parse(&query.to_text()).unwrap());
+		assert_eq!("", PhotoQuery::new().limit(10).to_text());
+		assert_eq!("tag:beach rating>=4 sort:-date", PhotoQuery::parse("tag:beach   rating>=4 sort:-date").unwrap().to_text());
+	}
+
+	#[test]
+	fn query_text_round_trip_special_values()
+	{
+		let values = ["IMG:1", "rating>3", "a=b", "<tag>", "say \"cheese\"", "back\\slash", "\"", "", "sort:-date", "two words"];
+		for value in values
+		{
+			let queries = [
+				PhotoQuery::new().tag(value),
+				PhotoQuery::new().album_named(value),
+				PhotoQuery::new().camera(value),
+				PhotoQuery::new().lens(value),
+				PhotoQuery::new().format(value),
+				PhotoQuery::new().text(value),
+			];
+			for query in queries
+			{
+				assert_eq!(query, PhotoQuery::parse(&query.to_text()).unwrap(), "{}", query.to_text());
+			}
+		}
+		let query = PhotoQuery::new().text("IMG:1").text("rating>3").min_rating(1).max_rating(0).starred(true)
+			.flag(ospl::api::rating::PickFlag::Picked).taken_between(NaiveDate::from_ymd_opt(1, 1, 1), None)
+			.within_area(-90.0, 179.5, 90.0, -179.5);
+		assert_eq!(query, PhotoQuery::parse(&query.to_text()).unwrap());
+		assert_eq!(PhotoQuery::parse("\"tag:beach\"").unwrap(), PhotoQuery::new().text("tag:beach"));
+		assert_eq!(PhotoQuery::parse("camera:\"X\\\"T\"").unwrap(), PhotoQuery::new().camera("X\"T"));
+		assert!(PhotoQuery::parse("\"unfinished\\\"").is_err());
+	}
+
+	#[test]
+	fn smart_album_special_values()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_smart_album("ids", "", collection.id(), &PhotoQuery::new().text("IMG:1")).unwrap();
+		assert!(library.list_photos_in_album(album.id()).unwrap().is_empty());
+		library.set_smart_album_query(album.id(), &PhotoQuery::new().text("rating>3")).unwrap();
+		assert!(library.list_photos_in_album(album.id()).unwrap().is_empty());
+		library.set_smart_album_query(album.id(), &PhotoQuery::new().text("test_photo")).unwrap();
+		assert_eq!(ids(library.list_photos_in_album(album.id()).unwrap()), vec![photo]);
+
+		// Values the text syntax cannot hold are refused instead of stored
+		assert_eq!(library.create_smart_album("nowhere", "", collection.id(), &PhotoQuery::new().within_area(95.0, 0.0, 96.0, 1.0)).err(),
+			Some(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.set_smart_album_query(album.id(), &PhotoQuery::new().within_area(f64::NAN, 0.0, 1.0, 1.0)),
+			Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(ids(library.list_photos_in_album(album.id()).unwrap()), vec![photo]);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn gps_area()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let files = path.with_extension("files");
+		std::fs::create_dir_all(&files).unwrap();
+		let mut photos = Vec::new();
+		for (index, (latitude, longitude)) in [(48.8584, 2.2945), (-33.8568, 151.2153), (64.1466, -21.9426), (-17.5, 179.5)].into_iter().enumerate()
+		{
+			let file = files.join(format!("{}.jpg", index));
+			write_photo_with_gps(&file, latitude, longitude, index as u8 * 60);
+			photos.push(library.import_photo(&file).unwrap());
+		}
+		std::fs::remove_dir_all(files).unwrap();
+		let canon = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+
+		let paris = library.get_photo_from_id(photos[0]).unwrap();
+		assert!((paris.latitude().unwrap() - 48.8584).abs() < 0.0001);
+		assert!((paris.longitude().unwrap() - 2.2945).abs() < 0.0001);
+		let reykjavik = library.get_photo_from_id(photos[2]).unwrap();
+		assert!((reykjavik.longitude().unwrap() + 21.9426).abs() < 0.0001);
+		assert_eq!(None, library.get_photo_from_id(canon).unwrap().latitude());
+
+		let search = |query: &PhotoQuery| ids(library.search_photos(query).unwrap());
+		assert_eq!(vec![photos[0]], search(&PhotoQuery::new().within_area(48.81, 2.22, 48.91, 2.47)));
+		assert_eq!(vec![photos[0], photos[2]], search(&PhotoQuery::parse("area:0,-30..70,10").unwrap()));
+		// Around the 180th meridian, from 170° east to 170° west
+		assert_eq!(vec![photos[3]], search(&PhotoQuery::new().within_area(-20.0, 170.0, -10.0, -170.0)));
+		assert!(PhotoQuery::parse("area:91,0..92,1").is_err());
+
+		let collection = library.create_collection("Travels", "").unwrap();
+		let south = library.create_smart_album("South", "", collection.id(), &PhotoQuery::parse("area:-90,-180..0,180").unwrap()).unwrap();
+		assert_eq!(vec![photos[1], photos[3]], ids(library.list_photos_in_album(south.id()).unwrap()));
+		super::remove_test_path(path);
+	}
+}
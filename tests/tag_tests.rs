@@ -0,0 +1,291 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::Error;
+	use ospl::OsplError;
+
+	#[test]
+	fn create_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		assert_eq!(1, tag.id());
+		assert_eq!("beach", tag.name());
+		let tag = library.get_tag_from_id(tag.id()).unwrap();
+		assert_eq!("beach", tag.name());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn create_tag_empty_name()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		assert_eq!(library.create_tag("").err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn get_unexisting_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		assert_eq!(library.get_tag_from_id(4).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn rename_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		library.rename_tag_with_id(tag.id(), "seaside").unwrap();
+		assert_eq!("seaside", library.get_tag_from_id(tag.id()).unwrap().name());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn rename_tag_conflicts()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let beach = library.create_tag("Places/beach").unwrap();
+		let seaside = library.create_tag("Places/seaside").unwrap();
+		library.create_tag("mountain").unwrap();
+		assert_eq!(library.rename_tag_with_id(seaside.id(), "beach").err().unwrap(), OsplError::IoError(std::io::ErrorKind::AlreadyExists));
+		assert_eq!(library.rename_tag_with_id(seaside.id(), "  ").err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		// Only the siblings count, and a tag keeps its own name
+		library.rename_tag_with_id(seaside.id(), " mountain ").unwrap();
+		assert_eq!("Places/mountain", library.get_tag_from_id(seaside.id()).unwrap().path());
+		library.rename_tag_with_id(beach.id(), "beach").unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn list_all_tags()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		library.create_tag("mountain").unwrap();
+		library.create_tag("beach").unwrap();
+		let tags = library.list_all_tags().unwrap();
+		assert_eq!(2, tags.len());
+		assert_eq!("beach", tags[0].name());
+		assert_eq!("mountain", tags[1].name());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn tag_and_untag_photos()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let beach = library.create_tag("beach").unwrap();
+		let sunset = library.create_tag("sunset").unwrap();
+
+		library.tag_photos(&[id_0, id_1], beach.id()).unwrap();
+		library.tag_photos(&[id_0, id_0], beach.id()).unwrap();
+		library.tag_photos(&[id_1], sunset.id()).unwrap();
+		let photos = library.list_photos_with_tag(beach.id()).unwrap();
+		assert_eq!(2, photos.len());
+		assert_eq!(id_0, photos[0].id());
+		assert_eq!(id_1, photos[1].id());
+		let tags = library.list_tags_of_photo(id_1).unwrap();
+		assert_eq!(vec!["beach", "sunset"], tags.iter().map(|t| t.name()).collect::<Vec<String>>());
+
+		library.untag_photos(&[id_0], beach.id()).unwrap();
+		let photos = library.list_photos_with_tag(beach.id()).unwrap();
+		assert_eq!(1, photos.len());
+		assert_eq!(id_1, photos[0].id());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn tag_unexisting_photo()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		assert_eq!(library.tag_photos(&[12], tag.id()).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert!(library.list_photos_with_tag(tag.id()).unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn merge_tags()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let seaside = library.create_tag("seaside").unwrap();
+		let beach = library.create_tag("beach").unwrap();
+		library.tag_photos(&[id_0, id_1], seaside.id()).unwrap();
+		library.tag_photos(&[id_0], beach.id()).unwrap();
+
+		library.merge_tags(seaside.id(), beach.id()).unwrap();
+		assert_eq!(library.get_tag_from_id(seaside.id()).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		let photos = library.list_photos_with_tag(beach.id()).unwrap();
+		assert_eq!(2, photos.len());
+		assert_eq!(1, library.list_tags_of_photo(id_0).unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		library.tag_photos(&[id], tag.id()).unwrap();
+		library.delete_tag_by_id(tag.id()).unwrap();
+		assert_eq!(library.get_tag_from_id(tag.id()).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert!(library.list_tags_of_photo(id).unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn create_tag_path()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let lyon = library.create_tag("Places/France/Lyon").unwrap();
+		assert_eq!("Paris", paris.name());
+		assert_eq!("Places/France/Paris", paris.path());
+		assert_eq!("Places|France|Paris", paris.hierarchical_subject());
+		let france = library.get_tag_from_path("Places/France").unwrap();
+		assert_eq!(Some(france.id()), paris.parent());
+		assert_eq!(Some(france.id()), lyon.parent());
+		assert_eq!(4, library.list_all_tags().unwrap().len());
+		let children = library.list_tag_children(france.id()).unwrap();
+		assert_eq!(vec!["Lyon", "Paris"], children.iter().map(|t| t.name()).collect::<Vec<String>>());
+		assert_eq!(library.create_tag("Places/France").err().unwrap(), OsplError::IoError(std::io::ErrorKind::AlreadyExists));
+		assert_eq!(library.create_tag("Places//Paris").err().unwrap(), OsplError::InternalError(Error::EmptyName));
+		assert_eq!(library.get_tag_from_path("Places/Spain").err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn same_name_under_different_parents()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let france = library.create_tag("France/Paris").unwrap();
+		let texas = library.create_tag("Texas/Paris").unwrap();
+		assert_ne!(france.id(), texas.id());
+		assert_eq!(library.rename_tag_with_id(texas.id(), "Paris/Texas").err().unwrap(), OsplError::InternalError(Error::InvalidName));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn list_photos_with_parent_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let lyon = library.create_tag("Places/France/Lyon").unwrap();
+		library.tag_photos(&[id_0], paris.id()).unwrap();
+		library.tag_photos(&[id_0, id_1], lyon.id()).unwrap();
+
+		let places = library.get_tag_from_path("Places").unwrap();
+		let photos = library.list_photos_with_tag(places.id()).unwrap();
+		assert_eq!(vec![id_0, id_1], photos.iter().map(|p| p.id()).collect::<Vec<u32>>());
+		assert_eq!(1, library.list_photos_with_tag(paris.id()).unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn move_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let places = library.get_tag_from_path("Places").unwrap();
+		let cities = library.create_tag("Cities").unwrap();
+		library.move_tag_by_id(paris.id(), Some(cities.id())).unwrap();
+		assert_eq!("Cities/Paris", library.get_tag_from_id(paris.id()).unwrap().path());
+		library.move_tag_by_id(paris.id(), None).unwrap();
+		assert_eq!("Paris", library.get_tag_from_id(paris.id()).unwrap().path());
+		let france = library.get_tag_from_path("Places/France").unwrap();
+		assert_eq!(library.move_tag_by_id(places.id(), Some(france.id())).err().unwrap(), OsplError::InternalError(Error::MovedInsideItself));
+		// Another Paris is already at the root
+		let other = library.create_tag("Cities/Paris").unwrap();
+		assert_eq!(library.move_tag_by_id(other.id(), None).err().unwrap(), OsplError::IoError(std::io::ErrorKind::AlreadyExists));
+		assert_eq!("Cities/Paris", library.get_tag_from_id(other.id()).unwrap().path());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_parent_tag()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		library.create_tag("Beach").unwrap();
+		let places = library.get_tag_from_path("Places").unwrap();
+		library.delete_tag_by_id(places.id()).unwrap();
+		assert_eq!(library.get_tag_from_id(paris.id()).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert_eq!(1, library.list_all_tags().unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn merge_parent_tags()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let paris = library.create_tag("Places/France/Paris").unwrap();
+		let france = library.get_tag_from_path("Places/France").unwrap();
+		let europe = library.create_tag("Europe").unwrap();
+		library.merge_tags(france.id(), europe.id()).unwrap();
+		assert_eq!("Europe/Paris", library.get_tag_from_id(paris.id()).unwrap().path());
+		let places = library.get_tag_from_path("Places").unwrap();
+		library.merge_tags(places.id(), paris.id()).unwrap();
+		let europe = library.get_tag_from_path("Europe").unwrap();
+		assert_eq!(library.merge_tags(europe.id(), paris.id()).err().unwrap(), OsplError::InternalError(Error::MovedInsideItself));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn merge_tags_with_same_children()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id_0 = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let id_1 = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let source = library.create_tag("A/Beach/Sunset").unwrap();
+		let source_beach = library.get_tag_from_path("A/Beach").unwrap();
+		let only_source = library.create_tag("A/Beach/Waves").unwrap();
+		let target = library.create_tag("B/Beach/Sunset").unwrap();
+		let target_beach = library.get_tag_from_path("B/Beach").unwrap();
+		library.tag_photos(&[id_0], source_beach.id()).unwrap();
+		library.tag_photos(&[id_0, id_1], source.id()).unwrap();
+		library.tag_photos(&[id_1], target.id()).unwrap();
+
+		let a = library.get_tag_from_path("A").unwrap();
+		let b = library.get_tag_from_path("B").unwrap();
+		library.merge_tags(a.id(), b.id()).unwrap();
+		let paths: Vec<String> = library.list_all_tags().unwrap().iter().map(|tag| tag.path()).collect();
+		assert_eq!(paths, vec!["B", "B/Beach", "B/Beach/Sunset", "B/Beach/Waves"]);
+		assert_eq!(library.get_tag_from_id(only_source.id()).unwrap().path(), "B/Beach/Waves");
+		assert!(library.get_tag_from_id(source.id()).is_err());
+		let in_beach: Vec<u32> = library.list_photos_with_tag(target_beach.id()).unwrap().iter().map(|photo| photo.id()).collect();
+		assert!(in_beach.contains(&id_0));
+		let tags: Vec<u32> = library.list_tags_of_photo(id_1).unwrap().iter().map(|tag| tag.id()).collect();
+		assert_eq!(tags, vec![target.id()]);
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,199 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError, Error};
+	use ospl::api::integrity::IntegrityIssue;
+	use ospl::api::thumbnail::{ThumbnailFormat, ThumbnailPreset, ThumbnailSettings};
+
+	static PHOTO: &str = "tests/files/test_photo_light.jpg";
+
+	/// Writes a 60x20 JPEG, red on the left and blue on the right,
+	/// with an EXIF orientation saying it must be turned clockwise to be shown
+	fn write_rotated_photo(path: &std::path::Path)
+	{
+		let img = image::RgbImage::from_fn(60, 20, |x, _| if x < 30 { image::Rgb([255, 0, 0]) } else { image::Rgb([0, 0, 255]) });
+		let mut jpeg = Vec::new();
+		image::codecs::jpeg::JpegEncoder::new(&mut jpeg).encode_image(&img).unwrap();
+
+		let orientation = exif::Field { tag: exif::Tag::Orientation, ifd_num: exif::In::PRIMARY, value: exif::Value::Short(vec![6]) };
+		let mut writer = exif::experimental::Writer::new();
+		writer.push_field(&orientation);
+		let mut tiff = std::io::Cursor::new(Vec::new());
+		writer.write(&mut tiff, false).unwrap();
+		let tiff = tiff.into_inner();
+
+		// APP1 segment right after the start of image marker
+		let mut file = jpeg[..2].to_vec();
+		file.extend([0xFF, 0xE1]);
+		file.extend(((tiff.len() + 8) as u16).to_be_bytes());
+		file.extend(b"Exif\0\0");
+		file.extend(tiff);
+		file.extend(&jpeg[2..]);
+		std::fs::write(path, file).unwrap();
+	}
+
+	#[test]
+	fn grid_thumbnail_on_import()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let (width, height) = image::image_dimensions(PHOTO).unwrap();
+
+		let thumbnail = library.get_thumbnail(id, ThumbnailPreset::Grid).unwrap();
+		assert_eq!(thumbnail, library.list_all_thumbnails().unwrap()[0].1);
+		assert!(thumbnail.starts_with(path.join("thumbnails").join("grid")));
+		assert_eq!(image::ImageFormat::Jpeg, image::ImageFormat::from_path(&thumbnail).unwrap());
+		let (thumbnail_width, thumbnail_height) = image::image_dimensions(&thumbnail).unwrap();
+		assert_eq!(325, thumbnail_height);
+		assert_eq!((width * 325 + height / 2) / height, thumbnail_width);
+		assert!(!path.join("thumbnails").join("preview").exists());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn lazy_preset()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		assert!(preview.starts_with(path.join("thumbnails").join("preview")));
+		let (width, height) = image::image_dimensions(&preview).unwrap();
+		assert!(width <= 1280 && height <= 1280);
+		// Served from the cache the second time
+		let modified = std::fs::metadata(&preview).unwrap().modified().unwrap();
+		assert_eq!(preview, library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap());
+		assert_eq!(modified, std::fs::metadata(&preview).unwrap().modified().unwrap());
+
+		library.delete_photo_by_id(id).unwrap();
+		assert!(!preview.exists());
+		assert_eq!(0, std::fs::read_dir(path.join("thumbnails").join("grid")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn thumbnail_settings()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		assert_eq!(ThumbnailPreset::Preview.default_settings(), library.thumbnail_settings(ThumbnailPreset::Preview).unwrap());
+
+		let jpeg = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		let settings = ThumbnailSettings { max_width: 200, max_height: 100, format: ThumbnailFormat::WebP, quality: 100 };
+		library.set_thumbnail_settings(ThumbnailPreset::Preview, settings).unwrap();
+		assert!(!jpeg.exists());
+		let webp = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		assert_eq!(image::ImageFormat::WebP, image::ImageFormat::from_path(&webp).unwrap());
+		let (width, height) = image::image_dimensions(&webp).unwrap();
+		assert!(width <= 200 && height <= 100);
+
+		// Settings are saved in the library
+		drop(library);
+		let library = Library::load(&path).unwrap();
+		assert_eq!(settings, library.thumbnail_settings(ThumbnailPreset::Preview).unwrap());
+
+		let invalid = ThumbnailSettings { max_width: 200, max_height: 0, format: ThumbnailFormat::Jpeg, quality: 80 };
+		assert_eq!(Err(OsplError::InternalError(Error::InvalidSettings)),
+			library.set_thumbnail_settings(ThumbnailPreset::Grid, invalid));
+		// WebP thumbnails are lossless, a lower quality would be ignored
+		let lossy = ThumbnailSettings { max_width: 200, max_height: 100, format: ThumbnailFormat::WebP, quality: 80 };
+		assert_eq!(Err(OsplError::InternalError(Error::InvalidSettings)),
+			library.set_thumbnail_settings(ThumbnailPreset::Preview, lossy));
+		assert_eq!(settings, library.thumbnail_settings(ThumbnailPreset::Preview).unwrap());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn grid_settings_render_again()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo(PHOTO).unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+
+		let settings = ThumbnailSettings { max_width: u32::MAX, max_height: 100, format: ThumbnailFormat::Jpeg, quality: 50 };
+		library.set_thumbnail_settings(ThumbnailPreset::Grid, settings).unwrap();
+		for id in [first, second]
+		{
+			let thumbnail = library.get_thumbnail(id, ThumbnailPreset::Grid).unwrap();
+			assert_eq!(100, image::image_dimensions(thumbnail).unwrap().1);
+		}
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn stale_thumbnails()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+
+		// Thumbnails rendered from another version of the photo
+		let connection = rusqlite::Connection::open(path.join("database.db")).unwrap();
+		connection.execute("UPDATE photos SET thumbnail_hash = 'outdated' WHERE id = ?1", [id]).unwrap();
+		assert_eq!(vec![IntegrityIssue::StaleThumbnail(id)], library.check_integrity().unwrap());
+
+		std::fs::write(&preview, "outdated").unwrap();
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+		image::image_dimensions(&preview).unwrap();
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn regenerate_thumbnails()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo(PHOTO).unwrap();
+		let grid = library.get_thumbnail(id, ThumbnailPreset::Grid).unwrap();
+		let preview = library.get_thumbnail(id, ThumbnailPreset::Preview).unwrap();
+
+		std::fs::remove_file(&grid).unwrap();
+		library.regenerate_thumbnail(id).unwrap();
+		assert!(grid.exists());
+		assert!(!preview.exists());
+
+		std::fs::remove_file(&grid).unwrap();
+		library.regenerate_all_thumbnails().unwrap();
+		assert!(grid.exists());
+		assert_eq!(Err(OsplError::IoError(std::io::ErrorKind::NotFound)), library.regenerate_thumbnail(id + 1));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn exif_orientation()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let file = path.with_extension("jpg");
+		write_rotated_photo(&file);
+		let id = library.import_photo(&file).unwrap();
+
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!(Some(6), photo.orientation());
+		assert_eq!((Some(60), Some(20)), (photo.width(), photo.height()));
+		assert_eq!((Some(20), Some(60)), (photo.display_width(), photo.display_height()));
+
+		for preset in [ThumbnailPreset::Grid, ThumbnailPreset::Preview]
+		{
+			let thumbnail = image::open(library.get_thumbnail(id, preset).unwrap()).unwrap().to_rgb8();
+			assert_eq!((20, 60), thumbnail.dimensions());
+			// The left of the file is shown at the top
+			let top = thumbnail.get_pixel(10, 5);
+			let bottom = thumbnail.get_pixel(10, 55);
+			assert!(top[0] > 200 && top[2] < 50, "top is {:?}", top);
+			assert!(bottom[2] > 200 && bottom[0] < 50, "bottom is {:?}", bottom);
+		}
+		std::fs::remove_file(file).unwrap();
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,284 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::OsplError;
+	use ospl::failpoint;
+	use ospl::api::import::{DuplicatePolicy, ImportDestination, ImportOptions};
+	use ospl::api::library::DeletePolicy;
+	use ospl::api::edit::Edit;
+	use ospl::api::thumbnail::ThumbnailPreset;
+	use std::path::{Path, PathBuf};
+
+	/// Lists every file and directory of the library except the journal, sorted
+	fn list_tree(path: &Path, files: &mut Vec<PathBuf>)
+	{
+		for entry in std::fs::read_dir(path).unwrap()
+		{
+			let entry = entry.unwrap().path();
+			if entry.file_name().unwrap() == "journal"
+			{
+				continue;
+			}
+			files.push(entry.clone());
+			if entry.is_dir()
+			{
+				list_tree(&entry, files);
+			}
+		}
+		files.sort();
+	}
+
+	/// Describes everything in the library, in the database and on the disk
+	fn state(library: &Library, path: &Path) -> String
+	{
+		let mut files = Vec::new();
+		list_tree(path, &mut files);
+		let photos: Vec<(u32, String)> = library.list_all_photos().unwrap().iter().map(|p| (p.id(), p.get_filename())).collect();
+		let trash: Vec<u32> = library.list_trash().unwrap().iter().map(|p| p.id()).collect();
+		let mut albums = Vec::new();
+		for collection in library.list_all_collections().unwrap()
+		{
+			for album in library.list_albums_in_collection(collection.id()).unwrap()
+			{
+				let inside: Vec<u32> = library.list_photos_in_album(album.id()).unwrap().iter().map(|p| p.id()).collect();
+				albums.push((album.id(), album.name(), album.collection_id(), inside));
+			}
+		}
+		let collections: Vec<(u32, String, Option<u32>)> = library.list_all_collections().unwrap().iter()
+			.map(|c| (c.id(), c.name(), c.parent_id())).collect();
+		format!("{:#?}\n{:?}\n{:?}\n{:?}\n{:?}", files, photos, trash, albums, collections)
+	}
+
+	/// Makes the operation fail at each step and checks nothing changed, then runs it for real
+	fn assert_atomic<F>(library: &Library, path: &Path, steps: &[&str], operation: F)
+	where
+		F: Fn(&Library) -> Result<(), OsplError>,
+	{
+		for step in steps
+		{
+			let before = state(library, path);
+			failpoint::set(step);
+			let result = operation(library);
+			failpoint::clear();
+			assert!(result.is_err(), "{} did not fail", step);
+			assert_eq!(before, state(library, path), "{} was not rolled back", step);
+		}
+		operation(library).unwrap();
+	}
+
+	#[test]
+	fn import_photo_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		assert_atomic(&library, &path, &["import_photo.duplicates", "import_photo.thumbnail", "import_photo.album", "import_photo.commit"],
+			|library| library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::Album(album.id())).map(|_| ()));
+		assert_eq!(1, library.list_photos_in_album(album.id()).unwrap().len());
+		// the duplicate is only put in another album
+		let other = library.create_album("best", "", collection.id()).unwrap();
+		assert_atomic(&library, &path, &["import_photo.duplicates", "import_photo.commit"],
+			|library| library.import_photo_into("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip, ImportDestination::Album(other.id())).map(|_| ()));
+		assert_eq!(1, library.list_all_photos().unwrap().len());
+		assert_eq!(1, library.list_photos_in_album(other.id()).unwrap().len());
+		// the album named after the folder is only created with the photo
+		assert_atomic(&library, &path, &["import_photo.duplicates", "import_photo.thumbnail", "import_photo.album", "import_photo.commit"],
+			|library| library.import_photo_into("tests/files/test_photo.jpg", DuplicatePolicy::Skip, ImportDestination::NewAlbum(collection.id())).map(|_| ()));
+		assert!(path.join("collections").join("2019").join("files").is_dir());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = path.with_extension("source");
+		std::fs::create_dir_all(&source).unwrap();
+		std::fs::copy("tests/files/test_photo_light.jpg", source.join("photo.jpg")).unwrap();
+		let before = state(&library, &path);
+		for step in ["import_photo.duplicates", "import_photo.thumbnail", "import_photo.commit"]
+		{
+			failpoint::set(step);
+			let report = library.import_directory(&source, &ImportOptions::default(), |_| {}).unwrap();
+			failpoint::clear();
+			assert_eq!(1, report.failed.len(), "{} did not fail", step);
+			assert_eq!(before, state(&library, &path), "{} was not rolled back", step);
+		}
+		let report = library.import_directory(&source, &ImportOptions::default(), |_| {}).unwrap();
+		assert_eq!(1, report.imported.len());
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_directory_error_removes_copies()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let source = path.with_extension("source");
+		std::fs::create_dir_all(&source).unwrap();
+		for i in 0..8
+		{
+			std::fs::copy("tests/files/test_photo_light.jpg", source.join(format!("photo_{}.jpg", i))).unwrap();
+		}
+		// The second batch cannot start while the workers have copied the next photos
+		let options = ImportOptions { duplicates: DuplicatePolicy::ImportAnyway, batch_size: 1, workers: 4, ..Default::default() };
+		failpoint::set("import_directory.batch");
+		let result = library.import_directory(&source, &options, |_| {});
+		failpoint::clear();
+		assert_eq!(OsplError::InternalError(ospl::Error::Other), result.err().unwrap());
+		assert_eq!(1, library.list_all_photos().unwrap().len());
+		assert_eq!(1, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		assert!(library.check_integrity().unwrap().is_empty());
+		std::fs::remove_dir_all(source).unwrap();
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn album_operations_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.create_collection("2019", "").unwrap();
+		let second = library.create_collection("2020", "").unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+
+		assert_atomic(&library, &path, &["create_album.filesystem", "create_album.commit"],
+			|library| library.create_album("summer", "", first.id()).map(|_| ()));
+		let album = library.list_albums_in_collection(first.id()).unwrap()[0].id();
+		assert_atomic(&library, &path, &["assign_photo_to_album.filesystem", "assign_photo_to_album.commit"],
+			|library| library.assign_photo_to_album(photo, album));
+		assert_atomic(&library, &path, &["remove_photo_from_album.filesystem", "remove_photo_from_album.commit"],
+			|library| library.remove_photo_from_album(photo, album));
+		assert!(library.list_photos_in_album(album).unwrap().is_empty());
+		library.assign_photo_to_album(photo, album).unwrap();
+		assert_atomic(&library, &path, &["rename_album.database", "rename_album.commit"],
+			|library| library.rename_album_with_id(album, "winter"));
+		assert_atomic(&library, &path, &["move_album.database", "move_album.commit"],
+			|library| library.move_album_by_id(album, second.id()));
+		assert!(path.join("collections").join("2020").join("winter").exists());
+
+		// an album that still contains photos is only deleted when asked to
+		let before = state(&library, &path);
+		assert!(library.delete_album_with_policy(album, DeletePolicy::Refuse).is_err());
+		assert_eq!(before, state(&library, &path));
+
+		let autumn = library.create_album("autumn", "", second.id()).unwrap().id();
+		assert_atomic(&library, &path, &["delete_album.reassign", "delete_album.database", "delete_album.commit"],
+			|library| library.delete_album_with_policy(album, DeletePolicy::Reassign(autumn)));
+		assert!(!path.join("collections").join("2020").join("winter").exists());
+		assert_eq!(1, library.list_photos_in_album(autumn).unwrap().len());
+		assert_atomic(&library, &path, &["delete_album.database", "delete_album.commit"],
+			|library| library.delete_album_by_id(autumn));
+		assert!(!path.join("collections").join("2020").join("autumn").exists());
+		assert_eq!(1, library.list_all_photos().unwrap().len());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn collection_operations_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		assert_atomic(&library, &path, &["create_collection.filesystem", "create_collection.commit"],
+			|library| library.create_collection("Travels", "").map(|_| ()));
+		let travels = library.list_all_collections().unwrap()[0].id();
+		assert_atomic(&library, &path, &["create_collection.filesystem", "create_collection.commit"],
+			|library| library.create_sub_collection("Japan", "", travels).map(|_| ()));
+		let japan = library.list_collections_in_collection(travels).unwrap()[0].id();
+		let tokyo = library.create_album("Tokyo", "", japan).unwrap();
+		assert_atomic(&library, &path, &["rename_collection.database", "rename_collection.commit"],
+			|library| library.rename_collection_with_id(japan, "Nihon"));
+		assert_atomic(&library, &path, &["move_collection.database", "move_collection.commit"],
+			|library| library.move_collection_by_id(japan, None));
+		assert!(path.join("collections").join("Nihon").join("Tokyo").exists());
+		assert_atomic(&library, &path, &["delete_collection.reassign", "delete_collection.database", "delete_collection.commit"],
+			|library| library.delete_collection_with_policy(japan, DeletePolicy::Reassign(travels)));
+		assert!(!path.join("collections").join("Nihon").exists());
+		assert!(path.join("collections").join("Travels").join("Tokyo").exists());
+		assert_atomic(&library, &path, &["delete_collection.database", "delete_collection.commit"],
+			|library| library.delete_collection_by_id(travels));
+		assert!(!path.join("collections").join("Travels").exists());
+		assert!(library.get_album_from_id(tokyo.id()).is_err());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn delete_photo_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		assert_atomic(&library, &path, &["delete_photo.thumbnail", "delete_photo.albums", "delete_photo.database", "delete_photo.commit"],
+			|library| library.delete_photo_by_id(photo));
+		assert!(library.list_all_photos().unwrap().is_empty());
+		assert_eq!(0, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		assert_eq!(1, std::fs::read_dir(path.join("trash")).unwrap().count());
+		assert_eq!(0, std::fs::read_dir(path.join("journal")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn restore_from_trash_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		library.delete_photo_by_id(photo).unwrap();
+		assert_atomic(&library, &path, &["restore_from_trash.albums", "restore_from_trash.database", "restore_from_trash.commit"],
+			|library| library.restore_from_trash(photo));
+		assert_eq!(1, library.list_photos_in_album(album.id()).unwrap().len());
+		assert_eq!(0, std::fs::read_dir(path.join("trash")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn set_edits_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let thumbnail = library.get_thumbnail(photo, ThumbnailPreset::Grid).unwrap();
+		let original = std::fs::read(&thumbnail).unwrap();
+		for step in ["set_edits.thumbnail", "set_edits.commit"]
+		{
+			failpoint::set(step);
+			let result = library.add_edit(photo, Edit::Rotate(1));
+			failpoint::clear();
+			assert!(result.is_err(), "{} did not fail", step);
+			assert!(library.list_edits_of_photo(photo).unwrap().is_empty(), "{} was not rolled back", step);
+			assert_eq!(original, std::fs::read(&thumbnail).unwrap(), "{} was not rolled back", step);
+		}
+		library.add_edit(photo, Edit::Rotate(1)).unwrap();
+		assert_ne!(original, std::fs::read(&thumbnail).unwrap());
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn empty_trash_rollback()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.delete_photo_by_id(photo).unwrap();
+		assert_atomic(&library, &path, &["empty_trash.commit"], |library| library.empty_trash().map(|_| ()));
+		assert!(library.list_trash().unwrap().is_empty());
+		assert_eq!(0, std::fs::read_dir(path.join("trash")).unwrap().count());
+		assert_eq!(0, std::fs::read_dir(path.join("journal")).unwrap().count());
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,167 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::{Library, OsplError};
+	use ospl::api::trash::DEFAULT_TRASH_RETENTION;
+	use ospl::api::thumbnail::ThumbnailPreset;
+	use rusqlite::Connection;
+
+	#[test]
+	fn delete_and_restore()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(photo, album.id()).unwrap();
+		let tag = library.create_tag("beach").unwrap();
+		library.tag_photos(&[photo], tag.id()).unwrap();
+		library.set_rating(photo, 4).unwrap();
+		let filename = library.get_photo_from_id(photo).unwrap().get_filename();
+		let link = path.join("collections").join("2019").join("summer").join(&filename);
+		assert!(link.exists());
+
+		library.delete_photo_by_id(photo).unwrap();
+		assert!(library.list_all_photos().unwrap().is_empty());
+		assert!(library.list_photos_in_album(album.id()).unwrap().is_empty());
+		assert!(library.list_photos_with_tag(tag.id()).unwrap().is_empty());
+		assert!(library.list_photos_with_rating(1).unwrap().is_empty());
+		assert!(library.search_text("test_photo_light").unwrap().is_empty());
+		let trash = library.list_trash().unwrap();
+		assert_eq!(trash.len(), 1);
+		assert!(trash[0].is_in_trash());
+		assert!(trash[0].deleted_datetime().is_some());
+		assert!(path.join("trash").join(&filename).exists());
+		assert!(!path.join("pictures").join(&filename).exists());
+		assert!(!link.exists());
+		// Deleting it again does nothing
+		library.delete_photo_by_id(photo).unwrap();
+		assert_eq!(library.list_trash().unwrap().len(), 1);
+
+		library.restore_from_trash(photo).unwrap();
+		assert!(library.list_trash().unwrap().is_empty());
+		assert_eq!(library.list_all_photos().unwrap().len(), 1);
+		assert_eq!(library.list_photos_in_album(album.id()).unwrap()[0].id(), photo);
+		assert_eq!(library.list_photos_with_tag(tag.id()).unwrap()[0].id(), photo);
+		assert_eq!(library.get_photo_from_id(photo).unwrap().rating(), 4);
+		assert!(path.join("pictures").join(&filename).exists());
+		assert!(link.exists());
+		assert!(library.get_thumbnail(photo, ThumbnailPreset::Grid).unwrap().exists());
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn restore_photo_not_in_trash()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		assert_eq!(library.restore_from_trash(photo), Err(OsplError::IoError(std::io::ErrorKind::InvalidInput)));
+		assert_eq!(library.restore_from_trash(42), Err(OsplError::IoError(std::io::ErrorKind::NotFound)));
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn empty_trash()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let kept = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let deleted = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let collection = library.create_collection("2019", "").unwrap();
+		let album = library.create_album("summer", "", collection.id()).unwrap();
+		library.assign_photo_to_album(deleted, album.id()).unwrap();
+		library.delete_photo_by_id(deleted).unwrap();
+		assert!(library.check_integrity().unwrap().is_empty());
+
+		assert_eq!(library.empty_trash().unwrap(), 1);
+		assert!(library.list_trash().unwrap().is_empty());
+		assert_eq!(library.get_photo_from_id(deleted).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert_eq!(library.list_all_photos().unwrap()[0].id(), kept);
+		assert_eq!(0, std::fs::read_dir(path.join("trash")).unwrap().count());
+		assert!(library.check_integrity().unwrap().is_empty());
+		assert_eq!(library.empty_trash().unwrap(), 0);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn import_again_after_delete()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let deleted = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.delete_photo_by_id(deleted).unwrap();
+		let imported = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		assert_ne!(deleted, imported);
+		assert!(library.find_exact_duplicates().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn trash_retention()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		assert_eq!(library.trash_retention().unwrap(), Some(DEFAULT_TRASH_RETENTION));
+		library.set_trash_retention(Some(7)).unwrap();
+		assert_eq!(library.trash_retention().unwrap(), Some(7));
+		library.set_trash_retention(None).unwrap();
+		assert_eq!(library.trash_retention().unwrap(), None);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn purge_trash_huge_retention()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let photo = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		library.delete_photo_by_id(photo).unwrap();
+		library.set_trash_retention(Some(200_000_000)).unwrap();
+		assert_eq!(library.purge_trash().unwrap(), 0);
+		library.set_trash_retention(Some(u32::MAX)).unwrap();
+		assert_eq!(library.purge_trash().unwrap(), 0);
+		assert_eq!(library.list_trash().unwrap().len(), 1);
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn purge_trash()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let old = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let recent = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		library.delete_photo_by_id(old).unwrap();
+		library.delete_photo_by_id(recent).unwrap();
+		library.set_trash_retention(None).unwrap();
+		drop(library);
+		Connection::open(path.join("database.db")).unwrap()
+			.execute("UPDATE photos SET deleted_datetime = '2000-01-01 00:00:00' WHERE id = ?1", [old]).unwrap();
+
+		// Kept forever
+		let library = Library::load(&path).unwrap();
+		assert_eq!(library.purge_trash().unwrap(), 0);
+		assert_eq!(library.list_trash().unwrap().len(), 2);
+		library.set_trash_retention(Some(DEFAULT_TRASH_RETENTION)).unwrap();
+		drop(library);
+
+		// Loading the library does not delete anything
+		let library = Library::load(&path).unwrap();
+		assert_eq!(library.list_trash().unwrap().len(), 2);
+		assert_eq!(library.purge_trash().unwrap(), 1);
+		let trash = library.list_trash().unwrap();
+		assert_eq!(trash.len(), 1);
+		assert_eq!(trash[0].id(), recent);
+		assert_eq!(library.get_photo_from_id(old).err().unwrap(), OsplError::IoError(std::io::ErrorKind::NotFound));
+		assert_eq!(1, std::fs::read_dir(path.join("trash")).unwrap().count());
+		assert!(library.check_integrity().unwrap().is_empty());
+		super::remove_test_path(path);
+	}
+}
@@ -0,0 +1,63 @@
+mod test_tools;
+use test_tools::generate_test_path;
+use test_tools::remove_test_path;
+
+#[cfg(test)]
+mod tests
+{
+	use ospl::Library;
+	use ospl::api::import::DuplicatePolicy;
+	use ospl::api::photo::HashMismatch;
+	use xxhash_rust::xxh3::xxh3_128;
+
+	#[test]
+	fn streaming_hash_is_the_whole_file_hash()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		let photo = library.get_photo_from_id(id).unwrap();
+		assert_eq!(xxh3_128(&std::fs::read("tests/files/test_photo.jpg").unwrap()), photo.hash());
+		let copy = path.join("pictures").join(photo.get_filename());
+		assert_eq!(std::fs::read("tests/files/test_photo.jpg").unwrap(), std::fs::read(copy).unwrap());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn skipped_duplicate_leaves_no_file()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let id = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let again = library.import_photo_with_policy("tests/files/test_photo_light.jpg", DuplicatePolicy::Skip).unwrap();
+		assert_eq!(id, again);
+		assert_eq!(1, std::fs::read_dir(path.join("pictures")).unwrap().count());
+		assert_eq!(1, std::fs::read_dir(path.join("thumbnails").join("grid")).unwrap().count());
+		super::remove_test_path(path);
+	}
+
+	#[test]
+	fn verify_photos()
+	{
+		let path = super::generate_test_path();
+		let library = Library::create(&path).unwrap();
+		let first = library.import_photo("tests/files/test_photo_light.jpg").unwrap();
+		let second = library.import_photo("tests/files/test_photo.jpg").unwrap();
+		assert!(library.verify_photos().unwrap().is_empty());
+
+		let first = library.get_photo_from_id(first).unwrap();
+		let corrupted = path.join("pictures").join(first.get_filename());
+		let mut content = std::fs::read(&corrupted).unwrap();
+		content[1000] ^= 0xFF;
+		std::fs::write(&corrupted, &content).unwrap();
+		let second = library.get_photo_from_id(second).unwrap();
+		std::fs::remove_file(path.join("pictures").join(second.get_filename())).unwrap();
+
+		let mismatches = library.verify_photos().unwrap();
+		assert_eq!(vec![
+			HashMismatch { id: first.id(), expected: first.hash(), found: Some(xxh3_128(&content)) },
+			HashMismatch { id: second.id(), expected: second.hash(), found: None },
+		], mismatches);
+		super::remove_test_path(path);
+	}
+}